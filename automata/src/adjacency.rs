@@ -0,0 +1,249 @@
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::iter::{Cloned, Zip};
+use std::ops::Range;
+use std::slice;
+
+/// A compact store of the transitions of an automaton, in compressed sparse row form. The
+/// outgoing edges of each state are stored contiguously, so they can be iterated without hashing
+/// or allocating. The incoming edges of each state are indexed the same way. Automata keep their transitions in an adjacency, so searches and algorithms
+/// that walk the transitions use it directly.
+///
+/// An adjacency is frozen once built. Constructions collect the edges of an automaton and build
+/// its adjacency from them at once, and adding transitions to an automaton builds a new one.
+#[derive(Clone, Debug)]
+pub struct Adjacency<L> {
+    /// The outgoing edges of state `s` are the edges `offsets[s]..offsets[s + 1]`. States past
+    /// the end of the offsets have no edges.
+    offsets: Vec<usize>,
+    labels: Vec<L>,
+    targets: Vec<usize>,
+    /// The incoming edges of state `s` are `incoming[in_offsets[s]..in_offsets[s + 1]]`, each an
+    /// index into the labels paired with the source of the edge in `sources`.
+    in_offsets: Vec<usize>,
+    incoming: Vec<usize>,
+    sources: Vec<usize>,
+}
+
+impl<L> Adjacency<L> {
+    /// Build an adjacency for the states `0..total_states` from (source, label, target) triples.
+    /// The outgoing edges of each state keep the order in which they are given.
+    #[inline]
+    pub fn new<I>(total_states: usize, edges: I) -> Self
+    where
+        I: IntoIterator<Item = (usize, L, usize)>,
+    {
+        // A stable sort keeps the given order of the outgoing edges of each state.
+        let mut edges: Vec<_> = edges.into_iter().collect();
+        edges.sort_by_key(|&(source, _, _)| source);
+
+        let mut offsets = vec![0; total_states + 1];
+        for &(source, _, _) in &edges {
+            offsets[source + 1] += 1;
+        }
+        for s in 0..total_states {
+            offsets[s + 1] += offsets[s];
+        }
+
+        let mut in_offsets = vec![0; total_states + 1];
+        for &(_, _, target) in &edges {
+            in_offsets[target + 1] += 1;
+        }
+        for s in 0..total_states {
+            in_offsets[s + 1] += in_offsets[s];
+        }
+
+        // Placing the edges in order of their sources keeps the incoming edges in that order too.
+        let mut next = in_offsets.clone();
+        let mut incoming = vec![0; edges.len()];
+        let mut sources = vec![0; edges.len()];
+        let mut labels = Vec::with_capacity(edges.len());
+        let mut targets = Vec::with_capacity(edges.len());
+        for (edge, (source, label, target)) in edges.into_iter().enumerate() {
+            incoming[next[target]] = edge;
+            sources[next[target]] = source;
+            next[target] += 1;
+            labels.push(label);
+            targets.push(target);
+        }
+
+        Self {
+            offsets,
+            labels,
+            targets,
+            in_offsets,
+            incoming,
+            sources,
+        }
+    }
+
+    #[inline]
+    pub fn total_edges(&self) -> usize {
+        self.targets.len()
+    }
+
+    #[inline]
+    fn range(&self, state: usize) -> Range<usize> {
+        if state + 1 < self.offsets.len() {
+            self.offsets[state]..self.offsets[state + 1]
+        } else {
+            self.targets.len()..self.targets.len()
+        }
+    }
+
+    /// The labels of the outgoing edges of a state.
+    #[inline]
+    pub fn labels(&self, state: usize) -> &[L] {
+        &self.labels[self.range(state)]
+    }
+
+    /// The targets of the outgoing edges of a state, in the same order as [`Adjacency::labels`].
+    #[inline]
+    pub fn targets(&self, state: usize) -> &[usize] {
+        &self.targets[self.range(state)]
+    }
+
+    /// Iterate over the (label, target) pairs of the outgoing edges of a state.
+    #[inline]
+    pub fn outgoing(&self, state: usize) -> Outgoing<'_, L> {
+        self.labels(state)
+            .iter()
+            .zip(self.targets(state).iter().cloned())
+    }
+
+    /// Iterate over the (source, label, target) triples of every edge, in order of their sources.
+    #[inline]
+    pub fn edges(&self) -> Edges<'_, L> {
+        Edges {
+            adjacency: self,
+            source: 0,
+            edge: 0,
+        }
+    }
+
+    /// Iterate over the (label, source) pairs of the incoming edges of a state, in order of their
+    /// sources.
+    #[inline]
+    pub fn incoming(&self, state: usize) -> Incoming<'_, L> {
+        let range = if state + 1 < self.in_offsets.len() {
+            self.in_offsets[state]..self.in_offsets[state + 1]
+        } else {
+            0..0
+        };
+        Incoming {
+            labels: &self.labels,
+            edges: self.incoming[range.clone()].iter(),
+            sources: self.sources[range].iter(),
+        }
+    }
+}
+
+impl<L> Adjacency<L>
+where
+    L: Clone + Eq + Hash,
+{
+    /// Build an adjacency for the states `0..total_states` with the edges of this one followed by
+    /// the given edges, skipping any edge that is already present.
+    #[inline]
+    pub fn extended<I>(&self, total_states: usize, edges: I) -> Self
+    where
+        I: IntoIterator<Item = (usize, L, usize)>,
+    {
+        let mut seen = HashSet::new();
+        let edges: Vec<_> = self
+            .edges()
+            .map(|(source, label, target)| (source, label.clone(), target))
+            .chain(edges)
+            .filter(|edge| seen.insert(edge.clone()))
+            .collect();
+        Self::new(total_states, edges)
+    }
+}
+
+impl<L> Default for Adjacency<L> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            offsets: Vec::new(),
+            labels: Vec::new(),
+            targets: Vec::new(),
+            in_offsets: Vec::new(),
+            incoming: Vec::new(),
+            sources: Vec::new(),
+        }
+    }
+}
+
+impl<'a, L> IntoIterator for &'a Adjacency<L> {
+    type Item = (usize, &'a L, usize);
+    type IntoIter = Edges<'a, L>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.edges()
+    }
+}
+
+/// An iterator over the outgoing edges of a state. See [`Adjacency::outgoing`].
+pub type Outgoing<'a, L> = Zip<slice::Iter<'a, L>, Cloned<slice::Iter<'a, usize>>>;
+
+/// An iterator over every edge of an adjacency. See [`Adjacency::edges`].
+#[derive(Clone, Debug)]
+pub struct Edges<'a, L> {
+    adjacency: &'a Adjacency<L>,
+    source: usize,
+    edge: usize,
+}
+
+impl<'a, L> Iterator for Edges<'a, L> {
+    type Item = (usize, &'a L, usize);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let adjacency = self.adjacency;
+        if self.edge >= adjacency.targets.len() {
+            return None;
+        }
+
+        while adjacency.offsets[self.source + 1] <= self.edge {
+            self.source += 1;
+        }
+        let edge = self.edge;
+        self.edge += 1;
+        Some((
+            self.source,
+            &adjacency.labels[edge],
+            adjacency.targets[edge],
+        ))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.adjacency.targets.len() - self.edge;
+        (remaining, Some(remaining))
+    }
+}
+
+/// An iterator over the incoming edges of a state. See [`Adjacency::incoming`].
+#[derive(Clone, Debug)]
+pub struct Incoming<'a, L> {
+    labels: &'a [L],
+    edges: slice::Iter<'a, usize>,
+    sources: slice::Iter<'a, usize>,
+}
+
+impl<'a, L> Iterator for Incoming<'a, L> {
+    type Item = (&'a L, usize);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let edge = *self.edges.next()?;
+        let source = *self.sources.next()?;
+        Some((&self.labels[edge], source))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.edges.size_hint()
+    }
+}
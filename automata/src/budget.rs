@@ -0,0 +1,139 @@
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A bound on the work a search may do: a number of steps, a flag that another thread sets to
+/// cancel the search, or both. A step is one symbol of input read by an automaton.
+///
+/// A search takes steps from the budget as it goes and aborts once there are none left or the
+/// flag is set. The flag is only polled every [`Budget::POLL_INTERVAL`] steps, so a cancelled
+/// search may read a few more symbols before it stops, and a search that reads nothing may finish
+/// without polling it. Once a budget has aborted a search it stays spent, and every later search
+/// with it aborts too.
+#[derive(Debug, Default)]
+pub struct Budget<'a> {
+    /// The steps left, or None if the steps are not limited.
+    remaining: Cell<Option<usize>>,
+    cancel: Option<&'a AtomicBool>,
+    /// The steps to take before the flag is polled again.
+    until_poll: Cell<usize>,
+    aborted: Cell<Option<Aborted>>,
+}
+
+/// Error returned when a search runs out of its [`Budget`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Aborted {
+    /// The search took all of the steps it was allowed.
+    OutOfSteps,
+    /// The search was cancelled through its flag.
+    Cancelled,
+}
+
+impl<'a> Budget<'a> {
+    /// The number of steps between polls of the cancellation flag.
+    pub const POLL_INTERVAL: usize = 64;
+
+    /// A budget that never runs out.
+    #[inline]
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// A budget of the given number of steps.
+    #[inline]
+    pub fn steps(steps: usize) -> Self {
+        Self::unlimited().with_steps(steps)
+    }
+
+    /// A budget that runs out when the flag is set.
+    #[inline]
+    pub fn cancellable(cancel: &'a AtomicBool) -> Self {
+        Self::unlimited().with_cancel(cancel)
+    }
+
+    /// Limit the budget to the given number of steps.
+    #[inline]
+    pub fn with_steps(self, steps: usize) -> Self {
+        self.remaining.set(Some(steps));
+        self
+    }
+
+    /// Run the budget out when the flag is set.
+    #[inline]
+    pub fn with_cancel(self, cancel: &'a AtomicBool) -> Self {
+        Self {
+            cancel: Some(cancel),
+            ..self
+        }
+    }
+
+    /// The steps left, or None if the steps are not limited.
+    #[inline]
+    pub fn remaining(&self) -> Option<usize> {
+        self.remaining.get()
+    }
+
+    /// Return why a search with this budget was aborted, if one was.
+    #[inline]
+    pub fn aborted(&self) -> Option<Aborted> {
+        self.aborted.get()
+    }
+
+    /// Fail if a search with this budget was aborted.
+    #[inline]
+    pub fn check(&self) -> Result<(), Aborted> {
+        match self.aborted.get() {
+            Some(reason) => Err(reason),
+            None => Ok(()),
+        }
+    }
+
+    /// Take one step.
+    #[inline]
+    pub fn step(&self) -> Result<(), Aborted> {
+        self.take(1)
+    }
+
+    /// Take the given number of steps at once, polling the flag if it is due.
+    #[inline]
+    pub fn take(&self, steps: usize) -> Result<(), Aborted> {
+        self.check()?;
+
+        if let Some(remaining) = self.remaining.get() {
+            if steps > remaining {
+                return self.abort(Aborted::OutOfSteps);
+            }
+            self.remaining.set(Some(remaining - steps));
+        }
+
+        let until_poll = self.until_poll.get();
+        if steps < until_poll {
+            self.until_poll.set(until_poll - steps);
+            return Ok(());
+        }
+        self.until_poll.set(Self::POLL_INTERVAL);
+        match self.cancel {
+            Some(cancel) if cancel.load(Ordering::Relaxed) => self.abort(Aborted::Cancelled),
+            _ => Ok(()),
+        }
+    }
+
+    #[inline]
+    fn abort(&self, reason: Aborted) -> Result<(), Aborted> {
+        self.aborted.set(Some(reason));
+        Err(reason)
+    }
+}
+
+impl fmt::Display for Aborted {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Aborted::OutOfSteps => write!(f, "search aborted: out of steps"),
+            Aborted::Cancelled => write!(f, "search aborted: cancelled"),
+        }
+    }
+}
+
+impl Error for Aborted {}
@@ -0,0 +1,191 @@
+use crate::limit::{Limits, SizeLimitExceeded};
+use crate::nfa::{Transition, NFA};
+
+use std::hash::Hash;
+
+/// Builds an NFA in place using Thompson's construction. Each operator appends a constant number
+/// of states to a single NFA and returns a [`Fragment`], so building an NFA takes time linear in
+/// the size of the expression, unlike [`NFA::union`], [`NFA::concatenation`] and
+/// [`NFA::kleene_star`], which copy their operands.
+///
+/// The operators cannot fail, so a builder with [`Limits`] only records them; callers check
+/// [`NFABuilder::check_limits`] as they go to stop building an NFA that has grown too large.
+#[derive(Clone, Debug)]
+pub struct NFABuilder<T: Clone + Eq + Hash> {
+    nfa: NFA<T>,
+    /// The transitions added so far, which are added to the NFA when it is built.
+    edges: Vec<(usize, Transition<T>, usize)>,
+    limits: Limits,
+}
+
+/// A piece of an NFA under construction. A fragment has a start state and a list of exits:
+/// transitions whose destination is not known yet. The exits are patched to the start state of
+/// the following fragment when fragments are combined, or to an accepting state when the NFA is
+/// built.
+#[derive(Debug)]
+pub struct Fragment<T: Clone + Eq + Hash> {
+    start: usize,
+    exits: Vec<Exit<T>>,
+}
+
+/// A transition from a state to a destination that has not been decided yet.
+#[derive(Debug)]
+struct Exit<T: Clone + Eq + Hash> {
+    state: usize,
+    label: Transition<T>,
+}
+
+impl<T: Clone + Eq + Hash> Fragment<T> {
+    /// The start state of the fragment.
+    #[inline]
+    pub fn start(&self) -> usize {
+        self.start
+    }
+}
+
+impl<T> NFABuilder<T>
+where
+    T: Clone + Eq + Hash,
+{
+    /// Create a builder with no states.
+    #[allow(clippy::new_without_default)]
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            nfa: NFA::with_states(0, 0),
+            edges: Vec::new(),
+            limits: Limits::none(),
+        }
+    }
+
+    /// Create a builder with no states that checks the number of states against the limits.
+    #[inline]
+    pub fn with_limits(limits: Limits) -> Self {
+        Self {
+            limits,
+            ..Self::new()
+        }
+    }
+
+    /// Check that the states added so far are within the limits.
+    #[inline]
+    pub fn check_limits(&self) -> Result<(), SizeLimitExceeded> {
+        self.limits.check_nfa_states(self.nfa.total_states)
+    }
+
+    /// The number of states added so far.
+    #[inline]
+    pub fn total_states(&self) -> usize {
+        self.nfa.total_states
+    }
+
+    /// Add a fragment that makes a single transition with the given label.
+    #[inline]
+    pub fn transition(&mut self, label: Transition<T>) -> Fragment<T> {
+        let state = self.nfa.add_state(false);
+        Fragment {
+            start: state,
+            exits: vec![Exit { state, label }],
+        }
+    }
+
+    /// Add a fragment that makes a single transition on the given symbol.
+    #[inline]
+    pub fn symbol(&mut self, symbol: T) -> Fragment<T> {
+        self.transition(Transition::Some(symbol))
+    }
+
+    /// Add a fragment that matches the empty string.
+    #[inline]
+    pub fn epsilon(&mut self) -> Fragment<T> {
+        self.transition(Transition::Epsilon)
+    }
+
+    /// Combine two fragments so that the first is followed by the second.
+    #[inline]
+    pub fn concatenation(&mut self, c1: Fragment<T>, c2: Fragment<T>) -> Fragment<T> {
+        self.patch(c1.exits, c2.start);
+        Fragment {
+            start: c1.start,
+            exits: c2.exits,
+        }
+    }
+
+    /// Combine two fragments so that either may be followed. A new start state has epsilon
+    /// transitions to the start states of both.
+    #[inline]
+    pub fn union(&mut self, c1: Fragment<T>, c2: Fragment<T>) -> Fragment<T> {
+        let start = self.nfa.add_state(false);
+        self.edges.push((start, Transition::Epsilon, c1.start));
+        self.edges.push((start, Transition::Epsilon, c2.start));
+
+        let mut exits = c1.exits;
+        exits.extend(c2.exits);
+        Fragment { start, exits }
+    }
+
+    /// Repeat a fragment zero or more times.
+    #[inline]
+    pub fn kleene_star(&mut self, c1: Fragment<T>) -> Fragment<T> {
+        let start = self.nfa.add_state(false);
+        self.edges.push((start, Transition::Epsilon, c1.start));
+        self.patch(c1.exits, start);
+
+        Fragment {
+            start,
+            exits: vec![Exit {
+                state: start,
+                label: Transition::Epsilon,
+            }],
+        }
+    }
+
+    /// Repeat a fragment one or more times.
+    #[inline]
+    pub fn plus(&mut self, c1: Fragment<T>) -> Fragment<T> {
+        let repeat = self.nfa.add_state(false);
+        self.patch(c1.exits, repeat);
+        self.edges.push((repeat, Transition::Epsilon, c1.start));
+
+        Fragment {
+            start: c1.start,
+            exits: vec![Exit {
+                state: repeat,
+                label: Transition::Epsilon,
+            }],
+        }
+    }
+
+    /// Match a fragment zero or one times.
+    #[inline]
+    pub fn optional(&mut self, c1: Fragment<T>) -> Fragment<T> {
+        let start = self.nfa.add_state(false);
+        self.edges.push((start, Transition::Epsilon, c1.start));
+
+        let mut exits = c1.exits;
+        exits.push(Exit {
+            state: start,
+            label: Transition::Epsilon,
+        });
+        Fragment { start, exits }
+    }
+
+    /// Finish the NFA, with the given fragment as the whole automaton. The exits of the fragment
+    /// lead to a single accepting state.
+    #[inline]
+    pub fn build(mut self, fragment: Fragment<T>) -> NFA<T> {
+        let accepting_state = self.nfa.add_state(true);
+        self.patch(fragment.exits, accepting_state);
+        self.nfa.start_state = fragment.start;
+        self.nfa.add_transitions(self.edges);
+        self.nfa
+    }
+
+    /// Point each of the exits at the given state.
+    #[inline]
+    fn patch(&mut self, exits: Vec<Exit<T>>, target: usize) {
+        for exit in exits {
+            self.edges.push((exit.state, exit.label, target));
+        }
+    }
+}
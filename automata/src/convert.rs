@@ -1,8 +1,11 @@
+use crate::adjacency::Adjacency;
 use crate::dfa::{Transition, DFA};
+use crate::limit::{Limits, SizeLimitExceeded};
 use crate::nfa::{self, NFA};
 
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::hash::Hash;
+use std::mem;
 
 /// Must be implemented by NFA transition symbol types to ensure each DFA state has only one
 /// possible transition on any symbol.
@@ -25,12 +28,13 @@ where
 #[derive(Clone, Debug)]
 struct DState {
     label: usize,
-    nfa_states: HashSet<usize>,
+    /// The NFA states in this DFA state, sorted so that they can be used as a key.
+    nfa_states: Vec<usize>,
 }
 
 impl DState {
     #[inline]
-    fn new(label: usize, nfa_states: HashSet<usize>) -> Self {
+    fn new(label: usize, nfa_states: Vec<usize>) -> Self {
         Self { label, nfa_states }
     }
 }
@@ -60,20 +64,68 @@ impl<T> From<NFA<T>> for DFAFromNFA<T>
 where
     T: Clone + Disjoin + Eq + Hash,
 {
-    // Create an equivalent DFA from an NFA using the subset construction described by Algorithm
-    // 3.20. The construction is slightly modified, with inspiration from [this Stack Overflow
-    //   answer](https://stackoverflow.com/a/25832898/8955108) to accomodate character ranges.
     #[inline]
     fn from(nfa: NFA<T>) -> Self {
+        Self::with_limits(nfa, Limits::none()).expect("there are no limits to exceed")
+    }
+}
+
+impl<T> DFAFromNFA<T>
+where
+    T: Clone + Disjoin + Eq + Hash,
+{
+    /// Create an equivalent DFA from an NFA, failing as soon as the NFA or the DFA under
+    /// construction exceeds the limits. The memory counted is that of the set of NFA states
+    /// stored for each DFA state, which is kept twice, and of each transition of the DFA.
+    #[inline]
+    pub fn with_limits(nfa: NFA<T>, limits: Limits) -> Result<Self, SizeLimitExceeded> {
+        // Create an equivalent DFA from an NFA using the subset construction described by
+        // Algorithm 3.20. The construction is slightly modified, with inspiration from [this
+        // Stack Overflow answer](https://stackoverflow.com/a/25832898/8955108) to accomodate
+        // character ranges.
+        limits.check_nfa_states(nfa.total_states)?;
+        let set_bytes = |states: &[usize]| 2 * states.len() * mem::size_of::<usize>();
+        let transition_bytes = mem::size_of::<T>() + 2 * mem::size_of::<usize>();
+        let mut memory = 0;
+
         let mut dfa = DFA::new();
         let mut nfa_mapping = HashMap::new();
 
-        let mut marked_states = Vec::new();
+        let closures = nfa.epsilon_closures();
+        let closure_of = |states: Vec<usize>| -> Vec<usize> {
+            let mut closure: Vec<usize> = closures.closure_set(states).into_iter().collect();
+            closure.sort_unstable();
+            closure
+        };
+
+        // Number each distinct transition symbol, and store the non-epsilon transitions of the
+        // NFA labeled by symbol number.
+        let mut symbols: Vec<&T> = Vec::new();
+        let mut symbol_ids: HashMap<&T, usize> = HashMap::new();
+        let mut labeled = Vec::new();
+        for (start, t, end) in nfa.adjacency() {
+            if let nfa::Transition::Some(a) = t {
+                let id = *symbol_ids.entry(a).or_insert_with(|| {
+                    symbols.push(a);
+                    symbols.len() - 1
+                });
+                labeled.push((start, id, end));
+            }
+        }
+        let edges = Adjacency::new(nfa.total_states, labeled);
+
+        // The disjoint symbols for each distinct set of transition symbols, along with the
+        // original symbols that contain each of them. Many DFA states share the same set of
+        // transition symbols, so this only needs to be computed once for each set.
+        let mut disjoint_cache: HashMap<Vec<usize>, Vec<(T, Vec<usize>)>> = HashMap::new();
+
+        // Map each set of NFA states to the label of its DFA state.
+        let mut labels: HashMap<Vec<usize>, usize> = HashMap::new();
         let mut unmarked_states = VecDeque::new();
+        let mut transitions = Vec::new();
 
         let label = 0;
-        let initial_e_closure = nfa.epsilon_closure(nfa.start_state);
-        let initial_unmarked = DState::new(label, initial_e_closure);
+        let initial_unmarked = DState::new(label, closure_of(vec![nfa.start_state]));
 
         if initial_unmarked
             .nfa_states
@@ -83,80 +135,81 @@ where
             dfa.accepting_states.insert(initial_unmarked.label);
         }
 
-        nfa_mapping.insert(initial_unmarked.label, initial_unmarked.nfa_states.clone());
+        memory += set_bytes(&initial_unmarked.nfa_states);
+        limits.check_memory(memory)?;
+        nfa_mapping.insert(
+            initial_unmarked.label,
+            initial_unmarked.nfa_states.iter().cloned().collect(),
+        );
+        labels.insert(initial_unmarked.nfa_states.clone(), initial_unmarked.label);
         unmarked_states.push_back(initial_unmarked);
 
         while let Some(s) = unmarked_states.pop_front() {
-            // Get all non-epsilon transitions and destinations from the NFA states in this set
-            // state.
-            let transition_map: Vec<(&T, &HashSet<usize>)> = s
-                .clone()
-                .nfa_states
-                .into_iter()
-                // Union of transitions from each NFA state
-                .flat_map(|nfa_state| nfa.transitions_from(nfa_state))
-                // Filter out epsilon transitions
-                .filter_map(|(t, v)| match t {
-                    nfa::Transition::Some(a) => Some((a, v)),
-                    nfa::Transition::Epsilon => None,
-                })
-                .collect();
-
-            // Isolate transitions.
-            let transitions: Vec<&T> = transition_map.iter().map(|(t, _)| *t).collect();
-            // Disjoin transitions.
-            let disjoint_transitions = T::disjoin(transitions);
+            // Union of non-epsilon transitions from each NFA state in this set state, grouped by
+            // symbol.
+            let mut moves: HashMap<usize, Vec<usize>> = HashMap::new();
+            for &nfa_state in s.nfa_states.iter() {
+                for (&id, end) in edges.outgoing(nfa_state) {
+                    moves.entry(id).or_default().push(end);
+                }
+            }
+
+            let mut symbol_set: Vec<usize> = moves.keys().cloned().collect();
+            symbol_set.sort_unstable();
 
-            for t in disjoint_transitions {
-                let moved_set: HashSet<usize> = transition_map
+            // Disjoin transitions.
+            let disjoint_transitions = disjoint_cache.entry(symbol_set).or_insert_with_key(|ids| {
+                let transitions: Vec<&T> = ids.iter().map(|&id| symbols[id]).collect();
+                T::disjoin(transitions)
+                    .into_iter()
+                    .map(|t| {
+                        let members = ids
+                            .iter()
+                            .cloned()
+                            .filter(|&id| symbols[id].contains(&t))
+                            .collect();
+                        (t, members)
+                    })
+                    .collect()
+            });
+
+            for (t, members) in disjoint_transitions.iter() {
+                let moved_set: Vec<usize> = members
                     .iter()
-                    .filter(|(a, _)| a.contains(&t))
-                    .flat_map(|(_, v)| (*v).clone())
+                    .flat_map(|id| moves[id].iter().cloned())
                     .collect();
-                let epsilon_closure = nfa.epsilon_closure_set(&moved_set);
-                let mut new_state = DState::new(0, epsilon_closure);
-
-                // If state already exists in unmarked or marked, change the label and do not push
-                // to unmarked.
-                if s.nfa_states == new_state.nfa_states {
-                    new_state.label = s.label;
-                    dfa.add_transition(s.label, new_state.label, Transition(t));
-                } else if let Some(existing) = marked_states
-                    .iter()
-                    .find(|ss: &&DState| ss.nfa_states == new_state.nfa_states)
-                {
-                    new_state.label = existing.label;
-                    dfa.add_transition(s.label, new_state.label, Transition(t));
-                } else if let Some(existing) = unmarked_states
-                    .iter()
-                    .find(|ss: &&DState| ss.nfa_states == new_state.nfa_states)
-                {
-                    new_state.label = existing.label;
-                    dfa.add_transition(s.label, new_state.label, Transition(t));
-                } else {
-                    // If not found, set a new label and push to unmarked.
-                    new_state.label = dfa.add_state(false);
-
-                    // If this set state contains an accepting NFA state, set this set state
-                    // as accepting in the DFA.
-                    if new_state
-                        .nfa_states
-                        .iter()
-                        .any(|i| nfa.is_accepting_state(i))
-                    {
-                        dfa.accepting_states.insert(new_state.label);
+                let nfa_states = closure_of(moved_set);
+
+                // If state already exists, use its label. Otherwise, set a new label and push to
+                // unmarked.
+                let label = match labels.get(&nfa_states) {
+                    Some(&label) => label,
+                    None => {
+                        let label = dfa.add_state(false);
+                        memory += set_bytes(&nfa_states);
+                        limits.check_dfa_states(dfa.total_states)?;
+                        limits.check_memory(memory)?;
+
+                        // If this set state contains an accepting NFA state, set this set state
+                        // as accepting in the DFA.
+                        if nfa_states.iter().any(|i| nfa.is_accepting_state(i)) {
+                            dfa.accepting_states.insert(label);
+                        }
+
+                        nfa_mapping.insert(label, nfa_states.iter().cloned().collect());
+                        labels.insert(nfa_states.clone(), label);
+                        unmarked_states.push_back(DState::new(label, nfa_states));
+                        label
                     }
+                };
 
-                    dfa.add_transition(s.label, new_state.label, Transition(t));
-                    nfa_mapping.insert(new_state.label, new_state.nfa_states.clone());
-                    unmarked_states.push_back(new_state);
-                }
+                memory += transition_bytes;
+                limits.check_memory(memory)?;
+                transitions.push((s.label, Transition(t.clone()), label));
             }
-
-            // Mark this state
-            marked_states.push(s);
         }
 
-        Self { dfa, nfa_mapping }
+        dfa.add_transitions(transitions);
+        Ok(Self { dfa, nfa_mapping })
     }
 }
@@ -1,9 +1,10 @@
+use crate::adjacency::{Adjacency, Outgoing};
+use crate::budget::{Aborted, Budget};
 use crate::matching::Match;
-use crate::table::Table;
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{hash_map::Entry, HashMap, HashSet, VecDeque};
 use std::hash::Hash;
-use std::iter::Peekable;
+use std::iter::{self, Peekable};
 use std::rc::Rc;
 
 /// A deterministic finite automaton, or DFA.
@@ -19,8 +20,8 @@ where
     pub total_states: usize,
     /// The set of accepting states.
     pub accepting_states: HashSet<usize>,
-    /// A lookup table for transitions between states.
-    pub transition: Table<usize, Transition<T>, usize>,
+    /// The transitions between states.
+    pub transition: Adjacency<T>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
@@ -49,7 +50,7 @@ where
             start_state: 0,
             total_states: 1,
             accepting_states: HashSet::new(),
-            transition: Table::new(),
+            transition: Adjacency::default(),
         }
     }
 }
@@ -78,22 +79,68 @@ where
         label
     }
 
+    /// Add a transition, replacing the destination of any transition from the same state on the
+    /// same symbol. Returns None if one or more of the states does not exist. Each call builds the
+    /// adjacency anew; see [`DFA::add_transitions`] to add many transitions.
     #[inline]
     pub fn add_transition<U>(&mut self, start: usize, end: usize, label: U) -> Option<()>
     where
         U: Into<Transition<T>>,
     {
-        if self.total_states < start + 1 || self.total_states < end + 1 {
-            None
-        } else {
-            self.transition.set(start, label.into(), end);
-            Some(())
+        self.add_transitions(iter::once((start, label, end)))
+    }
+
+    /// Add (start, label, end) transitions, each replacing the destination of any transition from
+    /// the same state on the same symbol, and build the adjacency of the DFA once for all of them.
+    /// Returns None, adding nothing, if one or more of the states does not exist.
+    #[inline]
+    pub fn add_transitions<U, I>(&mut self, transitions: I) -> Option<()>
+    where
+        U: Into<Transition<T>>,
+        I: IntoIterator<Item = (usize, U, usize)>,
+    {
+        let transitions: Vec<_> = transitions
+            .into_iter()
+            .map(|(start, label, end)| (start, label.into().0, end))
+            .collect();
+        if transitions
+            .iter()
+            .any(|&(start, _, end)| start >= self.total_states || end >= self.total_states)
+        {
+            return None;
+        }
+
+        // A replaced transition keeps its place among the outgoing edges of its state.
+        let mut edges: Vec<(usize, T, usize)> = Vec::new();
+        let mut index: HashMap<(usize, T), usize> = HashMap::new();
+        let old = self
+            .transition
+            .edges()
+            .map(|(start, t, end)| (start, t.clone(), end));
+        for (start, t, end) in old.chain(transitions) {
+            match index.entry((start, t.clone())) {
+                Entry::Occupied(e) => edges[*e.get()].2 = end,
+                Entry::Vacant(e) => {
+                    e.insert(edges.len());
+                    edges.push((start, t, end));
+                }
+            }
         }
+        self.transition = Adjacency::new(self.total_states, edges);
+        Some(())
     }
 
+    /// Returns the transitions and destinations from a specific state.
     #[inline]
-    pub fn transitions_on(&self, state: &usize) -> HashMap<&Transition<T>, &usize> {
-        self.transition.get_row(state)
+    pub fn transitions_on(&self, state: &usize) -> Outgoing<'_, T> {
+        self.transition.outgoing(*state)
+    }
+
+    /// The transitions of the DFA, as an [`Adjacency`]. The outgoing edges of each state are in
+    /// the order they were added.
+    #[inline]
+    pub fn adjacency(&self) -> &Adjacency<T> {
+        &self.transition
     }
 
     #[inline]
@@ -102,18 +149,100 @@ where
     }
 }
 
+impl<T> DFA<T>
+where
+    T: Clone + Eq + Hash + Ord,
+{
+    /// Determine if the DFA accepts no strings at all.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.shortest_accepted().is_none()
+    }
+
+    /// Find the symbols of a shortest string accepted by the DFA, using a breadth-first search
+    /// from the start state. Returns None if no string is accepted.
+    ///
+    /// The outgoing edges of each state are followed in order of their symbols, so of all the
+    /// shortest strings the least is found, whatever the numbering of the states.
+    #[inline]
+    pub fn shortest_accepted(&self) -> Option<Vec<T>> {
+        let adjacency = self.adjacency();
+
+        // The state and symbol each state was first reached from.
+        let mut parents: Vec<Option<(usize, &T)>> = vec![None; self.total_states];
+        let mut visited = vec![false; self.total_states];
+        let mut queue = VecDeque::new();
+
+        visited[self.start_state] = true;
+        queue.push_back(self.start_state);
+        while let Some(s) = queue.pop_front() {
+            if self.is_accepting_state(&s) {
+                let mut symbols = Vec::new();
+                let mut current = s;
+                while let Some((parent, t)) = parents[current] {
+                    symbols.push(t.clone());
+                    current = parent;
+                }
+                symbols.reverse();
+                return Some(symbols);
+            }
+
+            let mut outgoing: Vec<_> = adjacency.outgoing(s).collect();
+            outgoing.sort_by_key(|&(t, _)| t);
+            for (t, next) in outgoing {
+                if !visited[next] {
+                    visited[next] = true;
+                    parents[next] = Some((s, t));
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        None
+    }
+}
+
 impl<T> DFA<T>
 where
     T: Clone + Eq + Hash,
 {
     #[inline]
     pub fn iter_on<I>(&self, input: I) -> Iter<'_, T, I::IntoIter>
+    where
+        T: PartialEq<I::Item>,
+        I: IntoIterator,
+    {
+        self.iter_on_impl(input, None)
+    }
+
+    /// Iterate over the input as [`DFA::iter_on`] does, taking a step from the budget for each
+    /// symbol read. The iterator ends early if the budget runs out, which the budget records.
+    #[inline]
+    pub fn iter_on_within<'a, I>(
+        &'a self,
+        input: I,
+        budget: &'a Budget<'a>,
+    ) -> Iter<'a, T, I::IntoIter>
+    where
+        T: PartialEq<I::Item>,
+        I: IntoIterator,
+    {
+        self.iter_on_impl(input, Some(budget))
+    }
+
+    #[inline]
+    fn iter_on_impl<'a, I>(
+        &'a self,
+        input: I,
+        budget: Option<&'a Budget<'a>>,
+    ) -> Iter<'a, T, I::IntoIter>
     where
         T: PartialEq<I::Item>,
         I: IntoIterator,
     {
         Iter {
-            dfa: &self,
+            dfa: self,
+            budget,
 
             input: input.into_iter().peekable(),
             last: None,
@@ -142,6 +271,7 @@ where
     I: Iterator,
 {
     dfa: &'a DFA<T>,
+    budget: Option<&'a Budget<'a>>,
 
     input: Peekable<I>,
     last: Option<(LastIterState, usize)>,
@@ -157,7 +287,7 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        iter_on_next(&self.dfa, &mut self.input, &mut self.last)
+        iter_on_next(self.dfa, self.budget, &mut self.input, &mut self.last)
     }
 }
 
@@ -183,7 +313,7 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        iter_on_next(&self.dfa, &mut self.input, &mut self.last)
+        iter_on_next(&self.dfa, None, &mut self.input, &mut self.last)
     }
 }
 
@@ -206,6 +336,7 @@ enum LastIterState {
 #[inline]
 fn iter_on_next<T, I>(
     dfa: &DFA<T>,
+    budget: Option<&Budget<'_>>,
     input: &mut Peekable<I>,
     last: &mut Option<(LastIterState, usize)>,
 ) -> Option<IterState<I>>
@@ -228,18 +359,20 @@ where
         Some((LastIterState::Stuck, _)) => return None,
     };
 
-    let peek_is = match input.peek() {
-        Some(v) => v,
-        // No more input, so last item was the final.
-        None => return None,
-    };
+    // No more input, so last item was the final.
+    let peek_is = input.peek()?;
+
+    // Reading a symbol takes a step, so stop here if the budget has run out.
+    if let Some(budget) = budget {
+        budget.step().ok()?;
+    }
 
-    let transitions = dfa.transitions_on(&current);
-    let next = match transitions
-        .iter()
-        .find(|(&Transition(t), _)| *t == *peek_is)
+    let next = match dfa
+        .transition
+        .outgoing(current)
+        .find(|(t, _)| **t == *peek_is)
     {
-        Some((_, &&next_state)) => {
+        Some((_, next_state)) => {
             // Consume input symbol.
             let is = input.next().unwrap();
 
@@ -270,7 +403,29 @@ where
         T: PartialEq<I::Item>,
         I: IntoIterator,
     {
-        match self.iter_on(input).last() {
+        self.is_match_impl(input, None)
+    }
+
+    /// Determine if the given input is accepted by the DFA, or abort if the budget runs out
+    /// first.
+    #[inline]
+    pub fn is_match_within<I>(&self, input: I, budget: &Budget<'_>) -> Result<bool, Aborted>
+    where
+        T: PartialEq<I::Item>,
+        I: IntoIterator,
+    {
+        let is_match = self.is_match_impl(input, Some(budget));
+        budget.check()?;
+        Ok(is_match)
+    }
+
+    #[inline]
+    fn is_match_impl<I>(&self, input: I, budget: Option<&Budget<'_>>) -> bool
+    where
+        T: PartialEq<I::Item>,
+        I: IntoIterator,
+    {
+        match self.iter_on_impl(input, budget).last() {
             Some(IterState::Start(_, is_final) | IterState::Normal(_, _, is_final)) => is_final,
             Some(IterState::Stuck(_)) => false,
             // None means that no movement happened; check if the current state (start state) is an
@@ -294,7 +449,25 @@ where
         T: PartialEq<I::Item>,
         I: IntoIterator,
     {
-        self.find_at_impl(input, start, true)
+        self.find_at_impl(input, start, true, None)
+    }
+
+    /// Find the shortest match as [`DFA::find_shortest_at`] does, or abort if the budget runs
+    /// out first.
+    #[inline]
+    pub fn find_shortest_at_within<I>(
+        &self,
+        input: I,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match<I::Item>>, Aborted>
+    where
+        T: PartialEq<I::Item>,
+        I: IntoIterator,
+    {
+        let m = self.find_at_impl(input, start, true, Some(budget));
+        budget.check()?;
+        Ok(m)
     }
 
     #[inline]
@@ -312,17 +485,42 @@ where
         T: PartialEq<I::Item>,
         I: IntoIterator,
     {
-        self.find_at_impl(input, start, false)
+        self.find_at_impl(input, start, false, None)
+    }
+
+    /// Find the longest match as [`DFA::find_at`] does, or abort if the budget runs out first.
+    #[inline]
+    pub fn find_at_within<I>(
+        &self,
+        input: I,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match<I::Item>>, Aborted>
+    where
+        T: PartialEq<I::Item>,
+        I: IntoIterator,
+    {
+        let m = self.find_at_impl(input, start, false, Some(budget));
+        budget.check()?;
+        Ok(m)
     }
 
     #[inline]
-    fn find_at_impl<I>(&self, input: I, start: usize, shortest: bool) -> Option<Match<I::Item>>
+    fn find_at_impl<I>(
+        &self,
+        input: I,
+        start: usize,
+        shortest: bool,
+        budget: Option<&Budget<'_>>,
+    ) -> Option<Match<I::Item>>
     where
         T: PartialEq<I::Item>,
         I: IntoIterator,
     {
         let mut last_match = None;
-        let iter = self.iter_on(input).skip(start).enumerate();
+        let iter = self
+            .iter_on_impl(input.into_iter().skip(start), budget)
+            .enumerate();
 
         // Ensure span dropped before unwrapping Rc's.
         {
@@ -340,7 +538,7 @@ where
                 };
 
                 if is_final {
-                    last_match = Some(Match::new(start, i + 1, span.clone()));
+                    last_match = Some(Match::new(start, start + i, span.clone()));
                     if shortest {
                         break;
                     }
@@ -0,0 +1,304 @@
+use crate::convert::Disjoin;
+use crate::product::Universe;
+
+use std::hash::Hash;
+use std::iter::FromIterator;
+use std::ops::RangeInclusive;
+
+/// A totally ordered type with a least and a greatest value, in which every value but the
+/// greatest has a successor and every value but the least a predecessor. Sets of intervals of
+/// such a type can be complemented and split into disjoint intervals.
+pub trait Symbol: Copy + Ord + Hash {
+    const MIN: Self;
+    const MAX: Self;
+
+    /// The next value, or None if this is the greatest value.
+    fn successor(self) -> Option<Self>;
+
+    /// The previous value, or None if this is the least value.
+    fn predecessor(self) -> Option<Self>;
+}
+
+macro_rules! impl_symbol {
+    ($($t:ty),*) => {
+        $(
+            impl Symbol for $t {
+                const MIN: Self = <$t>::MIN;
+                const MAX: Self = <$t>::MAX;
+
+                #[inline]
+                fn successor(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+
+                #[inline]
+                fn predecessor(self) -> Option<Self> {
+                    self.checked_sub(1)
+                }
+            }
+        )*
+    };
+}
+
+impl_symbol!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// The surrogate code points are not characters, so they are skipped.
+impl Symbol for char {
+    const MIN: Self = '\0';
+    const MAX: Self = char::MAX;
+
+    #[inline]
+    fn successor(self) -> Option<Self> {
+        match self {
+            '\u{d7ff}' => Some('\u{e000}'),
+            c => std::char::from_u32(c as u32 + 1),
+        }
+    }
+
+    #[inline]
+    fn predecessor(self) -> Option<Self> {
+        match self {
+            '\u{e000}' => Some('\u{d7ff}'),
+            c => (c as u32).checked_sub(1).and_then(std::char::from_u32),
+        }
+    }
+}
+
+/// A set of symbols, stored as a sorted list of inclusive intervals. The intervals are kept
+/// disjoint and apart, so that sets with the same symbols are equal and hash the same. This makes
+/// a transition symbol type for automata over any [`Symbol`]: bytes, UTF-16 code units, integer
+/// token IDs, and so on.
+///
+/// An `IntervalSet<T>` is equal to a `T` it contains, so an automaton labeled by interval sets
+/// matches input of the underlying type.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct IntervalSet<T> {
+    ranges: Vec<(T, T)>,
+}
+
+impl<T: Symbol> IntervalSet<T> {
+    /// Create an empty set.
+    #[inline]
+    pub fn new() -> Self {
+        Self { ranges: Vec::new() }
+    }
+
+    /// Create a set of every symbol.
+    #[inline]
+    pub fn all() -> Self {
+        Self::range(T::MIN, T::MAX)
+    }
+
+    /// Create a set of the symbols from `start` to `end`, inclusive. The set is empty if `start`
+    /// is greater than `end`.
+    #[inline]
+    pub fn range(start: T, end: T) -> Self {
+        let mut set = Self::new();
+        set.add_range(start, end);
+        set
+    }
+
+    /// The intervals of the set, in increasing order.
+    #[inline]
+    pub fn ranges(&self) -> &[(T, T)] {
+        &self.ranges
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    #[inline]
+    pub fn contains(&self, symbol: T) -> bool {
+        let i = self.ranges.partition_point(|&(_, end)| end < symbol);
+        matches!(self.ranges.get(i), Some(&(start, _)) if start <= symbol)
+    }
+
+    /// Add the symbols from `start` to `end`, inclusive.
+    #[inline]
+    pub fn add_range(&mut self, start: T, end: T) {
+        if start > end {
+            return;
+        }
+        let i = self.ranges.partition_point(|&(s, _)| s < start);
+        self.ranges.insert(i, (start, end));
+        self.normalize();
+    }
+
+    #[inline]
+    pub fn union(&self, other: &Self) -> Self {
+        let mut ranges = self.ranges.clone();
+        ranges.extend_from_slice(&other.ranges);
+        ranges.sort_unstable();
+
+        let mut set = Self { ranges };
+        set.normalize();
+        set
+    }
+
+    #[inline]
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while let (Some(&(s1, e1)), Some(&(s2, e2))) = (self.ranges.get(i), other.ranges.get(j)) {
+            let (start, end) = (s1.max(s2), e1.min(e2));
+            if start <= end {
+                ranges.push((start, end));
+            }
+
+            // Move past whichever interval ends first.
+            if e1 < e2 {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { ranges }
+    }
+
+    #[inline]
+    pub fn complement(&self) -> Self {
+        let mut ranges = Vec::new();
+        let mut next = Some(T::MIN);
+        for &(start, end) in &self.ranges {
+            if let Some(n) = next {
+                if n < start {
+                    ranges.push((n, start.predecessor().unwrap()));
+                }
+            }
+            next = end.successor();
+        }
+        if let Some(n) = next {
+            ranges.push((n, T::MAX));
+        }
+        Self { ranges }
+    }
+
+    #[inline]
+    pub fn difference(&self, other: &Self) -> Self {
+        self.intersection(&other.complement())
+    }
+
+    /// Merge the sorted intervals that overlap or touch.
+    #[inline]
+    fn normalize(&mut self) {
+        let mut merged: Vec<(T, T)> = Vec::with_capacity(self.ranges.len());
+        for &(start, end) in &self.ranges {
+            if let Some((_, last_end)) = merged.last_mut() {
+                // An interval that ends at the greatest symbol touches everything after it.
+                let touches = match last_end.successor() {
+                    Some(after) => start <= after,
+                    None => true,
+                };
+                if touches {
+                    *last_end = end.max(*last_end);
+                    continue;
+                }
+            }
+            merged.push((start, end));
+        }
+        self.ranges = merged;
+    }
+}
+
+impl<T: Symbol> Default for IntervalSet<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Symbol> From<T> for IntervalSet<T> {
+    #[inline]
+    fn from(symbol: T) -> Self {
+        Self::range(symbol, symbol)
+    }
+}
+
+impl<T: Symbol> From<RangeInclusive<T>> for IntervalSet<T> {
+    #[inline]
+    fn from(range: RangeInclusive<T>) -> Self {
+        Self::range(*range.start(), *range.end())
+    }
+}
+
+impl<T: Symbol> FromIterator<(T, T)> for IntervalSet<T> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        let mut ranges: Vec<_> = iter.into_iter().filter(|(s, e)| s <= e).collect();
+        ranges.sort_unstable();
+
+        let mut set = Self { ranges };
+        set.normalize();
+        set
+    }
+}
+
+impl<T: Symbol> PartialEq<T> for IntervalSet<T> {
+    #[inline]
+    fn eq(&self, other: &T) -> bool {
+        self.contains(*other)
+    }
+}
+
+impl<T: Symbol> Disjoin for IntervalSet<T> {
+    /// Split the sets at every interval boundary, and return each piece that some set covers as
+    /// a set of one interval.
+    #[inline]
+    fn disjoin(vec: Vec<&Self>) -> Vec<Self> {
+        // Each interval opens at its start and closes just after its end; an interval that ends
+        // at the greatest symbol never closes.
+        let mut events: Vec<(T, i32)> = Vec::new();
+        for set in vec {
+            for &(start, end) in &set.ranges {
+                events.push((start, 1));
+                if let Some(after) = end.successor() {
+                    events.push((after, -1));
+                }
+            }
+        }
+        events.sort_unstable();
+
+        let mut disjoint = Vec::new();
+        let mut count = 0;
+        let mut i = 0;
+        while i < events.len() {
+            let point = events[i].0;
+            while i < events.len() && events[i].0 == point {
+                count += events[i].1;
+                i += 1;
+            }
+
+            if count > 0 {
+                let end = match events.get(i) {
+                    Some(&(next, _)) => next.predecessor().unwrap(),
+                    None => T::MAX,
+                };
+                disjoint.push(Self::range(point, end));
+            }
+        }
+        disjoint
+    }
+
+    #[inline]
+    fn contains(&self, other: &Self) -> bool {
+        !self.intersection(other).is_empty()
+    }
+}
+
+impl<T: Symbol> Universe for IntervalSet<T> {
+    #[inline]
+    fn complement_of(vec: Vec<&Self>) -> Option<Self> {
+        let union = vec
+            .into_iter()
+            .fold(Self::new(), |union, set| union.union(set));
+        let complement = union.complement();
+
+        if complement.is_empty() {
+            None
+        } else {
+            Some(complement)
+        }
+    }
+}
@@ -3,11 +3,23 @@
 
 mod matching;
 
+pub mod adjacency;
+pub mod budget;
+pub mod builder;
 pub mod convert;
 pub mod dfa;
+pub mod interval;
+pub mod limit;
+mod minimize;
 pub mod nfa;
+pub mod product;
+mod reverse;
+pub mod sparse;
 pub mod table;
+pub mod transducer;
+mod trim;
 
 pub use dfa::DFA;
 pub use matching::Match;
 pub use nfa::NFA;
+pub use transducer::Transducer;
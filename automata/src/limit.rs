@@ -0,0 +1,112 @@
+use std::error::Error;
+use std::fmt;
+
+/// Limits on the size of automata under construction, so that expressions whose automata blow up
+/// fail to compile instead of exhausting memory. A limit of None is no limit.
+///
+/// The default limits are generous enough for any expression a person would write by hand; use
+/// [`Limits::none`] to turn them off.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// The most states an NFA may have.
+    pub nfa_states: Option<usize>,
+    /// The most states a DFA may have.
+    pub dfa_states: Option<usize>,
+    /// The most bytes the subset construction may use for the sets of NFA states and the
+    /// transitions of the DFA. This is an estimate, not a measure of the heap.
+    pub memory: Option<usize>,
+}
+
+/// A size limit that may be exceeded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Limit {
+    NFAStates,
+    DFAStates,
+    Memory,
+}
+
+/// Error returned when the construction of an automaton would exceed one of its [`Limits`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SizeLimitExceeded {
+    /// The limit that was exceeded.
+    pub limit: Limit,
+    /// The value of the limit.
+    pub max: usize,
+}
+
+impl Limits {
+    pub const DEFAULT_NFA_STATES: usize = 1 << 20;
+    pub const DEFAULT_DFA_STATES: usize = 100_000;
+    pub const DEFAULT_MEMORY: usize = 64 << 20;
+
+    /// No limits at all.
+    #[inline]
+    pub const fn none() -> Self {
+        Self {
+            nfa_states: None,
+            dfa_states: None,
+            memory: None,
+        }
+    }
+
+    /// Check a number of NFA states against the limit.
+    #[inline]
+    pub fn check_nfa_states(&self, states: usize) -> Result<(), SizeLimitExceeded> {
+        check(Limit::NFAStates, self.nfa_states, states)
+    }
+
+    /// Check a number of DFA states against the limit.
+    #[inline]
+    pub fn check_dfa_states(&self, states: usize) -> Result<(), SizeLimitExceeded> {
+        check(Limit::DFAStates, self.dfa_states, states)
+    }
+
+    /// Check a number of bytes against the limit.
+    #[inline]
+    pub fn check_memory(&self, bytes: usize) -> Result<(), SizeLimitExceeded> {
+        check(Limit::Memory, self.memory, bytes)
+    }
+}
+
+#[inline]
+fn check(limit: Limit, max: Option<usize>, value: usize) -> Result<(), SizeLimitExceeded> {
+    match max {
+        Some(max) if value > max => Err(SizeLimitExceeded { limit, max }),
+        _ => Ok(()),
+    }
+}
+
+impl Default for Limits {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            nfa_states: Some(Self::DEFAULT_NFA_STATES),
+            dfa_states: Some(Self::DEFAULT_DFA_STATES),
+            memory: Some(Self::DEFAULT_MEMORY),
+        }
+    }
+}
+
+impl fmt::Display for Limit {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::NFAStates => write!(f, "NFA states"),
+            Limit::DFAStates => write!(f, "DFA states"),
+            Limit::Memory => write!(f, "bytes of memory"),
+        }
+    }
+}
+
+impl fmt::Display for SizeLimitExceeded {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "size limit exceeded: more than {} {}",
+            self.max, self.limit
+        )
+    }
+}
+
+impl Error for SizeLimitExceeded {}
@@ -0,0 +1,226 @@
+use crate::convert::Disjoin;
+use crate::dfa::DFA;
+
+use std::collections::{hash_map::Entry, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+impl<T> DFA<T>
+where
+    T: Clone + Disjoin + Eq + Hash,
+{
+    /// Create an equivalent DFA with the minimum number of states, using Hopcroft's partition
+    /// refinement algorithm. Unreachable states and states that can never reach an accepting
+    /// state are removed.
+    ///
+    /// Because the transition symbols of different states may overlap, the refinement is done
+    /// over the set of disjoint symbols produced by [`Disjoin::disjoin`] on every transition
+    /// symbol in the DFA.
+    #[inline]
+    pub fn minimize(&self) -> DFA<T> {
+        let adjacency = self.adjacency();
+
+        // Relabel the reachable states compactly in breadth-first order; the start state is 0.
+        let mut labels = HashMap::new();
+        let mut states = Vec::new();
+        let mut queue = VecDeque::new();
+
+        labels.insert(self.start_state, 0);
+        states.push(self.start_state);
+        queue.push_back(self.start_state);
+        while let Some(s) = queue.pop_front() {
+            for &next in adjacency.targets(s) {
+                if let Entry::Vacant(e) = labels.entry(next) {
+                    e.insert(states.len());
+                    states.push(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        // Disjoin the transition symbols of all reachable states.
+        let symbols = T::disjoin(states.iter().flat_map(|&s| adjacency.labels(s)).collect());
+
+        // Build a complete transition function over the disjoint symbols, with an explicit dead
+        // state labeled `dead`.
+        let dead = states.len();
+        let total = dead + 1;
+        let mut delta = vec![vec![dead; symbols.len()]; total];
+        for (i, s) in states.iter().enumerate() {
+            for (t, next) in adjacency.outgoing(*s) {
+                for (a, symbol) in symbols.iter().enumerate() {
+                    if t.contains(symbol) {
+                        delta[i][a] = labels[&next];
+                    }
+                }
+            }
+        }
+
+        // Inverse transition function: for each symbol and state, the states that move to it.
+        let mut inverse = vec![vec![Vec::new(); total]; symbols.len()];
+        for (q, row) in delta.iter().enumerate() {
+            for (a, &p) in row.iter().enumerate() {
+                inverse[a][p].push(q);
+            }
+        }
+
+        let mut partition =
+            Partition::new(total, |q| q < dead && self.is_accepting_state(&states[q]));
+
+        // Initialize the worklist with the smaller of the initial blocks.
+        let mut worklist = VecDeque::new();
+        let mut in_worklist = HashSet::new();
+        if partition.blocks.len() == 2 {
+            let smaller = if partition.blocks[0].len() <= partition.blocks[1].len() {
+                0
+            } else {
+                1
+            };
+            for a in 0..symbols.len() {
+                worklist.push_back((smaller, a));
+                in_worklist.insert((smaller, a));
+            }
+        }
+
+        while let Some((splitter, a)) = worklist.pop_front() {
+            in_worklist.remove(&(splitter, a));
+
+            // All states that move into the splitter block on symbol a.
+            let preimage: Vec<usize> = partition.blocks[splitter]
+                .iter()
+                .flat_map(|&p| inverse[a][p].iter().cloned())
+                .collect();
+
+            for (block, new_block) in partition.split(&preimage) {
+                for c in 0..symbols.len() {
+                    if in_worklist.contains(&(block, c)) {
+                        worklist.push_back((new_block, c));
+                        in_worklist.insert((new_block, c));
+                    } else {
+                        let smaller =
+                            if partition.blocks[block].len() <= partition.blocks[new_block].len() {
+                                block
+                            } else {
+                                new_block
+                            };
+                        worklist.push_back((smaller, c));
+                        in_worklist.insert((smaller, c));
+                    }
+                }
+            }
+        }
+
+        // Every state in the same block as the dead state can never reach an accepting state.
+        let dead_block = partition.block_of[dead];
+        let start_block = partition.block_of[0];
+
+        let mut dfa = DFA::new();
+        if start_block == dead_block {
+            return dfa;
+        }
+
+        // Label the blocks of the new DFA in breadth-first order from the start block.
+        let mut block_labels = HashMap::new();
+        let mut queue = VecDeque::new();
+        let mut edges = Vec::new();
+        block_labels.insert(start_block, dfa.start_state);
+        queue.push_back(start_block);
+        while let Some(block) = queue.pop_front() {
+            let label = block_labels[&block];
+
+            // Any state in the block may stand in for the others.
+            let representative = partition.blocks[block][0];
+            if self.is_accepting_state(&states[representative]) {
+                dfa.accepting_states.insert(label);
+            }
+
+            for (t, next) in adjacency.outgoing(states[representative]) {
+                let next_block = partition.block_of[labels[&next]];
+                if next_block == dead_block {
+                    continue;
+                }
+
+                let next_label = match block_labels.get(&next_block) {
+                    Some(&l) => l,
+                    None => {
+                        let l = dfa.add_state(false);
+                        block_labels.insert(next_block, l);
+                        queue.push_back(next_block);
+                        l
+                    }
+                };
+
+                edges.push((label, t.clone(), next_label));
+            }
+        }
+
+        dfa.add_transitions(edges);
+        dfa
+    }
+}
+
+/// A partition of the states `0..n` into disjoint blocks.
+struct Partition {
+    blocks: Vec<Vec<usize>>,
+    block_of: Vec<usize>,
+}
+
+impl Partition {
+    /// Create a partition of two blocks, the states for which `f` returns true and those for
+    /// which it returns false. Empty blocks are omitted.
+    #[inline]
+    fn new<F>(n: usize, f: F) -> Self
+    where
+        F: Fn(usize) -> bool,
+    {
+        let (yes, no): (Vec<_>, Vec<_>) = (0..n).partition(|&q| f(q));
+
+        let mut partition = Self {
+            blocks: Vec::new(),
+            block_of: vec![0; n],
+        };
+        for block in vec![yes, no].into_iter().filter(|b| !b.is_empty()) {
+            let id = partition.blocks.len();
+            for &q in &block {
+                partition.block_of[q] = id;
+            }
+            partition.blocks.push(block);
+        }
+
+        partition
+    }
+
+    /// Split every block that contains both states in `marked` and states not in `marked`. The
+    /// marked states remain in the original block, and the rest are moved into a new block.
+    /// Returns pairs of the original and new block for every split.
+    #[inline]
+    fn split(&mut self, marked: &[usize]) -> Vec<(usize, usize)> {
+        let mut marked_by_block: HashMap<usize, HashSet<usize>> = HashMap::new();
+        for &q in marked {
+            marked_by_block
+                .entry(self.block_of[q])
+                .or_default()
+                .insert(q);
+        }
+
+        let mut splits = Vec::new();
+        for (block, marked) in marked_by_block {
+            if marked.len() == self.blocks[block].len() {
+                continue;
+            }
+
+            let (kept, moved): (Vec<_>, Vec<_>) =
+                self.blocks[block].iter().partition(|q| marked.contains(q));
+
+            let new_block = self.blocks.len();
+            for &q in &moved {
+                self.block_of[q] = new_block;
+            }
+            self.blocks[block] = kept;
+            self.blocks.push(moved);
+
+            splits.push((block, new_block));
+        }
+
+        splits
+    }
+}
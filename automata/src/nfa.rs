@@ -1,16 +1,22 @@
+use crate::adjacency::{Adjacency, Outgoing};
+use crate::budget::{Aborted, Budget};
 use crate::matching::Match;
-use crate::table::Table;
+use crate::sparse::SparseSet;
 
+use std::fmt;
 use std::hash::Hash;
-use std::iter::Peekable;
-use std::{
-    collections::{HashMap, HashSet},
-    rc::Rc,
-};
+use std::iter::{self, Peekable};
+use std::mem;
+use std::sync::{Arc, Mutex};
+use std::{collections::HashSet, rc::Rc};
 
 include!("macros.rs");
 
 /// A non-deterministic finite automaton, or NFA.
+///
+/// Searches compute the epsilon-closures of the NFA once and keep them for later searches, along
+/// with the sets of states used while simulating it. The transitions can only be changed through
+/// the methods of the NFA, and the closures are recomputed when states or transitions are added.
 #[derive(Clone, Debug)]
 pub struct NFA<T: Clone + Eq + Hash> {
     /// An NFA has a single start state.
@@ -20,8 +26,13 @@ pub struct NFA<T: Clone + Eq + Hash> {
     pub total_states: usize,
     /// The set of accepting states.
     pub accepting_states: HashSet<usize>,
-    /// A lookup table for transitions between states.
-    pub transition: Table<usize, Transition<T>, HashSet<usize>>,
+    /// The transitions between states. See [`NFA::adjacency`].
+    transition: Adjacency<Transition<T>>,
+    /// The epsilon-closures of the states, with the number of states and transitions they were
+    /// computed for.
+    closures: Cache<(usize, usize, Arc<EpsilonClosures>)>,
+    /// The current and next sets of states of the last finished simulation.
+    scratch: Cache<(SparseSet, SparseSet)>,
 }
 
 /// A transition between states in an NFA.
@@ -42,11 +53,20 @@ where
     #[allow(clippy::new_without_default)]
     #[inline]
     pub fn new() -> Self {
+        Self::with_states(1, 0)
+    }
+
+    /// Create a new NFA with the given number of states and start state, and no transitions or
+    /// accepting states.
+    #[inline]
+    pub fn with_states(total_states: usize, start_state: usize) -> Self {
         NFA {
-            start_state: 0,
-            total_states: 1,
+            start_state,
+            total_states,
             accepting_states: HashSet::new(),
-            transition: Table::new(),
+            transition: Adjacency::default(),
+            closures: Cache::default(),
+            scratch: Cache::default(),
         }
     }
 
@@ -67,6 +87,15 @@ where
     /// total number of states in the destination NFA.
     #[inline]
     pub fn copy_into(dest: &mut NFA<T>, src: &NFA<T>) {
+        let mut edges = Vec::new();
+        NFA::copy_edges(dest, src, &mut edges);
+        dest.add_transitions(edges);
+    }
+
+    /// Add the states of an NFA to another, as [`NFA::copy_into`] does, and push its transitions
+    /// onto `edges` instead of adding them, so that they are added along with others.
+    #[inline]
+    fn copy_edges(dest: &mut NFA<T>, src: &NFA<T>, edges: &mut Vec<(usize, Transition<T>, usize)>) {
         let offset = dest.total_states;
         // Create new states.
         for _ in 0..src.total_states {
@@ -74,10 +103,8 @@ where
         }
 
         // Clone the transitions.
-        for (start, label, ends) in src.transition.into_iter() {
-            for end in ends {
-                dest.add_transition(*start + offset, *end + offset, (*label).clone());
-            }
+        for (start, label, end) in &src.transition {
+            edges.push((start + offset, label.clone(), end + offset));
         }
     }
 
@@ -87,25 +114,27 @@ where
     #[inline]
     pub fn union(c1: &NFA<T>, c2: &NFA<T>) -> NFA<T> {
         let mut new_nfa = NFA::new();
+        let mut edges = Vec::new();
         let accepting_state = new_nfa.add_state(true);
         let start_state = new_nfa.start_state;
 
         let mut offset = new_nfa.total_states;
 
-        NFA::copy_into(&mut new_nfa, c1);
-        new_nfa.add_epsilon_transition(start_state, c1.start_state + offset);
+        NFA::copy_edges(&mut new_nfa, c1, &mut edges);
+        edges.push((start_state, Transition::Epsilon, c1.start_state + offset));
         for c1_final in c1.accepting_states.iter() {
-            new_nfa.add_epsilon_transition(*c1_final + offset, accepting_state);
+            edges.push((*c1_final + offset, Transition::Epsilon, accepting_state));
         }
 
         offset = new_nfa.total_states;
 
-        NFA::copy_into(&mut new_nfa, c2);
-        new_nfa.add_epsilon_transition(start_state, c2.start_state + offset);
+        NFA::copy_edges(&mut new_nfa, c2, &mut edges);
+        edges.push((start_state, Transition::Epsilon, c2.start_state + offset));
         for c2_final in c2.accepting_states.iter() {
-            new_nfa.add_epsilon_transition(*c2_final + offset, accepting_state);
+            edges.push((*c2_final + offset, Transition::Epsilon, accepting_state));
         }
 
+        new_nfa.add_transitions(edges);
         new_nfa
     }
 
@@ -116,14 +145,16 @@ where
     #[inline]
     pub fn concatenation(c1: &NFA<T>, c2: &NFA<T>) -> NFA<T> {
         let mut new_nfa = c1.clone();
+        let mut edges = Vec::new();
 
         let offset = new_nfa.total_states;
-        NFA::copy_into(&mut new_nfa, &c2);
+        NFA::copy_edges(&mut new_nfa, c2, &mut edges);
 
         // Epsilon transitions from c1 finals to start of c2
         for c1_final in c1.accepting_states.iter() {
-            new_nfa.add_epsilon_transition(*c1_final, c2.start_state + offset);
+            edges.push((*c1_final, Transition::Epsilon, c2.start_state + offset));
         }
+        new_nfa.add_transitions(edges);
         new_nfa.accepting_states = HashSet::new();
 
         // Set accepting states
@@ -138,18 +169,28 @@ where
     #[inline]
     pub fn kleene_star(c1: &NFA<T>) -> NFA<T> {
         let mut new_nfa = NFA::new_epsilon();
+        let mut edges = Vec::new();
         let offset = new_nfa.total_states;
 
-        NFA::copy_into(&mut new_nfa, &c1);
-        new_nfa.add_epsilon_transition(new_nfa.start_state, c1.start_state + offset);
+        NFA::copy_edges(&mut new_nfa, c1, &mut edges);
+        edges.push((
+            new_nfa.start_state,
+            Transition::Epsilon,
+            c1.start_state + offset,
+        ));
 
         for c1_final in c1.accepting_states.iter() {
-            new_nfa.add_epsilon_transition(c1_final + offset, c1.start_state + offset);
-            for accepting_state in new_nfa.accepting_states.clone().iter() {
-                new_nfa.add_epsilon_transition(c1_final + offset, *accepting_state);
+            edges.push((
+                c1_final + offset,
+                Transition::Epsilon,
+                c1.start_state + offset,
+            ));
+            for accepting_state in new_nfa.accepting_states.iter() {
+                edges.push((c1_final + offset, Transition::Epsilon, *accepting_state));
             }
         }
 
+        new_nfa.add_transitions(edges);
         new_nfa
     }
 
@@ -158,10 +199,15 @@ where
     #[inline]
     pub fn combine(cc: &[&NFA<T>]) -> NFA<T> {
         let mut new_nfa = NFA::new();
+        let mut edges = Vec::new();
         let mut offset = new_nfa.total_states;
         for c in cc {
-            NFA::copy_into(&mut new_nfa, c);
-            new_nfa.add_epsilon_transition(new_nfa.start_state, c.start_state + offset);
+            NFA::copy_edges(&mut new_nfa, c, &mut edges);
+            edges.push((
+                new_nfa.start_state,
+                Transition::Epsilon,
+                c.start_state + offset,
+            ));
 
             for c_final in c.accepting_states.iter() {
                 new_nfa.accepting_states.insert(c_final + offset);
@@ -169,6 +215,7 @@ where
             offset += c.total_states;
         }
 
+        new_nfa.add_transitions(edges);
         new_nfa
     }
 
@@ -185,17 +232,34 @@ where
         label
     }
 
-    /// Add a transition. Returns None if one or more of the states does not exist.
+    /// Add a transition, unless the same transition already exists. Returns None if one or more
+    /// of the states does not exist. Each call builds the adjacency anew; see
+    /// [`NFA::add_transitions`] to add many transitions.
     #[inline]
     pub fn add_transition(&mut self, start: usize, end: usize, label: Transition<T>) -> Option<()> {
-        if self.total_states < start + 1 || self.total_states < end + 1 {
-            None
-        } else {
-            self.transition.set_or(start, label, hash_set![end], |v| {
-                v.insert(end);
-            });
-            Some(())
+        self.add_transitions(iter::once((start, label, end)))
+    }
+
+    /// Add (start, label, end) transitions, skipping those that already exist, and build the
+    /// adjacency of the NFA once for all of them. Returns None, adding nothing, if one or more of
+    /// the states does not exist.
+    #[inline]
+    pub fn add_transitions<I>(&mut self, transitions: I) -> Option<()>
+    where
+        I: IntoIterator<Item = (usize, Transition<T>, usize)>,
+    {
+        let transitions: Vec<_> = transitions.into_iter().collect();
+        let total_states = self.total_states;
+        if transitions
+            .iter()
+            .any(|&(start, _, end)| start >= total_states || end >= total_states)
+        {
+            return None;
         }
+
+        let transition = self.transition.extended(total_states, transitions);
+        self.set_transitions(transition);
+        Some(())
     }
 
     // Add a non-epsilon transition. See [add_transition].
@@ -217,64 +281,145 @@ where
 
     /// Returns the transitions and destinations from a specific state.
     #[inline]
-    pub fn transitions_from(&self, state: usize) -> HashMap<&Transition<T>, &HashSet<usize>> {
-        self.transition.get_row(&state)
+    pub fn transitions_from(&self, state: usize) -> Outgoing<'_, Transition<T>> {
+        self.transition.outgoing(state)
+    }
+
+    /// The transitions of the NFA, as an [`Adjacency`]. The outgoing edges of each state are in
+    /// the order they were added.
+    #[inline]
+    pub fn adjacency(&self) -> &Adjacency<Transition<T>> {
+        &self.transition
+    }
+
+    /// Replace every transition of the NFA, and drop the closures kept for the old ones.
+    #[inline]
+    pub(crate) fn set_transitions(&mut self, transition: Adjacency<Transition<T>>) {
+        self.transition = transition;
+        self.clear_cache();
+    }
+
+    /// Returns the states reachable from a specific state on a single epsilon transition.
+    #[inline]
+    fn epsilon_successors(&self, state: usize) -> impl Iterator<Item = usize> + '_ {
+        self.transition
+            .outgoing(state)
+            .filter(|(t, _)| **t == Transition::Epsilon)
+            .map(|(_, dest)| dest)
     }
 
     /// Computes the function epsilon-closure for some given state in the NFA. Returns the set of
     /// all states accessible from the given state on epsilon transitions only.
     #[inline]
     pub fn epsilon_closure(&self, state: usize) -> HashSet<usize> {
-        let transitions = self.transitions_from(state);
-        let mut closure: HashSet<_> = transitions
-            .into_iter()
-            .filter(|(t, _)| **t == Transition::Epsilon)
-            .flat_map(|(_, dest)| dest.iter().flat_map(|&i| self.epsilon_closure(i)))
-            .collect();
-        closure.insert(state);
-        closure
+        self.epsilon_closure_set(&hash_set![state])
     }
 
     /// Computes the union of epsilon-closures for each state in the given set of states.
     #[inline]
     pub fn epsilon_closure_set(&self, state_set: &HashSet<usize>) -> HashSet<usize> {
         let mut set = state_set.clone();
-        for state in state_set.iter() {
-            let state_closure = self.epsilon_closure(*state);
-            set = set.union(&state_closure).cloned().collect();
+        let mut stack: Vec<usize> = state_set.iter().cloned().collect();
+        while let Some(state) = stack.pop() {
+            for next in self.epsilon_successors(state) {
+                if set.insert(next) {
+                    stack.push(next);
+                }
+            }
         }
         set
     }
 
+    /// Computes the epsilon-closure of every state in the NFA.
+    #[inline]
+    pub fn epsilon_closures(&self) -> EpsilonClosures {
+        // visited[s] == state when s has been added to the closure of state.
+        let mut visited = vec![usize::MAX; self.total_states];
+        let mut stack = Vec::new();
+
+        let closures = (0..self.total_states)
+            .map(|state| {
+                let mut closure = vec![state];
+                visited[state] = state;
+                stack.push(state);
+
+                while let Some(s) = stack.pop() {
+                    for next in self.epsilon_successors(s) {
+                        if visited[next] != state {
+                            visited[next] = state;
+                            closure.push(next);
+                            stack.push(next);
+                        }
+                    }
+                }
+
+                closure.sort_unstable();
+                closure
+            })
+            .collect();
+
+        EpsilonClosures { closures }
+    }
+
+    /// Returns the epsilon-closures kept from an earlier search, or computes and keeps them if
+    /// the NFA has changed since.
     #[inline]
-    fn move_set<S>(&self, state_set: &HashSet<usize>, input: &S) -> HashSet<usize>
+    fn cached_epsilon_closures(&self) -> Arc<EpsilonClosures> {
+        let key = (self.total_states, self.transition.total_edges());
+        let closures = match self.closures.take() {
+            Some((states, edges, closures)) if (states, edges) == key => closures,
+            _ => Arc::new(self.epsilon_closures()),
+        };
+        self.closures.put((key.0, key.1, closures.clone()));
+        closures
+    }
+
+    /// Drop what searches have kept from earlier searches, to free the memory they use. Adding
+    /// states or transitions is noticed by the next search without this.
+    #[inline]
+    pub fn clear_cache(&self) {
+        self.closures.take();
+        self.scratch.take();
+    }
+
+    #[inline]
+    pub fn iter_on<I>(&self, input: I) -> Iter<'_, T, I::IntoIter>
     where
-        T: PartialEq<S>,
+        I: IntoIterator,
+        T: PartialEq<I::Item>,
     {
-        let mut set = HashSet::new();
-        for state in state_set.iter() {
-            let transitions = self.transitions_from(*state);
-            let input_transitions = transitions
-                .into_iter()
-                .filter(|(t, _)| match *t {
-                    Transition::Some(symbol) => *symbol == *input,
-                    Transition::Epsilon => false,
-                })
-                .flat_map(|(_, dest)| dest.iter().cloned())
-                .collect();
-            set = set.union(&input_transitions).cloned().collect();
-        }
-        set
+        self.iter_on_impl(input, None)
     }
 
+    /// Iterate over the input as [`NFA::iter_on`] does, taking a step from the budget for each
+    /// symbol read. The iterator ends early if the budget runs out, which the budget records.
     #[inline]
-    pub fn iter_on<I>(&self, input: I) -> Iter<'_, T, I::IntoIter>
+    pub fn iter_on_within<'a, I>(
+        &'a self,
+        input: I,
+        budget: &'a Budget<'a>,
+    ) -> Iter<'a, T, I::IntoIter>
+    where
+        I: IntoIterator,
+        T: PartialEq<I::Item>,
+    {
+        self.iter_on_impl(input, Some(budget))
+    }
+
+    #[inline]
+    fn iter_on_impl<'a, I>(
+        &'a self,
+        input: I,
+        budget: Option<&'a Budget<'a>>,
+    ) -> Iter<'a, T, I::IntoIter>
     where
         I: IntoIterator,
         T: PartialEq<I::Item>,
     {
         Iter {
-            nfa: &self,
+            nfa: self,
+            sim: Simulation::new(self),
+            budget,
             input: input.into_iter().peekable(),
             last: None,
         }
@@ -287,6 +432,7 @@ where
         T: PartialEq<I::Item>,
     {
         IntoIter {
+            sim: Simulation::new(&self),
             nfa: self,
             input: input.into_iter().peekable(),
             last: None,
@@ -294,6 +440,110 @@ where
     }
 }
 
+/// The precomputed epsilon-closure of every state in an NFA. See [NFA::epsilon_closures].
+#[derive(Clone, Debug)]
+pub struct EpsilonClosures {
+    closures: Vec<Vec<usize>>,
+}
+
+impl EpsilonClosures {
+    /// Returns the sorted epsilon-closure of the given state.
+    #[inline]
+    pub fn get(&self, state: usize) -> &[usize] {
+        &self.closures[state]
+    }
+
+    /// Returns the union of epsilon-closures for each of the given states.
+    #[inline]
+    pub fn closure_set<I>(&self, states: I) -> HashSet<usize>
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        states
+            .into_iter()
+            .flat_map(|state| self.get(state).iter().cloned())
+            .collect()
+    }
+}
+
+/// A value kept between searches. Only one search takes the value at a time; a search that
+/// finds it taken computes its own. Clones of an NFA start with an empty cache.
+struct Cache<V> {
+    slot: Mutex<Option<V>>,
+}
+
+impl<V> Cache<V> {
+    #[inline]
+    fn take(&self) -> Option<V> {
+        self.slot.try_lock().ok()?.take()
+    }
+
+    #[inline]
+    fn put(&self, value: V) {
+        if let Ok(mut slot) = self.slot.try_lock() {
+            *slot = Some(value);
+        }
+    }
+}
+
+impl<V> Default for Cache<V> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            slot: Mutex::new(None),
+        }
+    }
+}
+
+impl<V> Clone for Cache<V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl<V> fmt::Debug for Cache<V> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cache").finish()
+    }
+}
+
+/// Scratch space for simulating an NFA. The current and next sets of states are kept in sparse
+/// sets, so each step costs time proportional to the number of active states and transitions
+/// rather than to allocation. The sets are taken from the NFA's cache and given back when the
+/// iterator is dropped, so consecutive searches share them.
+#[derive(Clone, Debug)]
+struct Simulation {
+    closures: Arc<EpsilonClosures>,
+    current: SparseSet,
+    next: SparseSet,
+}
+
+impl Simulation {
+    #[inline]
+    fn new<T: Clone + Eq + Hash>(nfa: &NFA<T>) -> Self {
+        let (current, next) = match nfa.scratch.take() {
+            Some((current, next)) if current.capacity() == nfa.total_states => (current, next),
+            _ => (
+                SparseSet::new(nfa.total_states),
+                SparseSet::new(nfa.total_states),
+            ),
+        };
+
+        Self {
+            closures: nfa.cached_epsilon_closures(),
+            current,
+            next,
+        }
+    }
+
+    #[inline]
+    fn is_accepting<T: Clone + Eq + Hash>(&self, nfa: &NFA<T>) -> bool {
+        self.current.iter().any(|s| nfa.is_accepting_state(s))
+    }
+}
+
 pub struct Iter<'a, T, I>
 where
     T: Clone + Eq + Hash,
@@ -301,9 +551,24 @@ where
     I: Iterator,
 {
     nfa: &'a NFA<T>,
+    sim: Simulation,
+    budget: Option<&'a Budget<'a>>,
 
     input: Peekable<I>,
-    last: Option<(LastIterState, HashSet<usize>)>,
+    last: Option<LastIterState>,
+}
+
+impl<'a, T, I> Iter<'a, T, I>
+where
+    T: Clone + Eq + Hash,
+    T: PartialEq<I::Item>,
+    I: Iterator,
+{
+    /// The states the NFA is in after the last step. Empty if the NFA is stuck.
+    #[inline]
+    pub fn states(&self) -> &[usize] {
+        self.sim.current.as_slice()
+    }
 }
 
 impl<'a, T, I> Iterator for Iter<'a, T, I>
@@ -316,7 +581,27 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        iter_on_next(&self.nfa, &mut self.input, &mut self.last)
+        iter_on_next(
+            self.nfa,
+            &mut self.sim,
+            self.budget,
+            &mut self.input,
+            &mut self.last,
+        )
+    }
+}
+
+impl<'a, T, I> Drop for Iter<'a, T, I>
+where
+    T: Clone + Eq + Hash,
+    T: PartialEq<I::Item>,
+    I: Iterator,
+{
+    #[inline]
+    fn drop(&mut self) {
+        let current = mem::take(&mut self.sim.current);
+        let next = mem::take(&mut self.sim.next);
+        self.nfa.scratch.put((current, next));
     }
 }
 
@@ -327,9 +612,23 @@ where
     I: Iterator,
 {
     nfa: NFA<T>,
+    sim: Simulation,
 
     input: Peekable<I>,
-    last: Option<(LastIterState, HashSet<usize>)>,
+    last: Option<LastIterState>,
+}
+
+impl<T, I> IntoIter<T, I>
+where
+    T: Clone + Eq + Hash,
+    T: PartialEq<I::Item>,
+    I: Iterator,
+{
+    /// The states the NFA is in after the last step. Empty if the NFA is stuck.
+    #[inline]
+    pub fn states(&self) -> &[usize] {
+        self.sim.current.as_slice()
+    }
 }
 
 impl<T, I> Iterator for IntoIter<T, I>
@@ -342,18 +641,26 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        iter_on_next(&self.nfa, &mut self.input, &mut self.last)
+        iter_on_next(
+            &self.nfa,
+            &mut self.sim,
+            None,
+            &mut self.input,
+            &mut self.last,
+        )
     }
 }
 
+/// A step of an NFA over its input. Each state records whether the NFA accepts the input
+/// consumed so far; the states themselves can be retrieved from the iterator.
 #[derive(Debug)]
 pub enum IterState<I>
 where
     I: Iterator,
 {
-    Start(HashMap<usize, bool>),
-    Normal(I::Item, HashMap<usize, bool>),
-    Stuck(HashSet<usize>),
+    Start(bool),
+    Normal(I::Item, bool),
+    Stuck,
 }
 
 enum LastIterState {
@@ -365,57 +672,69 @@ enum LastIterState {
 #[inline]
 fn iter_on_next<T, I>(
     nfa: &NFA<T>,
+    sim: &mut Simulation,
+    budget: Option<&Budget<'_>>,
     input: &mut Peekable<I>,
-    last: &mut Option<(LastIterState, HashSet<usize>)>,
+    last: &mut Option<LastIterState>,
 ) -> Option<IterState<I>>
 where
     T: Clone + Eq + Hash,
     T: PartialEq<I::Item>,
     I: Iterator,
 {
-    let current_set = match last {
+    match last {
         None => {
-            let starting_set = nfa.epsilon_closure(nfa.start_state);
-            let starting_set_map = starting_set
-                .iter()
-                .map(|s| (*s, nfa.is_accepting_state(s)))
-                .collect();
-
-            *last = Some((LastIterState::Start, starting_set));
-            return Some(IterState::Start(starting_set_map));
+            sim.current.clear();
+            for &s in sim.closures.get(nfa.start_state) {
+                sim.current.insert(s);
+            }
+
+            *last = Some(LastIterState::Start);
+            return Some(IterState::Start(sim.is_accepting(nfa)));
         }
-        Some((LastIterState::Start | LastIterState::Normal, set)) => set,
+        Some(LastIterState::Start | LastIterState::Normal) => {}
         // If we were last stuck, return None to indicate that last state was stuck.
-        Some((LastIterState::Stuck, _)) => return None,
+        Some(LastIterState::Stuck) => return None,
     };
 
-    let peek_is = match input.peek() {
-        Some(v) => v,
-        // No more input, so last item was the final.
-        None => return None,
-    };
+    // No more input, so last item was the final.
+    let peek_is = input.peek()?;
+
+    // Reading a symbol takes a step, so stop here if the budget has run out.
+    if let Some(budget) = budget {
+        budget.step().ok()?;
+    }
 
-    let moved_set = nfa.move_set(&current_set, peek_is);
-    let next_set = nfa.epsilon_closure_set(&moved_set);
+    let Simulation {
+        closures,
+        current,
+        next,
+    } = sim;
+    next.clear();
+    for &state in current.iter() {
+        for (t, dest) in nfa.transition.outgoing(state) {
+            match t {
+                Transition::Some(symbol) if *symbol == *peek_is => {}
+                _ => continue,
+            }
 
-    let next = if !next_set.is_empty() {
+            for &s in closures.get(dest) {
+                next.insert(s);
+            }
+        }
+    }
+    std::mem::swap(current, next);
+
+    if !sim.current.is_empty() {
         // Consume input symbol.
         let is = input.next().unwrap();
 
-        // Check if states are accepting ones.
-        let next_set_map = next_set
-            .iter()
-            .map(|s| (*s, nfa.is_accepting_state(s)))
-            .collect();
-
-        *last = Some((LastIterState::Normal, next_set));
-        Some(IterState::Normal(is, next_set_map))
+        *last = Some(LastIterState::Normal);
+        Some(IterState::Normal(is, sim.is_accepting(nfa)))
     } else {
-        *last = Some((LastIterState::Stuck, next_set.clone()));
-        Some(IterState::Stuck(next_set))
-    };
-
-    next
+        *last = Some(LastIterState::Stuck);
+        Some(IterState::Stuck)
+    }
 }
 
 impl<T> NFA<T>
@@ -429,11 +748,31 @@ where
         T: PartialEq<I::Item>,
         I: IntoIterator,
     {
-        match self.iter_on(input).last() {
-            Some(IterState::Start(state_map) | IterState::Normal(_, state_map)) => {
-                state_map.values().any(|&b| b)
-            }
-            Some(IterState::Stuck(_)) => false,
+        self.is_match_impl(input, None)
+    }
+
+    /// Determines if the given input is accepted by the NFA, or aborts if the budget runs out
+    /// first.
+    #[inline]
+    pub fn is_match_within<I>(&self, input: I, budget: &Budget<'_>) -> Result<bool, Aborted>
+    where
+        T: PartialEq<I::Item>,
+        I: IntoIterator,
+    {
+        let is_match = self.is_match_impl(input, Some(budget));
+        budget.check()?;
+        Ok(is_match)
+    }
+
+    #[inline]
+    fn is_match_impl<I>(&self, input: I, budget: Option<&Budget<'_>>) -> bool
+    where
+        T: PartialEq<I::Item>,
+        I: IntoIterator,
+    {
+        match self.iter_on_impl(input, budget).last() {
+            Some(IterState::Start(is_final) | IterState::Normal(_, is_final)) => is_final,
+            Some(IterState::Stuck) => false,
             None => unreachable!(),
         }
     }
@@ -453,7 +792,25 @@ where
         T: PartialEq<I::Item>,
         I: IntoIterator,
     {
-        self.find_at_impl(input, start, true)
+        self.find_at_impl(input, start, true, None)
+    }
+
+    /// Find the shortest match as [`NFA::find_shortest_at`] does, or abort if the budget runs
+    /// out first.
+    #[inline]
+    pub fn find_shortest_at_within<I>(
+        &self,
+        input: I,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match<I::Item>>, Aborted>
+    where
+        T: PartialEq<I::Item>,
+        I: IntoIterator,
+    {
+        let m = self.find_at_impl(input, start, true, Some(budget));
+        budget.check()?;
+        Ok(m)
     }
 
     #[inline]
@@ -471,35 +828,60 @@ where
         T: PartialEq<I::Item>,
         I: IntoIterator,
     {
-        self.find_at_impl(input, start, false)
+        self.find_at_impl(input, start, false, None)
+    }
+
+    /// Find the longest match as [`NFA::find_at`] does, or abort if the budget runs out first.
+    #[inline]
+    pub fn find_at_within<I>(
+        &self,
+        input: I,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match<I::Item>>, Aborted>
+    where
+        T: PartialEq<I::Item>,
+        I: IntoIterator,
+    {
+        let m = self.find_at_impl(input, start, false, Some(budget));
+        budget.check()?;
+        Ok(m)
     }
 
     #[inline]
-    fn find_at_impl<I>(&self, input: I, start: usize, shortest: bool) -> Option<Match<I::Item>>
+    fn find_at_impl<I>(
+        &self,
+        input: I,
+        start: usize,
+        shortest: bool,
+        budget: Option<&Budget<'_>>,
+    ) -> Option<Match<I::Item>>
     where
         T: PartialEq<I::Item>,
         I: IntoIterator,
     {
         let mut last_match = None;
-        let iter = self.iter_on(input).skip(start).enumerate();
+        let iter = self
+            .iter_on_impl(input.into_iter().skip(start), budget)
+            .enumerate();
 
         // Ensure span dropped before unwrapping Rc's.
         {
             let mut span = Vec::new();
             for (i, iter_state) in iter {
                 let is_final = match iter_state {
-                    IterState::Start(state_map) => state_map.values().any(|&b| b),
-                    IterState::Normal(is, state_map) => {
+                    IterState::Start(is_final) => is_final,
+                    IterState::Normal(is, is_final) => {
                         let is_rc = Rc::new(is);
                         span.push(is_rc);
 
-                        state_map.values().any(|&b| b)
+                        is_final
                     }
-                    IterState::Stuck(_) => break,
+                    IterState::Stuck => break,
                 };
 
                 if is_final {
-                    last_match = Some(Match::new(start, i + 1, span.clone()));
+                    last_match = Some(Match::new(start, start + i, span.clone()));
                     if shortest {
                         break;
                     }
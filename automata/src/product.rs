@@ -0,0 +1,163 @@
+use crate::adjacency::Adjacency;
+use crate::convert::Disjoin;
+use crate::dfa::DFA;
+
+use std::collections::{hash_map::Entry, HashMap, VecDeque};
+use std::hash::Hash;
+
+/// Must be implemented by DFA transition symbol types to complete a DFA, where every state has a
+/// transition on every symbol.
+pub trait Universe: Disjoin {
+    /// Return the symbol that covers everything not covered by any of the given symbols, or None
+    /// if the given symbols cover everything.
+    fn complement_of(vec: Vec<&Self>) -> Option<Self>;
+}
+
+impl<T> DFA<T>
+where
+    T: Clone + Universe + Eq + Hash + Ord,
+{
+    /// Determine if this DFA accepts every string the other DFA accepts. If not, returns the
+    /// symbols of a shortest string accepted by this DFA but not the other.
+    #[inline]
+    pub fn is_subset_of(&self, other: &DFA<T>) -> Result<(), Vec<T>> {
+        match self.difference(other).shortest_accepted() {
+            Some(counterexample) => Err(counterexample),
+            None => Ok(()),
+        }
+    }
+
+    /// Determine if both DFAs accept the same strings. If not, returns the symbols of a shortest
+    /// string accepted by exactly one of them.
+    #[inline]
+    pub fn is_equivalent_to(&self, other: &DFA<T>) -> Result<(), Vec<T>> {
+        match self.symmetric_difference(other).shortest_accepted() {
+            Some(counterexample) => Err(counterexample),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T> DFA<T>
+where
+    T: Clone + Universe + Eq + Hash,
+{
+    /// Create an equivalent DFA in which every state has a transition on every symbol. Missing
+    /// transitions lead to a new, non-accepting dead state, which is only added if some state
+    /// needs it.
+    #[inline]
+    pub fn complete(&self) -> DFA<T> {
+        let adjacency = self.adjacency();
+        let mut dfa = self.clone();
+
+        let missing: Vec<(usize, T)> = (0..self.total_states)
+            .filter_map(|s| {
+                T::complement_of(adjacency.labels(s).iter().collect()).map(|rest| (s, rest))
+            })
+            .collect();
+        if missing.is_empty() {
+            return dfa;
+        }
+
+        // The missing transitions are added at once, after the existing ones of each state.
+        let dead = dfa.add_state(false);
+        let mut edges: Vec<_> = adjacency
+            .edges()
+            .map(|(s, t, next)| (s, t.clone(), next))
+            .collect();
+        edges.extend(missing.into_iter().map(|(s, rest)| (s, rest, dead)));
+        if let Some(all) = T::complement_of(Vec::new()) {
+            edges.push((dead, all, dead));
+        }
+        dfa.transition = Adjacency::new(dfa.total_states, edges);
+
+        dfa
+    }
+
+    /// Create a DFA that accepts exactly the strings this DFA rejects.
+    #[inline]
+    pub fn complement(&self) -> DFA<T> {
+        let mut dfa = self.complete();
+        dfa.accepting_states = (0..dfa.total_states)
+            .filter(|s| !dfa.accepting_states.contains(s))
+            .collect();
+        dfa
+    }
+
+    /// Create a DFA that accepts the strings accepted by both DFAs.
+    #[inline]
+    pub fn intersection(&self, other: &DFA<T>) -> DFA<T> {
+        self.product(other, |a, b| a && b)
+    }
+
+    /// Create a DFA that accepts the strings accepted by either DFA.
+    #[inline]
+    pub fn union(&self, other: &DFA<T>) -> DFA<T> {
+        self.product(other, |a, b| a || b)
+    }
+
+    /// Create a DFA that accepts the strings accepted by this DFA but not the other.
+    #[inline]
+    pub fn difference(&self, other: &DFA<T>) -> DFA<T> {
+        self.product(other, |a, b| a && !b)
+    }
+
+    /// Create a DFA that accepts the strings accepted by exactly one of the DFAs.
+    #[inline]
+    pub fn symmetric_difference(&self, other: &DFA<T>) -> DFA<T> {
+        self.product(other, |a, b| a != b)
+    }
+
+    /// Run both DFAs in parallel using the product construction. A state of the new DFA is a
+    /// pair of states of the completed operands, and it is accepting when `accept` returns true
+    /// for whether each of the pair is accepting. Only pairs reachable from the pair of start
+    /// states are constructed.
+    #[inline]
+    pub fn product<F>(&self, other: &DFA<T>, accept: F) -> DFA<T>
+    where
+        F: Fn(bool, bool) -> bool,
+    {
+        let (c1, c2) = (self.complete(), other.complete());
+        let (a1, a2) = (c1.adjacency(), c2.adjacency());
+
+        let mut dfa = DFA::new();
+        let mut labels = HashMap::new();
+        let mut queue = VecDeque::new();
+        let mut edges = Vec::new();
+
+        let start = (c1.start_state, c2.start_state);
+        labels.insert(start, dfa.start_state);
+        queue.push_back(start);
+        while let Some((p, q)) = queue.pop_front() {
+            let label = labels[&(p, q)];
+            if accept(c1.is_accepting_state(&p), c2.is_accepting_state(&q)) {
+                dfa.accepting_states.insert(label);
+            }
+
+            let symbols = T::disjoin(a1.labels(p).iter().chain(a2.labels(q)).collect());
+            for t in symbols {
+                // Both operands are complete, so each has a transition on every symbol.
+                let next_p = a1.outgoing(p).find(|(u, _)| u.contains(&t));
+                let next_q = a2.outgoing(q).find(|(u, _)| u.contains(&t));
+                let next = match (next_p, next_q) {
+                    (Some((_, next_p)), Some((_, next_q))) => (next_p, next_q),
+                    _ => continue,
+                };
+
+                let next_label = match labels.entry(next) {
+                    Entry::Occupied(e) => *e.get(),
+                    Entry::Vacant(e) => {
+                        let l = dfa.add_state(false);
+                        e.insert(l);
+                        queue.push_back(next);
+                        l
+                    }
+                };
+                edges.push((label, t, next_label));
+            }
+        }
+
+        dfa.add_transitions(edges);
+        dfa
+    }
+}
@@ -0,0 +1,73 @@
+use crate::adjacency::Adjacency;
+use crate::dfa::DFA;
+use crate::nfa::{Transition, NFA};
+
+use std::collections::HashSet;
+use std::hash::Hash;
+
+impl<T> NFA<T>
+where
+    T: Clone + Eq + Hash,
+{
+    /// Create an NFA that accepts the reverse of every string this NFA accepts. Every transition
+    /// is reversed, a new start state has epsilon transitions to each of the old accepting
+    /// states, and the old start state is the only accepting state.
+    #[inline]
+    pub fn reverse(&self) -> NFA<T> {
+        reversed(
+            self.total_states,
+            self.start_state,
+            &self.accepting_states,
+            self.adjacency()
+                .edges()
+                .map(|(start, label, end)| (end, label.clone(), start)),
+        )
+    }
+}
+
+impl<T> DFA<T>
+where
+    T: Clone + Eq + Hash,
+{
+    /// Create an NFA that accepts the reverse of every string this DFA accepts, as with
+    /// [`NFA::reverse`]. The reverse of a DFA is generally not deterministic, since a state may
+    /// be entered on the same symbol from several states; convert the result into a DFA to
+    /// search with it.
+    #[inline]
+    pub fn reverse(&self) -> NFA<T> {
+        reversed(
+            self.total_states,
+            self.start_state,
+            &self.accepting_states,
+            self.transition
+                .edges()
+                .map(|(start, t, end)| (end, Transition::Some(t.clone()), start)),
+        )
+    }
+}
+
+/// Create a reversed automaton from its reversed transitions. The states keep their labels, and
+/// a new start state is added after them.
+#[inline]
+fn reversed<T, I>(
+    total_states: usize,
+    start_state: usize,
+    accepting: &HashSet<usize>,
+    edges: I,
+) -> NFA<T>
+where
+    T: Clone + Eq + Hash,
+    I: Iterator<Item = (usize, Transition<T>, usize)>,
+{
+    // The transitions are all distinct, so they are stored at once rather than added one by one.
+    let start = total_states;
+    let edges = accepting
+        .iter()
+        .map(|&s| (start, Transition::Epsilon, s))
+        .chain(edges);
+
+    let mut nfa = NFA::with_states(total_states + 1, start);
+    nfa.set_transitions(Adjacency::new(total_states + 1, edges));
+    nfa.accepting_states.insert(start_state);
+    nfa
+}
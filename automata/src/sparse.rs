@@ -0,0 +1,69 @@
+/// A set of integers in `0..capacity` that preserves insertion order and can be cleared in
+/// constant time. Membership is tracked with a pair of arrays, so inserting and testing never
+/// allocate.
+#[derive(Clone, Debug, Default)]
+pub struct SparseSet {
+    dense: Vec<usize>,
+    sparse: Vec<usize>,
+}
+
+impl SparseSet {
+    /// Create an empty set that can hold the integers in `0..capacity`.
+    #[inline]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            dense: Vec::with_capacity(capacity),
+            sparse: vec![0; capacity],
+        }
+    }
+
+    /// The largest number of elements the set can hold.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.sparse.len()
+    }
+
+    #[inline]
+    pub fn contains(&self, value: usize) -> bool {
+        let i = self.sparse[value];
+        i < self.dense.len() && self.dense[i] == value
+    }
+
+    /// Add a value to the set. Returns false if the value was already present.
+    #[inline]
+    pub fn insert(&mut self, value: usize) -> bool {
+        if self.contains(value) {
+            return false;
+        }
+
+        self.sparse[value] = self.dense.len();
+        self.dense.push(value);
+        true
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.dense.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.dense.is_empty()
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.dense.clear();
+    }
+
+    /// The elements of the set, in insertion order.
+    #[inline]
+    pub fn as_slice(&self) -> &[usize] {
+        &self.dense
+    }
+
+    #[inline]
+    pub fn iter(&self) -> std::slice::Iter<'_, usize> {
+        self.dense.iter()
+    }
+}
@@ -0,0 +1,326 @@
+use crate::adjacency::Adjacency;
+use crate::nfa::{Transition, NFA};
+
+use std::collections::{hash_map::Entry, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::iter;
+
+/// A finite-state transducer: an NFA whose transitions also write an output symbol. A run over
+/// an input reads the input labels along a path to an accepting state and writes the output
+/// labels along the same path. Either label of a transition may be epsilon, so a transition may
+/// read without writing or write without reading.
+///
+/// Input labels are matched against the input like the labels of an [`NFA`], so they may be sets
+/// of symbols, but output labels are written as they are.
+#[derive(Clone, Debug)]
+pub struct Transducer<T: Clone + Eq + Hash, U: Clone + Eq + Hash> {
+    /// A transducer has a single start state.
+    pub start_state: usize,
+    /// The number of total states in the transducer. There is a state labeled i for every i
+    /// where 0 <= i < total_states.
+    pub total_states: usize,
+    /// The set of accepting states.
+    pub accepting_states: HashSet<usize>,
+    /// The transitions between states.
+    pub transition: Adjacency<Label<T, U>>,
+}
+
+/// The labels of a transition of a transducer.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Label<T: Clone + Eq + Hash, U: Clone + Eq + Hash> {
+    /// The symbol read, or epsilon if the transition reads nothing.
+    pub input: Transition<T>,
+    /// The symbol written, or epsilon if the transition writes nothing.
+    pub output: Transition<U>,
+}
+
+impl<T, U> Label<T, U>
+where
+    T: Clone + Eq + Hash,
+    U: Clone + Eq + Hash,
+{
+    #[inline]
+    pub fn new(input: Transition<T>, output: Transition<U>) -> Self {
+        Self { input, output }
+    }
+}
+
+impl<T, U> Transducer<T, U>
+where
+    T: Clone + Eq + Hash,
+    U: Clone + Eq + Hash,
+{
+    /// Create a new transducer with a single start state.
+    #[allow(clippy::new_without_default)]
+    #[inline]
+    pub fn new() -> Self {
+        Transducer {
+            start_state: 0,
+            total_states: 1,
+            accepting_states: HashSet::new(),
+            transition: Adjacency::default(),
+        }
+    }
+
+    /// Add a new state to the transducer and return its label.
+    #[inline]
+    pub fn add_state(&mut self, is_final: bool) -> usize {
+        let label = self.total_states;
+        if is_final {
+            self.accepting_states.insert(label);
+        }
+
+        self.total_states += 1;
+        label
+    }
+
+    /// Add a transition, unless the same transition already exists. Returns None if one or more
+    /// of the states does not exist. Each call builds the adjacency anew; see
+    /// [`Transducer::add_transitions`] to add many transitions.
+    #[inline]
+    pub fn add_transition(
+        &mut self,
+        start: usize,
+        end: usize,
+        input: Transition<T>,
+        output: Transition<U>,
+    ) -> Option<()> {
+        self.add_transitions(iter::once((start, Label::new(input, output), end)))
+    }
+
+    /// Add (start, label, end) transitions, skipping those that already exist, and build the
+    /// adjacency of the transducer once for all of them. Returns None, adding nothing, if one or
+    /// more of the states does not exist.
+    #[inline]
+    pub fn add_transitions<I>(&mut self, transitions: I) -> Option<()>
+    where
+        I: IntoIterator<Item = (usize, Label<T, U>, usize)>,
+    {
+        let transitions: Vec<_> = transitions.into_iter().collect();
+        if transitions
+            .iter()
+            .any(|&(start, _, end)| start >= self.total_states || end >= self.total_states)
+        {
+            return None;
+        }
+
+        self.transition = self.transition.extended(self.total_states, transitions);
+        Some(())
+    }
+
+    #[inline]
+    pub fn is_accepting_state(&self, label: &usize) -> bool {
+        self.accepting_states.contains(label)
+    }
+
+    /// The transitions of the transducer, as an [`Adjacency`]. The outgoing edges of each state
+    /// are in the order they were added.
+    #[inline]
+    pub fn adjacency(&self) -> &Adjacency<Label<T, U>> {
+        &self.transition
+    }
+
+    /// Create a transducer with the same states and transitions whose labels are mapped by the
+    /// given function.
+    #[inline]
+    fn map_labels<V, W, F>(&self, f: F) -> Transducer<V, W>
+    where
+        V: Clone + Eq + Hash,
+        W: Clone + Eq + Hash,
+        F: Fn(&Label<T, U>) -> Label<V, W>,
+    {
+        Transducer {
+            start_state: self.start_state,
+            total_states: self.total_states,
+            accepting_states: self.accepting_states.clone(),
+            transition: Adjacency::new(
+                self.total_states,
+                self.transition
+                    .edges()
+                    .map(|(start, label, end)| (start, f(label), end)),
+            ),
+        }
+    }
+
+    /// Create the inverse transducer, which reads what this one writes and writes what it reads.
+    #[inline]
+    pub fn invert(&self) -> Transducer<U, T> {
+        self.map_labels(|label| Label::new(label.output.clone(), label.input.clone()))
+    }
+
+    /// Create an NFA that accepts the inputs this transducer accepts, by dropping the output
+    /// labels. Transitions that read nothing become epsilon transitions.
+    #[inline]
+    pub fn input_nfa(&self) -> NFA<T> {
+        self.project(|label| label.input.clone())
+    }
+
+    /// Create an NFA that accepts the outputs this transducer writes, by dropping the input
+    /// labels. Transitions that write nothing become epsilon transitions.
+    #[inline]
+    pub fn output_nfa(&self) -> NFA<U> {
+        self.project(|label| label.output.clone())
+    }
+
+    #[inline]
+    fn project<V, F>(&self, f: F) -> NFA<V>
+    where
+        V: Clone + Eq + Hash,
+        F: Fn(&Label<T, U>) -> Transition<V>,
+    {
+        let mut nfa = NFA::new();
+        for _ in 1..self.total_states {
+            nfa.add_state(false);
+        }
+        nfa.start_state = self.start_state;
+        nfa.accepting_states = self.accepting_states.clone();
+        nfa.add_transitions(
+            self.transition
+                .edges()
+                .map(|(start, label, end)| (start, f(label), end)),
+        );
+        nfa
+    }
+
+    /// Compose this transducer with another that reads what this one writes. The composition
+    /// reads what this transducer reads and writes what the other writes when given this
+    /// transducer's output.
+    ///
+    /// A state of the composition is a pair of states of the operands. A transition that writes a
+    /// symbol is paired with each transition of the other transducer that reads it, while a
+    /// transition of this transducer that writes nothing, or of the other that reads nothing,
+    /// moves that transducer alone. Only pairs reachable from the pair of start states are
+    /// constructed.
+    #[inline]
+    pub fn compose<V, W>(&self, other: &Transducer<V, W>) -> Transducer<T, W>
+    where
+        V: Clone + Eq + Hash + PartialEq<U>,
+        W: Clone + Eq + Hash,
+    {
+        let (a1, a2) = (self.adjacency(), other.adjacency());
+
+        let mut transducer = Transducer::new();
+        let mut edges = Vec::new();
+        let mut labels = HashMap::new();
+        let mut queue = VecDeque::new();
+
+        let start = (self.start_state, other.start_state);
+        labels.insert(start, transducer.start_state);
+        queue.push_back(start);
+        while let Some((p, q)) = queue.pop_front() {
+            let label = labels[&(p, q)];
+            if self.is_accepting_state(&p) && other.is_accepting_state(&q) {
+                transducer.accepting_states.insert(label);
+            }
+
+            let mut moves = Vec::new();
+            for (l1, next_p) in a1.outgoing(p) {
+                match &l1.output {
+                    Transition::Epsilon => {
+                        moves.push((l1.input.clone(), Transition::Epsilon, (next_p, q)));
+                    }
+                    Transition::Some(u) => {
+                        for (l2, next_q) in a2.outgoing(q) {
+                            if matches!(&l2.input, Transition::Some(v) if v == u) {
+                                moves.push((l1.input.clone(), l2.output.clone(), (next_p, next_q)));
+                            }
+                        }
+                    }
+                }
+            }
+            for (l2, next_q) in a2.outgoing(q) {
+                if l2.input == Transition::Epsilon {
+                    moves.push((Transition::Epsilon, l2.output.clone(), (p, next_q)));
+                }
+            }
+
+            for (input, output, next) in moves {
+                let next_label = match labels.entry(next) {
+                    Entry::Occupied(e) => *e.get(),
+                    Entry::Vacant(e) => {
+                        let l = transducer.add_state(false);
+                        e.insert(l);
+                        queue.push_back(next);
+                        l
+                    }
+                };
+                edges.push((label, Label::new(input, output), next_label));
+            }
+        }
+
+        transducer.add_transitions(edges);
+        transducer
+    }
+
+    /// Run the transducer over the input and return every output it can write along a path that
+    /// reads the whole input and ends in an accepting state. The set is empty if the input is not
+    /// accepted.
+    ///
+    /// Cycles of transitions that read nothing are not followed around, so the set of outputs is
+    /// always finite.
+    #[inline]
+    pub fn run<I>(&self, input: I) -> HashSet<Vec<U>>
+    where
+        T: PartialEq<I::Item>,
+        I: IntoIterator,
+    {
+        let mut configs = self.closure(vec![(self.start_state, Vec::new())]);
+        for c in input {
+            let mut next = Vec::new();
+            for (state, output) in &configs {
+                for (label, end) in self.transition.outgoing(*state) {
+                    if !matches!(&label.input, Transition::Some(t) if *t == c) {
+                        continue;
+                    }
+                    let mut output = output.clone();
+                    if let Transition::Some(u) = &label.output {
+                        output.push(u.clone());
+                    }
+                    next.push((end, output));
+                }
+            }
+
+            if next.is_empty() {
+                return HashSet::new();
+            }
+            configs = self.closure(next);
+        }
+
+        configs
+            .into_iter()
+            .filter(|(state, _)| self.is_accepting_state(state))
+            .map(|(_, output)| output)
+            .collect()
+    }
+
+    /// Follow the transitions that read nothing from each configuration of a state and the
+    /// output written so far. Each configuration is extended once, and never back to a state on
+    /// the path that reached it.
+    #[inline]
+    fn closure(&self, configs: Vec<(usize, Vec<U>)>) -> HashSet<(usize, Vec<U>)> {
+        let mut closure = HashSet::new();
+        let mut stack: Vec<_> = configs
+            .into_iter()
+            .map(|(state, output)| (state, output, vec![state]))
+            .collect();
+        while let Some((state, output, path)) = stack.pop() {
+            if !closure.insert((state, output.clone())) {
+                continue;
+            }
+
+            for (label, end) in self.transition.outgoing(state) {
+                if label.input != Transition::Epsilon || path.contains(&end) {
+                    continue;
+                }
+                let mut output = output.clone();
+                if let Transition::Some(u) = &label.output {
+                    output.push(u.clone());
+                }
+                let mut path = path.clone();
+                path.push(end);
+                stack.push((end, output, path));
+            }
+        }
+        closure
+    }
+}
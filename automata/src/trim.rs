@@ -0,0 +1,105 @@
+use crate::nfa::{Transition, NFA};
+
+use std::hash::Hash;
+
+impl<T> NFA<T>
+where
+    T: Clone + Eq + Hash,
+{
+    /// Create an equivalent NFA without epsilon transitions. A state moves on a symbol to every
+    /// state that any state in its epsilon-closure moves to on that symbol, and it is accepting
+    /// if its epsilon-closure contains an accepting state. The states keep their labels, so
+    /// states that were only entered on epsilon transitions become unreachable; see
+    /// [`NFA::trim`].
+    #[inline]
+    pub fn remove_epsilons(&self) -> NFA<T> {
+        let adjacency = self.adjacency();
+        let closures = self.epsilon_closures();
+
+        let mut nfa = NFA::with_states(self.total_states, self.start_state);
+        let mut edges = Vec::new();
+        for s in 0..self.total_states {
+            let closure = closures.get(s);
+            if closure.iter().any(|q| self.is_accepting_state(q)) {
+                nfa.accepting_states.insert(s);
+            }
+
+            for &q in closure {
+                for (t, next) in adjacency.outgoing(q) {
+                    if let Transition::Some(_) = t {
+                        edges.push((s, t.clone(), next));
+                    }
+                }
+            }
+        }
+
+        nfa.add_transitions(edges);
+        nfa
+    }
+
+    /// Create an equivalent NFA with only the useful states: those reachable from the start
+    /// state from which an accepting state can be reached. The start state is always kept. The
+    /// remaining states are relabeled compactly, keeping their relative order.
+    #[inline]
+    pub fn trim(&self) -> NFA<T> {
+        let adjacency = self.adjacency();
+
+        let mut reachable = vec![false; self.total_states];
+        let mut stack = vec![self.start_state];
+        reachable[self.start_state] = true;
+        while let Some(s) = stack.pop() {
+            for &next in adjacency.targets(s) {
+                if !reachable[next] {
+                    reachable[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+
+        let mut coreachable = vec![false; self.total_states];
+        let mut stack: Vec<usize> = self.accepting_states.iter().cloned().collect();
+        for &s in &stack {
+            coreachable[s] = true;
+        }
+        while let Some(s) = stack.pop() {
+            for (_, prev) in adjacency.incoming(s) {
+                if !coreachable[prev] {
+                    coreachable[prev] = true;
+                    stack.push(prev);
+                }
+            }
+        }
+
+        let mut labels = vec![None; self.total_states];
+        let mut total_states = 0;
+        for s in 0..self.total_states {
+            if s == self.start_state || (reachable[s] && coreachable[s]) {
+                labels[s] = Some(total_states);
+                total_states += 1;
+            }
+        }
+
+        let mut nfa = NFA::with_states(total_states, labels[self.start_state].unwrap());
+        let mut edges = Vec::new();
+        for s in 0..self.total_states {
+            let label = match labels[s] {
+                Some(label) if coreachable[s] => label,
+                _ => continue,
+            };
+            if self.is_accepting_state(&s) {
+                nfa.accepting_states.insert(label);
+            }
+
+            for (t, next) in adjacency.outgoing(s) {
+                if let Some(next_label) = labels[next] {
+                    if coreachable[next] {
+                        edges.push((label, t.clone(), next_label));
+                    }
+                }
+            }
+        }
+
+        nfa.add_transitions(edges);
+        nfa
+    }
+}
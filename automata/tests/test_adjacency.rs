@@ -0,0 +1,105 @@
+use automata::adjacency::Adjacency;
+use automata::nfa::Transition;
+use automata::{DFA, NFA};
+
+#[test]
+fn test_adjacency() {
+    let adjacency = Adjacency::new(4, vec![(2, 'c', 3), (0, 'a', 1), (0, 'b', 2), (1, 'd', 3)]);
+    assert_eq!(4, adjacency.total_edges());
+
+    // Outgoing edges keep the order they were given in.
+    let outgoing: Vec<_> = adjacency.outgoing(0).collect();
+    assert_eq!(vec![(&'a', 1), (&'b', 2)], outgoing);
+    assert_eq!(&['a', 'b'], adjacency.labels(0));
+    assert_eq!(&[1, 2], adjacency.targets(0));
+    assert_eq!(0, adjacency.outgoing(3).count());
+
+    let edges: Vec<_> = adjacency.edges().collect();
+    assert_eq!(
+        vec![(0, &'a', 1), (0, &'b', 2), (1, &'d', 3), (2, &'c', 3)],
+        edges
+    );
+
+    // Incoming edges are in order of their sources.
+    let incoming: Vec<_> = adjacency.incoming(3).collect();
+    assert_eq!(vec![(&'d', 1), (&'c', 2)], incoming);
+    assert_eq!(0, adjacency.incoming(0).count());
+    assert_eq!(0, adjacency.incoming(10).count());
+}
+
+#[test]
+fn test_extended() {
+    let adjacency = Adjacency::new(4, vec![(1, 'b', 2), (3, 'd', 0)]);
+    // Edges already present are not added again.
+    let extended = adjacency.extended(4, vec![(0, 'a', 1), (1, 'c', 3), (1, 'b', 2)]);
+
+    assert_eq!(2, adjacency.total_edges());
+    assert_eq!(4, extended.total_edges());
+    assert_eq!(&['a'], extended.labels(0));
+    assert_eq!(&['b', 'c'], extended.labels(1));
+    assert_eq!(&[2, 3], extended.targets(1));
+    assert_eq!(0, extended.outgoing(2).count());
+    assert_eq!(&['d'], extended.labels(3));
+    assert_eq!(0, extended.outgoing(10).count());
+}
+
+#[test]
+fn test_nfa_adjacency() {
+    let mut n: NFA<char> = NFA::new();
+    let a = n.add_state(false);
+    let b = n.add_state(true);
+    n.add_labeled_transition(n.start_state, a, 'a');
+    n.add_labeled_transition(n.start_state, b, 'a');
+    n.add_epsilon_transition(a, b);
+    // Adding a transition again changes nothing.
+    n.add_labeled_transition(n.start_state, b, 'a');
+
+    let adjacency = n.adjacency();
+    assert_eq!(3, adjacency.total_edges());
+
+    // Outgoing edges are in the order they were added.
+    assert_eq!(&[a, b], adjacency.targets(n.start_state));
+    assert_eq!(
+        vec![(&Transition::Epsilon, b)],
+        adjacency.outgoing(a).collect::<Vec<_>>()
+    );
+    assert_eq!(2, adjacency.incoming(b).count());
+
+    // Transitions added together are deduplicated in one rebuild.
+    let c = n.add_state(false);
+    n.add_transitions(vec![
+        (b, Transition::Some('c'), c),
+        (b, Transition::Some('c'), c),
+        (c, Transition::Epsilon, a),
+    ]);
+    assert_eq!(5, n.adjacency().total_edges());
+    assert_eq!(&[c], n.adjacency().targets(b));
+    assert_eq!(None, n.add_transitions(vec![(c, Transition::Epsilon, 10)]));
+}
+
+#[test]
+fn test_dfa_adjacency() {
+    let mut d: DFA<char> = DFA::new();
+    let a = d.add_state(true);
+    d.add_transition(d.start_state, a, 'a');
+    d.add_transition(a, d.start_state, 'b');
+    // A transition on the same symbol replaces the old one.
+    d.add_transition(a, a, 'b');
+
+    let adjacency = d.adjacency();
+    assert_eq!(2, adjacency.total_edges());
+    assert_eq!(
+        vec![(&'a', a)],
+        adjacency.outgoing(d.start_state).collect::<Vec<_>>()
+    );
+
+    let incoming: Vec<_> = adjacency.incoming(a).collect();
+    assert_eq!(vec![(&'a', d.start_state), (&'b', a)], incoming);
+
+    // The last of several transitions on the same symbol wins.
+    d.add_transitions(vec![(a, 'c', d.start_state), (a, 'c', a)]);
+    assert_eq!(
+        vec![(&'b', a), (&'c', a)],
+        d.adjacency().outgoing(a).collect::<Vec<_>>()
+    );
+}
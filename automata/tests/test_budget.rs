@@ -0,0 +1,126 @@
+use automata::budget::{Aborted, Budget};
+use automata::builder::NFABuilder;
+use automata::interval::IntervalSet;
+use automata::{DFA, NFA};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+type Byte = IntervalSet<u8>;
+
+/// Build `(a|b)*abb`.
+fn nfa() -> NFA<Byte> {
+    let mut builder = NFABuilder::new();
+    let a = builder.symbol(b'a'.into());
+    let b = builder.symbol(b'b'.into());
+    let either = builder.union(a, b);
+    let prefix = builder.kleene_star(either);
+
+    let suffix = b"abb"
+        .iter()
+        .map(|&c| builder.symbol(c.into()))
+        .collect::<Vec<_>>();
+    let fragment = suffix
+        .into_iter()
+        .fold(prefix, |lhs, rhs| builder.concatenation(lhs, rhs));
+    builder.build(fragment)
+}
+
+#[test]
+fn test_step_budget() {
+    let nfa = nfa();
+    let dfa: DFA<Byte> = nfa.clone().into();
+    let input = b"ababb";
+
+    // Each symbol read takes one step.
+    let budget = Budget::steps(5);
+    assert_eq!(
+        Ok(true),
+        nfa.is_match_within(input.iter().copied(), &budget)
+    );
+    assert_eq!(Some(0), budget.remaining());
+    let budget = Budget::steps(5);
+    assert_eq!(
+        Ok(true),
+        dfa.is_match_within(input.iter().copied(), &budget)
+    );
+
+    let budget = Budget::steps(4);
+    assert_eq!(
+        Err(Aborted::OutOfSteps),
+        nfa.is_match_within(input.iter().copied(), &budget)
+    );
+    assert_eq!(Some(Aborted::OutOfSteps), budget.aborted());
+    let budget = Budget::steps(4);
+    assert_eq!(
+        Err(Aborted::OutOfSteps),
+        dfa.find_at_within(input.iter().copied(), 0, &budget)
+    );
+
+    // A search that stops early only takes the steps it needs.
+    let budget = Budget::steps(3);
+    let m = dfa
+        .find_shortest_at_within(b"abbabb".iter().copied(), 0, &budget)
+        .unwrap()
+        .unwrap();
+    assert_eq!(0..3, m.range());
+    let budget = Budget::steps(2);
+    let m = nfa
+        .find_at_within(b"ab".iter().copied(), 0, &budget)
+        .unwrap();
+    assert!(m.is_none());
+
+    // A spent budget aborts every later search.
+    let budget = Budget::steps(1);
+    assert!(dfa.is_match_within(input.iter().copied(), &budget).is_err());
+    assert_eq!(
+        Err(Aborted::OutOfSteps),
+        dfa.is_match_within(b"".iter().copied(), &budget)
+    );
+}
+
+#[test]
+fn test_iter_within() {
+    let dfa: DFA<Byte> = nfa().into();
+    let budget = Budget::steps(3);
+    let steps = dfa
+        .iter_on_within(b"aaaaaa".iter().copied(), &budget)
+        .count();
+
+    // The start, then one item for each step taken.
+    assert_eq!(4, steps);
+    assert_eq!(Some(Aborted::OutOfSteps), budget.aborted());
+}
+
+#[test]
+fn test_cancellation() {
+    let nfa = nfa();
+    let dfa: DFA<Byte> = nfa.clone().into();
+    let cancel = AtomicBool::new(false);
+
+    let budget = Budget::cancellable(&cancel);
+    assert_eq!(
+        Ok(true),
+        dfa.is_match_within(b"abb".iter().copied(), &budget)
+    );
+    assert_eq!(None, budget.remaining());
+
+    cancel.store(true, Ordering::Relaxed);
+    let budget = Budget::cancellable(&cancel);
+    assert_eq!(
+        Err(Aborted::Cancelled),
+        nfa.is_match_within(b"abb".iter().copied(), &budget)
+    );
+
+    // The flag is polled periodically, so a search notices it within the interval.
+    cancel.store(false, Ordering::Relaxed);
+    let budget = Budget::cancellable(&cancel).with_steps(1000);
+    budget.step().unwrap();
+    cancel.store(true, Ordering::Relaxed);
+    let input = vec![b'a'; 500];
+    assert_eq!(
+        Err(Aborted::Cancelled),
+        dfa.is_match_within(input.iter().copied(), &budget)
+    );
+    let taken = 1000 - budget.remaining().unwrap();
+    assert!(taken <= 1 + Budget::POLL_INTERVAL, "{} steps taken", taken);
+}
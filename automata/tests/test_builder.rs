@@ -0,0 +1,71 @@
+use automata::builder::NFABuilder;
+use automata::NFA;
+
+/// Build an NFA for the concatenation of the characters of a string.
+fn literal(s: &str) -> NFA<char> {
+    let mut builder = NFABuilder::new();
+    let fragments: Vec<_> = s.chars().map(|c| builder.symbol(c)).collect();
+    let fragment = fragments
+        .into_iter()
+        .reduce(|lhs, rhs| builder.concatenation(lhs, rhs))
+        .unwrap_or_else(|| builder.epsilon());
+    builder.build(fragment)
+}
+
+#[test]
+fn test_build_concatenation() {
+    let nfa = literal("abc");
+    assert_eq!(4, nfa.total_states);
+    assert_eq!(1, nfa.accepting_states.len());
+    assert!(nfa.is_match("abc".chars()));
+    assert!(!nfa.is_match("ab".chars()));
+    assert!(!nfa.is_match("abcc".chars()));
+
+    // Each symbol adds one state, no matter how long the chain is.
+    let s = "ab".repeat(500);
+    let nfa = literal(&s);
+    assert_eq!(s.len() + 1, nfa.total_states);
+    assert!(nfa.is_match(s.chars()));
+
+    let nfa = literal("");
+    assert!(nfa.is_match("".chars()));
+    assert!(!nfa.is_match("a".chars()));
+}
+
+#[test]
+fn test_build_operators() {
+    // (a|b)*c+d?
+    let mut builder = NFABuilder::new();
+    let a = builder.symbol('a');
+    let b = builder.symbol('b');
+    let ab = builder.union(a, b);
+    let star = builder.kleene_star(ab);
+    let c = builder.symbol('c');
+    let plus = builder.plus(c);
+    let d = builder.symbol('d');
+    let optional = builder.optional(d);
+    let tail = builder.concatenation(plus, optional);
+    let fragment = builder.concatenation(star, tail);
+    let nfa = builder.build(fragment);
+
+    for valid in &["c", "abc", "bbacc", "cd", "abccd"] {
+        assert!(nfa.is_match(valid.chars()), "{}", valid);
+    }
+    for invalid in &["", "ab", "d", "cdd", "cad"] {
+        assert!(!nfa.is_match(invalid.chars()), "{}", invalid);
+    }
+}
+
+#[test]
+fn test_build_nested_star() {
+    // (a*)* has a cycle of epsilon transitions.
+    let mut builder = NFABuilder::new();
+    let a = builder.symbol('a');
+    let inner = builder.kleene_star(a);
+    let outer = builder.kleene_star(inner);
+    let nfa = builder.build(outer);
+
+    assert!(nfa.is_match("".chars()));
+    assert!(nfa.is_match("aaaa".chars()));
+    assert!(!nfa.is_match("ab".chars()));
+}
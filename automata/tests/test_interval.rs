@@ -0,0 +1,154 @@
+use automata::convert::Disjoin;
+use automata::interval::{IntervalSet, Symbol};
+use automata::{DFA, NFA};
+
+use std::iter::FromIterator;
+
+#[test]
+fn test_normalize() {
+    let set: IntervalSet<u8> = vec![(5, 9), (0, 2), (3, 4), (20, 30), (25, 40), (9, 1)]
+        .into_iter()
+        .collect();
+    assert_eq!(set.ranges(), &[(0, 9), (20, 40)]);
+
+    let mut set = IntervalSet::from(b'a'..=b'c');
+    set.add_range(b'd', b'f');
+    set.add_range(b'x', b'z');
+    assert_eq!(
+        set,
+        IntervalSet::from_iter(vec![(b'x', b'z'), (b'a', b'f')])
+    );
+    assert!(IntervalSet::range(5u8, 4).is_empty());
+}
+
+#[test]
+fn test_set_operations() {
+    let a = IntervalSet::from(10u8..=20);
+    let b = IntervalSet::from(15u8..=30);
+
+    assert_eq!(a.union(&b), IntervalSet::from(10..=30));
+    assert_eq!(a.intersection(&b), IntervalSet::from(15..=20));
+    assert_eq!(a.difference(&b), IntervalSet::from(10..=14));
+    assert_eq!(a.complement().ranges(), &[(u8::MIN, 9), (21, u8::MAX)]);
+    assert_eq!(IntervalSet::<u8>::all().complement(), IntervalSet::new());
+    assert_eq!(IntervalSet::<u8>::new().complement(), IntervalSet::all());
+
+    assert!(a.contains(10) && a.contains(20) && !a.contains(21) && !a.contains(9));
+}
+
+#[test]
+fn test_char_skips_surrogates() {
+    assert_eq!('\u{d7ff}'.successor(), Some('\u{e000}'));
+    assert_eq!('\u{e000}'.predecessor(), Some('\u{d7ff}'));
+    assert_eq!(char::MAX.successor(), None);
+    assert_eq!('\0'.predecessor(), None);
+
+    let set = IntervalSet::from('\u{d000}'..='\u{d7ff}');
+    assert_eq!(
+        set.union(&IntervalSet::from('\u{e000}')).ranges(),
+        &[('\u{d000}', '\u{e000}')]
+    );
+    assert_eq!(
+        IntervalSet::from('\0'..='\u{d7ff}').complement().ranges(),
+        &[('\u{e000}', char::MAX)]
+    );
+}
+
+#[test]
+fn test_disjoin() {
+    let a = IntervalSet::from(0u8..=10);
+    let b = IntervalSet::from(5u8..=15);
+    let c = IntervalSet::from(200u8..=u8::MAX);
+
+    let disjoint = IntervalSet::disjoin(vec![&a, &b, &c]);
+    assert_eq!(
+        disjoint,
+        vec![
+            IntervalSet::from(0..=4),
+            IntervalSet::from(5..=10),
+            IntervalSet::from(11..=15),
+            IntervalSet::from(200..=u8::MAX),
+        ]
+    );
+    assert!(Disjoin::contains(&a, &b));
+    assert!(!Disjoin::contains(&a, &c));
+}
+
+/// An NFA over bytes for `[0-9]+(\.[0-9]+)?`.
+fn decimal() -> NFA<IntervalSet<u8>> {
+    let digit = IntervalSet::from(b'0'..=b'9');
+
+    let mut nfa = NFA::new();
+    let integer = nfa.add_state(true);
+    let point = nfa.add_state(false);
+    let fraction = nfa.add_state(true);
+    nfa.add_labeled_transition(nfa.start_state, integer, digit.clone());
+    nfa.add_labeled_transition(integer, integer, digit.clone());
+    nfa.add_labeled_transition(integer, point, IntervalSet::from(b'.'));
+    nfa.add_labeled_transition(point, fraction, digit.clone());
+    nfa.add_labeled_transition(fraction, fraction, digit);
+    nfa
+}
+
+#[test]
+fn test_bytes() {
+    let nfa = decimal();
+    let dfa: DFA<IntervalSet<u8>> = nfa.clone().into();
+    let dfa = dfa.minimize();
+
+    for input in &["0", "42", "3.14"] {
+        assert!(nfa.is_match(input.bytes()));
+        assert!(dfa.is_match(input.bytes()));
+    }
+    for input in &["", ".5", "1.", "1.2.3", "x"] {
+        assert!(!nfa.is_match(input.bytes()));
+        assert!(!dfa.is_match(input.bytes()));
+    }
+
+    let m = dfa.find("3.14 apples".bytes()).unwrap();
+    assert_eq!(m.range(), 0..4);
+    assert_eq!(m.span, b"3.14".to_vec());
+}
+
+#[test]
+fn test_product() {
+    let dfa: DFA<IntervalSet<u8>> = decimal().into();
+    let same = dfa.intersection(&dfa.complement().complement());
+    assert_eq!(same.is_equivalent_to(&dfa), Ok(()));
+
+    let not_decimal = dfa.complement();
+    assert!(not_decimal.is_match("1.".bytes()));
+    assert!(!not_decimal.is_match("1.5".bytes()));
+    assert!(dfa.intersection(&not_decimal).is_empty());
+}
+
+#[test]
+fn test_token_ids() {
+    // Token IDs: 0 opens, 1 closes, anything from 100 up is a word.
+    let mut nfa: NFA<IntervalSet<u32>> = NFA::new();
+    let open = nfa.add_state(false);
+    let close = nfa.add_state(true);
+    nfa.add_labeled_transition(nfa.start_state, open, IntervalSet::from(0));
+    nfa.add_labeled_transition(open, open, IntervalSet::from(100..=u32::MAX));
+    nfa.add_labeled_transition(open, close, IntervalSet::from(1));
+
+    let dfa: DFA<IntervalSet<u32>> = nfa.into();
+    assert!(dfa.is_match(vec![0, 1]));
+    assert!(dfa.is_match(vec![0, 100, 4_000_000_000, 1]));
+    assert!(!dfa.is_match(vec![0, 2, 1]));
+    assert!(!dfa.is_match(vec![0, 100]));
+}
+
+#[test]
+fn test_utf16_code_units() {
+    // A surrogate pair: a high surrogate followed by a low surrogate.
+    let mut nfa: NFA<IntervalSet<u16>> = NFA::new();
+    let high = nfa.add_state(false);
+    let low = nfa.add_state(true);
+    nfa.add_labeled_transition(nfa.start_state, high, IntervalSet::from(0xd800..=0xdbff));
+    nfa.add_labeled_transition(high, low, IntervalSet::from(0xdc00..=0xdfff));
+
+    let dfa: DFA<IntervalSet<u16>> = nfa.into();
+    assert!(dfa.is_match("😀".encode_utf16()));
+    assert!(!dfa.is_match("a".encode_utf16()));
+}
@@ -0,0 +1,112 @@
+use automata::builder::{Fragment, NFABuilder};
+use automata::convert::DFAFromNFA;
+use automata::interval::IntervalSet;
+use automata::limit::{Limit, Limits, SizeLimitExceeded};
+use automata::NFA;
+
+type Byte = IntervalSet<u8>;
+
+/// Build `a|b`.
+fn either(builder: &mut NFABuilder<Byte>) -> Fragment<Byte> {
+    let a = builder.symbol(b'a'.into());
+    let b = builder.symbol(b'b'.into());
+    builder.union(a, b)
+}
+
+/// Build `(a|b)*a(a|b){n}`, whose DFA has 2^(n + 1) states.
+fn blowup(builder: &mut NFABuilder<Byte>, n: usize) -> Fragment<Byte> {
+    let prefix = either(builder);
+    let prefix = builder.kleene_star(prefix);
+    let a = builder.symbol(b'a'.into());
+    let mut fragment = builder.concatenation(prefix, a);
+    for _ in 0..n {
+        let next = either(builder);
+        fragment = builder.concatenation(fragment, next);
+    }
+    fragment
+}
+
+fn blowup_nfa(n: usize) -> NFA<Byte> {
+    let mut builder = NFABuilder::new();
+    let fragment = blowup(&mut builder, n);
+    builder.build(fragment)
+}
+
+#[test]
+fn test_builder_limits() {
+    let limits = Limits {
+        nfa_states: Some(20),
+        ..Limits::none()
+    };
+    let mut builder = NFABuilder::with_limits(limits);
+    blowup(&mut builder, 2);
+    assert!(builder.check_limits().is_ok());
+    blowup(&mut builder, 4);
+    assert_eq!(
+        Err(SizeLimitExceeded {
+            limit: Limit::NFAStates,
+            max: 20
+        }),
+        builder.check_limits()
+    );
+
+    // Without limits the builder never complains.
+    let mut builder = NFABuilder::new();
+    blowup(&mut builder, 50);
+    assert!(builder.check_limits().is_ok());
+}
+
+#[test]
+fn test_subset_construction_limits() {
+    let limits = Limits {
+        dfa_states: Some(100),
+        ..Limits::none()
+    };
+    let err = DFAFromNFA::with_limits(blowup_nfa(10), limits).unwrap_err();
+    assert_eq!(Limit::DFAStates, err.limit);
+    assert_eq!(100, err.max);
+
+    let limits = Limits {
+        memory: Some(1 << 10),
+        ..Limits::none()
+    };
+    let err = DFAFromNFA::with_limits(blowup_nfa(10), limits).unwrap_err();
+    assert_eq!(Limit::Memory, err.limit);
+
+    let limits = Limits {
+        nfa_states: Some(10),
+        ..Limits::none()
+    };
+    let err = DFAFromNFA::with_limits(blowup_nfa(10), limits).unwrap_err();
+    assert_eq!(Limit::NFAStates, err.limit);
+
+    // The DFA for n = 4 has 32 states, which fits.
+    let limits = Limits {
+        dfa_states: Some(32),
+        ..Limits::none()
+    };
+    let dfa = DFAFromNFA::with_limits(blowup_nfa(4), limits).unwrap().dfa;
+    assert_eq!(32, dfa.total_states);
+    assert!(dfa.is_match(b"abbab".iter().copied()));
+    assert!(!dfa.is_match(b"abbbbb".iter().copied()));
+}
+
+#[test]
+fn test_default_limits() {
+    let limits = Limits::default();
+    assert_eq!(Some(Limits::DEFAULT_DFA_STATES), limits.dfa_states);
+    assert!(limits.check_dfa_states(Limits::DEFAULT_DFA_STATES).is_ok());
+    assert!(limits
+        .check_dfa_states(Limits::DEFAULT_DFA_STATES + 1)
+        .is_err());
+    assert!(Limits::none().check_memory(usize::MAX).is_ok());
+
+    let err = SizeLimitExceeded {
+        limit: Limit::DFAStates,
+        max: 100,
+    };
+    assert_eq!(
+        "size limit exceeded: more than 100 DFA states",
+        err.to_string()
+    );
+}
@@ -1,4 +1,6 @@
-use automata::{nfa::Transition, NFA};
+use automata::nfa::{IterState, Transition};
+use automata::sparse::SparseSet;
+use automata::NFA;
 
 #[test]
 fn test_new() {
@@ -7,7 +9,7 @@ fn test_new() {
     assert_eq!(1, n.total_states);
     assert_eq!(0, n.start_state);
     assert_eq!(0, n.accepting_states.len());
-    assert_eq!(0, n.transition.into_iter().count());
+    assert_eq!(0, n.adjacency().into_iter().count());
 }
 
 #[test]
@@ -18,9 +20,9 @@ fn test_new_epsilon() {
     assert_eq!(0, n.start_state);
     assert_eq!(1, n.accepting_states.len());
 
-    assert_eq!(1, n.transition.into_iter().count());
+    assert_eq!(1, n.adjacency().into_iter().count());
 
-    let (_, tran, _) = n.transition.into_iter().next().unwrap();
+    let (_, tran, _) = n.adjacency().into_iter().next().unwrap();
     assert_eq!(Transition::Epsilon, *tran);
 }
 
@@ -80,3 +82,203 @@ fn test_combine() {
     assert_eq!(5, combined.total_states);
     assert_eq!(2, combined.accepting_states.len());
 }
+
+#[test]
+fn test_epsilon_cycle() {
+    // Nested stars produce a cycle of epsilon transitions.
+    let mut c: NFA<char> = NFA::new();
+    let end = c.add_state(true);
+    c.add_labeled_transition(c.start_state, end, 'a');
+    let star = NFA::kleene_star(&NFA::kleene_star(&c));
+
+    let closure = star.epsilon_closure(star.start_state);
+    assert!(closure.contains(&star.start_state));
+    assert!(closure.iter().any(|s| star.is_accepting_state(s)));
+
+    let closures = star.epsilon_closures();
+    for state in 0..star.total_states {
+        let mut expected: Vec<usize> = star.epsilon_closure(state).into_iter().collect();
+        expected.sort_unstable();
+        assert_eq!(expected, closures.get(state));
+    }
+
+    assert!(star.is_match("".chars()));
+    assert!(star.is_match("aaa".chars()));
+    assert!(!star.is_match("ab".chars()));
+}
+
+#[test]
+fn test_closures_kept_between_searches() {
+    let mut n: NFA<char> = NFA::new();
+    let a = n.add_state(false);
+    let b = n.add_state(true);
+    n.add_labeled_transition(n.start_state, a, 'a');
+    assert!(!n.is_match("a".chars()));
+
+    // An added transition is noticed by the next search.
+    n.add_epsilon_transition(a, b);
+    assert!(n.is_match("a".chars()));
+    assert!(n.clone().is_match("a".chars()));
+
+    // Clearing the cache only frees memory.
+    n.clear_cache();
+    assert!(n.is_match("a".chars()));
+}
+
+#[test]
+fn test_scratch_shared_between_searches() {
+    let mut n: NFA<char> = NFA::new();
+    let a = n.add_state(true);
+    n.add_labeled_transition(n.start_state, a, 'a');
+    n.add_labeled_transition(a, a, 'a');
+
+    // Searches running at the same time each get their own sets of states.
+    let mut outer = n.iter_on("aa".chars());
+    outer.next();
+    assert!(n.is_match("aaa".chars()));
+    assert!(!n.is_match("b".chars()));
+    assert!(outer.last().is_some());
+
+    // Sets left over from a smaller NFA are not reused.
+    let b = n.add_state(true);
+    n.add_labeled_transition(a, b, 'b');
+    assert!(n.is_match("ab".chars()));
+    assert!(!n.is_match("ba".chars()));
+}
+
+#[test]
+fn test_iter_on() {
+    // a(b|c), with each branch ending in its own accepting state.
+    let mut n: NFA<char> = NFA::new();
+    let a = n.add_state(false);
+    let b = n.add_state(true);
+    let c = n.add_state(true);
+    n.add_labeled_transition(n.start_state, a, 'a');
+    n.add_labeled_transition(a, b, 'b');
+    n.add_labeled_transition(a, c, 'c');
+
+    let mut iter = n.iter_on("ab".chars());
+    assert!(matches!(iter.next(), Some(IterState::Start(false))));
+    assert_eq!(&[n.start_state], iter.states());
+    assert!(matches!(iter.next(), Some(IterState::Normal('a', false))));
+    assert_eq!(&[a], iter.states());
+    assert!(matches!(iter.next(), Some(IterState::Normal('b', true))));
+    assert_eq!(&[b], iter.states());
+    assert!(iter.next().is_none());
+
+    let mut iter = n.iter_on("ad".chars());
+    iter.next();
+    iter.next();
+    assert!(matches!(iter.next(), Some(IterState::Stuck)));
+    assert!(iter.states().is_empty());
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn test_sparse_set() {
+    let mut set = SparseSet::new(8);
+    assert!(set.is_empty());
+    assert!(set.insert(5));
+    assert!(set.insert(2));
+    assert!(!set.insert(5));
+    assert_eq!(&[5, 2], set.as_slice());
+    assert!(set.contains(2));
+    assert!(!set.contains(3));
+
+    set.clear();
+    assert!(!set.contains(5));
+    assert!(set.insert(3));
+    assert_eq!(1, set.len());
+    assert_eq!(8, set.capacity());
+}
+
+/// An NFA over chars for `(ab|a)*c`, built with epsilon transitions and a dead branch.
+fn nfa_with_epsilons() -> NFA<char> {
+    let mut n = NFA::new();
+    let loop_start = n.add_state(false);
+    let a1 = n.add_state(false);
+    let b = n.add_state(false);
+    let a2 = n.add_state(false);
+    let end = n.add_state(true);
+    let dead = n.add_state(false);
+    let unreachable = n.add_state(true);
+
+    n.add_epsilon_transition(n.start_state, loop_start);
+    n.add_labeled_transition(loop_start, a1, 'a');
+    n.add_labeled_transition(a1, b, 'b');
+    n.add_epsilon_transition(b, loop_start);
+    n.add_labeled_transition(loop_start, a2, 'a');
+    n.add_epsilon_transition(a2, loop_start);
+    n.add_labeled_transition(loop_start, end, 'c');
+    n.add_labeled_transition(loop_start, dead, 'd');
+    n.add_labeled_transition(unreachable, end, 'e');
+    n
+}
+
+fn assert_same_language(n1: &NFA<char>, n2: &NFA<char>) {
+    let inputs = [
+        "", "c", "ac", "abc", "aabc", "abac", "ababc", "a", "ab", "abd", "d", "ec", "acc",
+    ];
+    for input in inputs.iter() {
+        assert_eq!(
+            n1.is_match(input.chars()),
+            n2.is_match(input.chars()),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_remove_epsilons() {
+    let n = nfa_with_epsilons();
+    let removed = n.remove_epsilons();
+
+    assert_eq!(n.total_states, removed.total_states);
+    assert!(removed
+        .adjacency()
+        .into_iter()
+        .all(|(_, t, _)| *t != Transition::Epsilon));
+    assert_same_language(&n, &removed);
+
+    // A state whose epsilon-closure contains an accepting state becomes accepting.
+    let mut n: NFA<char> = NFA::new_epsilon();
+    let removed = n.remove_epsilons();
+    assert!(removed.is_accepting_state(&removed.start_state));
+    assert!(removed.is_match("".chars()));
+
+    let s = n.add_state(false);
+    n.add_labeled_transition(n.start_state, s, 'x');
+    assert!(!n.remove_epsilons().is_match("x".chars()));
+}
+
+#[test]
+fn test_trim() {
+    let n = nfa_with_epsilons();
+    let trimmed = n.trim();
+
+    // The dead and unreachable states are gone.
+    assert_eq!(n.total_states - 2, trimmed.total_states);
+    assert_same_language(&n, &trimmed);
+
+    // After removing epsilons, the states only entered on epsilons are gone too.
+    let trimmed = n.remove_epsilons().trim();
+    assert_eq!(5, trimmed.total_states);
+    assert_same_language(&n, &trimmed);
+
+    // Trimming a trimmed NFA changes nothing.
+    assert_eq!(trimmed.total_states, trimmed.trim().total_states);
+}
+
+#[test]
+fn test_trim_empty_language() {
+    let mut n: NFA<char> = NFA::new();
+    let s = n.add_state(false);
+    n.add_labeled_transition(n.start_state, s, 'a');
+
+    let trimmed = n.trim();
+    assert_eq!(1, trimmed.total_states);
+    assert_eq!(0, trimmed.adjacency().into_iter().count());
+    assert!(!trimmed.is_match("".chars()));
+    assert!(!trimmed.is_match("a".chars()));
+}
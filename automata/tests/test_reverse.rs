@@ -0,0 +1,89 @@
+use automata::builder::NFABuilder;
+use automata::{DFA, NFA};
+
+/// An NFA for `ab(c|d)*`.
+fn nfa() -> NFA<char> {
+    let mut builder = NFABuilder::new();
+    let a = builder.symbol('a');
+    let b = builder.symbol('b');
+    let c = builder.symbol('c');
+    let d = builder.symbol('d');
+
+    let ab = builder.concatenation(a, b);
+    let cd = builder.union(c, d);
+    let cd_star = builder.kleene_star(cd);
+    let fragment = builder.concatenation(ab, cd_star);
+    builder.build(fragment)
+}
+
+/// A DFA for `a(b|c)` or `da`, where two states are entered on `a`.
+fn dfa() -> DFA<char> {
+    let mut dfa = DFA::new();
+    let s1 = dfa.add_state(false);
+    let s2 = dfa.add_state(true);
+    let s3 = dfa.add_state(false);
+    dfa.add_transition(dfa.start_state, s1, 'a');
+    dfa.add_transition(s1, s2, 'b');
+    dfa.add_transition(s1, s2, 'c');
+    dfa.add_transition(dfa.start_state, s3, 'd');
+    dfa.add_transition(s3, s2, 'a');
+    dfa
+}
+
+fn reversed(s: &str) -> String {
+    s.chars().rev().collect()
+}
+
+#[test]
+fn test_nfa_reverse() {
+    let nfa = nfa();
+    let reverse = nfa.reverse();
+
+    assert_eq!(nfa.total_states + 1, reverse.total_states);
+    assert_eq!(1, reverse.accepting_states.len());
+    assert!(reverse.is_accepting_state(&nfa.start_state));
+
+    for s in &["ab", "abc", "abdc", "abcccd"] {
+        assert!(nfa.is_match(s.chars()), "{}", s);
+        assert!(reverse.is_match(reversed(s).chars()), "{}", s);
+        assert!(!reverse.is_match(s.chars()), "{}", s);
+    }
+    for s in &["", "a", "ab", "cab", "abab"] {
+        assert!(!reverse.is_match(s.chars()), "{}", s);
+    }
+
+    // Reversing twice gives back the original language.
+    let twice = reverse.reverse();
+    for s in &["ab", "abc", "abdc"] {
+        assert!(twice.is_match(s.chars()), "{}", s);
+    }
+    assert!(!twice.is_match("ba".chars()));
+}
+
+#[test]
+fn test_dfa_reverse() {
+    let dfa = dfa();
+    let reverse = dfa.reverse();
+
+    assert_eq!(dfa.total_states + 1, reverse.total_states);
+    for s in &["ab", "ac", "da"] {
+        assert!(dfa.is_match(s.chars()), "{}", s);
+        assert!(reverse.is_match(reversed(s).chars()), "{}", s);
+    }
+    for s in &["", "a", "ab", "da", "ac", "bd"] {
+        assert!(!reverse.is_match(s.chars()), "{}", s);
+    }
+}
+
+#[test]
+fn test_reverse_epsilon() {
+    // The reverse of a language containing the empty string also contains it.
+    let mut dfa: DFA<char> = DFA::new();
+    dfa.accepting_states.insert(dfa.start_state);
+    dfa.add_transition(dfa.start_state, dfa.start_state, 'a');
+
+    let reverse = dfa.reverse();
+    assert!(reverse.is_match("".chars()));
+    assert!(reverse.is_match("aaa".chars()));
+    assert!(!reverse.is_match("ab".chars()));
+}
@@ -0,0 +1,147 @@
+use automata::nfa::Transition;
+use automata::Transducer;
+
+use std::collections::HashSet;
+
+/// A transducer with a single accepting state that rewrites each character by the given pairs.
+/// An output of None deletes the character.
+fn rewrite(pairs: &[(char, Option<char>)]) -> Transducer<char, char> {
+    let mut t = Transducer::new();
+    t.accepting_states.insert(t.start_state);
+    for &(input, output) in pairs {
+        let output = output.map_or(Transition::Epsilon, Transition::Some);
+        t.add_transition(
+            t.start_state,
+            t.start_state,
+            Transition::Some(input),
+            output,
+        );
+    }
+    t
+}
+
+fn outputs(t: &Transducer<char, char>, input: &str) -> HashSet<String> {
+    t.run(input.chars())
+        .into_iter()
+        .map(|output| output.into_iter().collect())
+        .collect()
+}
+
+fn set(strings: &[&str]) -> HashSet<String> {
+    strings.iter().map(|s| s.to_string()).collect()
+}
+
+fn upper() -> Transducer<char, char> {
+    rewrite(&[('a', Some('A')), ('b', Some('B')), (' ', Some(' '))])
+}
+
+#[test]
+fn test_run() {
+    let t = upper();
+    assert_eq!(outputs(&t, "ab ba"), set(&["AB BA"]));
+    assert_eq!(outputs(&t, ""), set(&[""]));
+    assert_eq!(outputs(&t, "abc"), set(&[]));
+
+    let no_vowels = rewrite(&[('a', None), ('b', Some('b')), ('e', None)]);
+    assert_eq!(outputs(&no_vowels, "babe"), set(&["bb"]));
+}
+
+#[test]
+fn test_run_nondeterministic() {
+    // Either keep or double each a, then write ! at the end without reading anything.
+    let mut t = Transducer::new();
+    let end = t.add_state(true);
+    let double = t.add_state(false);
+    let s = t.start_state;
+    t.add_transition(s, s, Transition::Some('a'), Transition::Some('a'));
+    t.add_transition(s, double, Transition::Some('a'), Transition::Some('a'));
+    t.add_transition(double, s, Transition::Epsilon, Transition::Some('a'));
+    t.add_transition(s, end, Transition::Epsilon, Transition::Some('!'));
+
+    assert_eq!(outputs(&t, "aa"), set(&["aa!", "aaa!", "aaaa!"]));
+}
+
+#[test]
+fn test_run_epsilon_cycle() {
+    // A cycle that writes without reading is not followed back to where it started, so the
+    // outputs are finite.
+    let mut t = Transducer::new();
+    let other = t.add_state(true);
+    t.accepting_states.insert(t.start_state);
+    t.add_transition(
+        t.start_state,
+        other,
+        Transition::Epsilon,
+        Transition::Some('x'),
+    );
+    t.add_transition(
+        other,
+        t.start_state,
+        Transition::Epsilon,
+        Transition::Some('y'),
+    );
+
+    assert_eq!(outputs(&t, ""), set(&["", "x"]));
+}
+
+#[test]
+fn test_invert() {
+    let lower = upper().invert();
+    assert_eq!(outputs(&lower, "AB BA"), set(&["ab ba"]));
+    assert_eq!(outputs(&lower, "ab"), set(&[]));
+}
+
+#[test]
+fn test_compose() {
+    let swap = rewrite(&[('A', Some('B')), ('B', Some('A')), (' ', None)]);
+    let t = upper().compose(&swap);
+    assert_eq!(outputs(&t, "ab ba"), set(&["BAAB"]));
+
+    // Composing with the inverse maps each input back to itself.
+    let round_trip = upper().compose(&upper().invert());
+    assert_eq!(outputs(&round_trip, "a b"), set(&["a b"]));
+}
+
+#[test]
+fn test_compose_epsilons() {
+    // The first transducer writes a marker without reading, and the second inserts a symbol
+    // without reading.
+    let mut mark = Transducer::new();
+    let end = mark.add_state(true);
+    mark.add_transition(
+        mark.start_state,
+        mark.start_state,
+        Transition::Some('a'),
+        Transition::Some('a'),
+    );
+    mark.add_transition(
+        mark.start_state,
+        end,
+        Transition::Epsilon,
+        Transition::Some('#'),
+    );
+
+    let mut expand = Transducer::new();
+    let inserted = expand.add_state(false);
+    let s = expand.start_state;
+    expand.accepting_states.insert(s);
+    expand.add_transition(s, s, Transition::Some('a'), Transition::Some('a'));
+    expand.add_transition(s, inserted, Transition::Some('#'), Transition::Some('<'));
+    expand.add_transition(inserted, s, Transition::Epsilon, Transition::Some('>'));
+
+    let t = mark.compose(&expand);
+    assert_eq!(outputs(&t, "aa"), set(&["aa<>"]));
+}
+
+#[test]
+fn test_projections() {
+    let no_vowels = rewrite(&[('a', None), ('b', Some('b'))]);
+    let inputs = no_vowels.input_nfa();
+    let outputs = no_vowels.output_nfa();
+
+    assert!(inputs.is_match("abba".chars()));
+    assert!(!inputs.is_match("abc".chars()));
+    assert!(outputs.is_match("bb".chars()));
+    assert!(outputs.is_match("".chars()));
+    assert!(!outputs.is_match("ab".chars()));
+}
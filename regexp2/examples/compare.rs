@@ -0,0 +1,70 @@
+/// Compares the ways of building a DFA from a regular expression: Thompson's construction
+/// followed by the subset construction, Glushkov's construction followed by the subset
+/// construction, and the direct construction from the syntax tree using followpos. Prints the
+/// time each takes and the number of states in the DFA it produces, before and after
+/// minimization.
+use regexp2::automata::{DFA, NFA};
+use regexp2::class::CharClass;
+use regexp2::parser::ast::ASTParser;
+use regexp2::parser::nfa::NFAParser;
+use std::env;
+use std::time::{Duration, Instant};
+
+const DEFAULT_EXPRS: &[&str] = &[
+    "(a|b)*abb",
+    "(a|b)*a(a|b)(a|b)(a|b)(a|b)(a|b)(a|b)",
+    "[a-z]+@[a-z]+\\.(com|org|net)",
+    "((a|b)*c(d|e)*)+f?",
+    "(0|1(01*0)*1)*",
+];
+
+fn time<F: FnOnce() -> DFA<CharClass>>(f: F) -> (DFA<CharClass>, Duration) {
+    let start = Instant::now();
+    let dfa = f();
+    (dfa, start.elapsed())
+}
+
+fn report(name: &str, (dfa, elapsed): (DFA<CharClass>, Duration)) {
+    println!(
+        "  {:<10} {:>10.3?} {:>8} states {:>8} minimized",
+        name,
+        elapsed,
+        dfa.total_states,
+        dfa.minimize().total_states
+    );
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let exprs: Vec<&str> = if args.is_empty() {
+        DEFAULT_EXPRS.to_vec()
+    } else {
+        args.iter().map(String::as_str).collect()
+    };
+
+    for expr in exprs {
+        println!("{}", expr);
+
+        report(
+            "thompson",
+            time(|| {
+                let nfa: NFA<CharClass> = NFAParser::new().parse(expr).unwrap();
+                nfa.into()
+            }),
+        );
+        report(
+            "glushkov",
+            time(|| {
+                let parser: ASTParser<CharClass> = ASTParser::new();
+                parser.parse(expr).unwrap().glushkov_nfa().unwrap().into()
+            }),
+        );
+        report(
+            "followpos",
+            time(|| {
+                let parser: ASTParser<CharClass> = ASTParser::new();
+                parser.parse(expr).unwrap().followpos_dfa().unwrap()
+            }),
+        );
+    }
+}
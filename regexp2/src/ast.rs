@@ -1,21 +1,475 @@
 use crate::class::CharClass;
 
-#[derive(Clone, Debug, PartialEq)]
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// A regular expression as a tree. The parser produces atoms, groups and the unary and binary
+/// operators of the syntax; [`Expr::Empty`], [`Expr::Epsilon`], intersection and complement only
+/// arise from [`Expr::derivative`] and from the smart constructors.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Expr {
+    /// Matches no strings.
+    Empty,
+    /// Matches only the empty string.
+    Epsilon,
     Unary(UnaryOp, Box<Self>),
     Binary(BinaryOp, Box<Self>, Box<Self>),
     Atom(CharClass),
+    /// A capturing group, numbered from 1.
+    Group(usize, Box<Self>),
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum UnaryOp {
     Star,
     Plus,
     Optional,
+    /// Matches every string the operand does not.
+    Complement,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum BinaryOp {
     Concat,
     Alternate,
+    /// Matches the strings matched by both operands.
+    Intersect,
+}
+
+/// Smart constructors, which simplify as they build so that the derivatives of an expression are
+/// finitely many up to equality. Alternation and intersection are flattened, sorted and
+/// deduplicated, and concatenation is kept right-nested.
+impl Expr {
+    #[inline]
+    pub fn atom(class: CharClass) -> Self {
+        if class.is_empty() {
+            Expr::Empty
+        } else {
+            Expr::Atom(class)
+        }
+    }
+
+    #[inline]
+    pub fn concat(lhs: Self, rhs: Self) -> Self {
+        match (lhs, rhs) {
+            (Expr::Empty, _) | (_, Expr::Empty) => Expr::Empty,
+            (Expr::Epsilon, e) | (e, Expr::Epsilon) => e,
+            (Expr::Binary(BinaryOp::Concat, l1, l2), rhs) => {
+                Self::concat(*l1, Self::concat(*l2, rhs))
+            }
+            (lhs, rhs) => Expr::Binary(BinaryOp::Concat, Box::new(lhs), Box::new(rhs)),
+        }
+    }
+
+    #[inline]
+    pub fn alternate(lhs: Self, rhs: Self) -> Self {
+        let mut operands = BTreeSet::new();
+        let mut class = CharClass::new();
+        for e in lhs
+            .operands(BinaryOp::Alternate)
+            .chain(rhs.operands(BinaryOp::Alternate))
+        {
+            match e {
+                Expr::Empty => {}
+                // Alternatives of single characters merge into one class.
+                Expr::Atom(c) => class.add_other(c),
+                // Everything absorbs everything else.
+                Expr::Unary(UnaryOp::Complement, ref inner) if **inner == Expr::Empty => return e,
+                e => {
+                    operands.insert(e);
+                }
+            }
+        }
+        if !class.is_empty() {
+            operands.insert(Expr::Atom(class));
+        }
+
+        Self::rebuild(BinaryOp::Alternate, operands).unwrap_or(Expr::Empty)
+    }
+
+    #[inline]
+    pub fn intersect(lhs: Self, rhs: Self) -> Self {
+        let mut operands = BTreeSet::new();
+        for e in lhs
+            .operands(BinaryOp::Intersect)
+            .chain(rhs.operands(BinaryOp::Intersect))
+        {
+            match e {
+                Expr::Empty => return Expr::Empty,
+                // Everything is the identity of intersection.
+                Expr::Unary(UnaryOp::Complement, ref inner) if **inner == Expr::Empty => {}
+                e => {
+                    operands.insert(e);
+                }
+            }
+        }
+
+        Self::rebuild(BinaryOp::Intersect, operands).unwrap_or_else(Self::everything)
+    }
+
+    #[inline]
+    pub fn star(inner: Self) -> Self {
+        match inner {
+            Expr::Empty | Expr::Epsilon => Expr::Epsilon,
+            e @ Expr::Unary(UnaryOp::Star, _) => e,
+            // The empty string is already in every starred language.
+            e @ Expr::Binary(BinaryOp::Alternate, _, _) => {
+                let mut operands: BTreeSet<_> = e.operands(BinaryOp::Alternate).collect();
+                let nullable = operands.remove(&Expr::Epsilon);
+                let inner = Self::rebuild(BinaryOp::Alternate, operands).unwrap_or(Expr::Epsilon);
+                if nullable {
+                    Self::star(inner)
+                } else {
+                    Expr::Unary(UnaryOp::Star, Box::new(inner))
+                }
+            }
+            e => Expr::Unary(UnaryOp::Star, Box::new(e)),
+        }
+    }
+
+    #[inline]
+    pub fn complement(inner: Self) -> Self {
+        match inner {
+            Expr::Unary(UnaryOp::Complement, e) => *e,
+            e => Expr::Unary(UnaryOp::Complement, Box::new(e)),
+        }
+    }
+
+    /// An expression that matches every string.
+    #[inline]
+    pub fn everything() -> Self {
+        Self::complement(Expr::Empty)
+    }
+
+    /// The operands of a chain of the given binary operator, or the expression itself if it is
+    /// not such a chain.
+    #[inline]
+    pub(crate) fn operands(self, op: BinaryOp) -> Box<dyn Iterator<Item = Self>> {
+        match self {
+            Expr::Binary(o, lhs, rhs) if o == op => {
+                Box::new(lhs.operands(op.clone()).chain(rhs.operands(op)))
+            }
+            e => Box::new(std::iter::once(e)),
+        }
+    }
+
+    /// Join sorted operands with the given binary operator, nesting to the right. Returns None if
+    /// there are no operands.
+    #[inline]
+    fn rebuild(op: BinaryOp, operands: BTreeSet<Self>) -> Option<Self> {
+        operands
+            .into_iter()
+            .rev()
+            .reduce(|rhs, lhs| Expr::Binary(op.clone(), Box::new(lhs), Box::new(rhs)))
+    }
+}
+
+/// Brzozowski derivatives.
+impl Expr {
+    /// Determine if the expression matches the empty string.
+    #[inline]
+    pub fn is_nullable(&self) -> bool {
+        match self {
+            Expr::Empty | Expr::Atom(_) => false,
+            Expr::Epsilon => true,
+            Expr::Unary(UnaryOp::Star | UnaryOp::Optional, _) => true,
+            Expr::Unary(UnaryOp::Plus, inner) | Expr::Group(_, inner) => inner.is_nullable(),
+            Expr::Unary(UnaryOp::Complement, inner) => !inner.is_nullable(),
+            Expr::Binary(BinaryOp::Concat | BinaryOp::Intersect, lhs, rhs) => {
+                lhs.is_nullable() && rhs.is_nullable()
+            }
+            Expr::Binary(BinaryOp::Alternate, lhs, rhs) => lhs.is_nullable() || rhs.is_nullable(),
+        }
+    }
+
+    /// Return the derivative of the expression with respect to a character: an expression that
+    /// matches `s` exactly when this expression matches `c` followed by `s`. Groups are dropped,
+    /// since they do not change the strings matched.
+    #[inline]
+    pub fn derivative(&self, c: char) -> Self {
+        match self {
+            Expr::Empty | Expr::Epsilon => Expr::Empty,
+            Expr::Atom(class) if class.contains(c) => Expr::Epsilon,
+            Expr::Atom(_) => Expr::Empty,
+            Expr::Group(_, inner) | Expr::Unary(UnaryOp::Optional, inner) => inner.derivative(c),
+            Expr::Unary(UnaryOp::Star | UnaryOp::Plus, inner) => {
+                Self::concat(inner.derivative(c), Self::star(inner.without_groups()))
+            }
+            Expr::Unary(UnaryOp::Complement, inner) => Self::complement(inner.derivative(c)),
+            Expr::Binary(BinaryOp::Concat, lhs, rhs) => {
+                let first = Self::concat(lhs.derivative(c), rhs.without_groups());
+                if lhs.is_nullable() {
+                    Self::alternate(first, rhs.derivative(c))
+                } else {
+                    first
+                }
+            }
+            Expr::Binary(BinaryOp::Alternate, lhs, rhs) => {
+                Self::alternate(lhs.derivative(c), rhs.derivative(c))
+            }
+            Expr::Binary(BinaryOp::Intersect, lhs, rhs) => {
+                Self::intersect(lhs.derivative(c), rhs.derivative(c))
+            }
+        }
+    }
+
+    /// Partition the characters into classes such that all characters in a class have the same
+    /// derivative. The partition may be finer than necessary, and classes may be empty.
+    #[inline]
+    pub fn derivative_classes(&self) -> Vec<CharClass> {
+        match self {
+            Expr::Empty | Expr::Epsilon => vec![CharClass::all()],
+            Expr::Atom(class) => vec![class.clone(), class.complement()],
+            Expr::Group(_, inner) | Expr::Unary(_, inner) => inner.derivative_classes(),
+            Expr::Binary(BinaryOp::Concat, lhs, rhs) if !lhs.is_nullable() => {
+                lhs.derivative_classes()
+            }
+            Expr::Binary(_, lhs, rhs) => {
+                let rhs = rhs.derivative_classes();
+                lhs.derivative_classes()
+                    .iter()
+                    .flat_map(|l| rhs.iter().map(move |r| l.intersection(r)))
+                    .filter(|class| !class.is_empty())
+                    .collect()
+            }
+        }
+    }
+
+    /// Rebuild the expression with the smart constructors, dropping groups and rewriting the
+    /// operators the derivatives do not produce.
+    #[inline]
+    pub fn without_groups(&self) -> Self {
+        match self {
+            Expr::Empty => Expr::Empty,
+            Expr::Epsilon => Expr::Epsilon,
+            Expr::Atom(class) => Self::atom(class.clone()),
+            Expr::Group(_, inner) => inner.without_groups(),
+            Expr::Unary(UnaryOp::Star, inner) => Self::star(inner.without_groups()),
+            Expr::Unary(UnaryOp::Plus, inner) => {
+                let inner = inner.without_groups();
+                Self::concat(inner.clone(), Self::star(inner))
+            }
+            Expr::Unary(UnaryOp::Optional, inner) => {
+                Self::alternate(Expr::Epsilon, inner.without_groups())
+            }
+            Expr::Unary(UnaryOp::Complement, inner) => Self::complement(inner.without_groups()),
+            Expr::Binary(BinaryOp::Concat, lhs, rhs) => {
+                Self::concat(lhs.without_groups(), rhs.without_groups())
+            }
+            Expr::Binary(BinaryOp::Alternate, lhs, rhs) => {
+                Self::alternate(lhs.without_groups(), rhs.without_groups())
+            }
+            Expr::Binary(BinaryOp::Intersect, lhs, rhs) => {
+                Self::intersect(lhs.without_groups(), rhs.without_groups())
+            }
+        }
+    }
+}
+
+/// Precedence levels for printing, from loosest to tightest binding.
+const ALTERNATE: u8 = 0;
+const CONCAT: u8 = 1;
+const POSTFIX: u8 = 2;
+const ATOM: u8 = 3;
+
+/// Print the expression in the syntax of the parser, adding parentheses only where precedence
+/// requires them. An alternative of the empty string is printed with `?`, and a concatenation
+/// of an expression and its star with `+`.
+///
+/// The parser has no syntax of its own for the empty language or the empty string, so they are
+/// printed as a class of no characters, `[^\s\S]`, and its star. It has none at all for
+/// intersection and complement, which are printed with `&` and a prefix `~`. Parentheses added
+/// for precedence are capturing groups when parsed again.
+impl fmt::Display for Expr {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_at(f, ALTERNATE)
+    }
+}
+
+impl Expr {
+    /// Print the expression as an operand of an operator with the given precedence.
+    fn fmt_at(&self, f: &mut fmt::Formatter<'_>, precedence: u8) -> fmt::Result {
+        if self.precedence() < precedence {
+            f.write_str("(")?;
+            self.fmt_inner(f)?;
+            f.write_str(")")
+        } else {
+            self.fmt_inner(f)
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Empty | Expr::Atom(_) | Expr::Group(_, _) => ATOM,
+            Expr::Epsilon | Expr::Unary(_, _) => POSTFIX,
+            Expr::Binary(BinaryOp::Concat, _, _) => CONCAT,
+            Expr::Binary(BinaryOp::Alternate, _, _) => {
+                if self.chain(&BinaryOp::Alternate).contains(&&Expr::Epsilon) {
+                    POSTFIX
+                } else {
+                    ALTERNATE
+                }
+            }
+            Expr::Binary(BinaryOp::Intersect, _, _) => ALTERNATE,
+        }
+    }
+
+    fn fmt_inner(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Empty => f.write_str(r"[^\s\S]"),
+            Expr::Epsilon => f.write_str(r"[^\s\S]*"),
+            Expr::Atom(class) => fmt_class(f, class),
+            Expr::Group(_, inner) => {
+                f.write_str("(")?;
+                inner.fmt_at(f, ALTERNATE)?;
+                f.write_str(")")
+            }
+            Expr::Unary(UnaryOp::Complement, inner) => {
+                f.write_str("~")?;
+                inner.fmt_at(f, ATOM)
+            }
+            Expr::Unary(op, inner) => {
+                inner.fmt_at(f, ATOM)?;
+                f.write_str(match op {
+                    UnaryOp::Star => "*",
+                    UnaryOp::Plus => "+",
+                    _ => "?",
+                })
+            }
+            Expr::Binary(BinaryOp::Concat, _, _) => {
+                let operands = self.chain(&BinaryOp::Concat);
+                let mut i = 0;
+                while i < operands.len() {
+                    let e = operands[i];
+                    match operands.get(i + 1) {
+                        Some(Expr::Unary(UnaryOp::Star, inner)) if **inner == *e => {
+                            e.fmt_at(f, ATOM)?;
+                            f.write_str("+")?;
+                            i += 2;
+                        }
+                        _ => {
+                            e.fmt_at(f, CONCAT)?;
+                            i += 1;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            Expr::Binary(BinaryOp::Alternate, _, _) => {
+                let operands = self.chain(&BinaryOp::Alternate);
+                let rest: Vec<_> = operands.iter().filter(|e| ***e != Expr::Epsilon).collect();
+                if rest.len() == operands.len() {
+                    return fmt_separated(f, &operands, "|");
+                }
+
+                if let [e] = rest[..] {
+                    e.fmt_at(f, ATOM)?;
+                } else {
+                    f.write_str("(")?;
+                    fmt_separated(f, &rest.into_iter().copied().collect::<Vec<_>>(), "|")?;
+                    f.write_str(")")?;
+                }
+                f.write_str("?")
+            }
+            Expr::Binary(BinaryOp::Intersect, _, _) => {
+                fmt_separated(f, &self.chain(&BinaryOp::Intersect), "&")
+            }
+        }
+    }
+
+    /// Borrow the operands of a chain of the given binary operator.
+    fn chain(&self, op: &BinaryOp) -> Vec<&Self> {
+        match self {
+            Expr::Binary(o, lhs, rhs) if o == op => {
+                let mut operands = lhs.chain(op);
+                operands.extend(rhs.chain(op));
+                operands
+            }
+            e => vec![e],
+        }
+    }
+}
+
+fn fmt_separated(f: &mut fmt::Formatter<'_>, operands: &[&Expr], separator: &str) -> fmt::Result {
+    for (i, e) in operands.iter().enumerate() {
+        if i > 0 {
+            f.write_str(separator)?;
+        }
+        e.fmt_at(f, CONCAT)?;
+    }
+    Ok(())
+}
+
+/// Print a class as a wildcard, a named class, a single character, or a bracketed class, negated
+/// if that takes fewer ranges.
+fn fmt_class(f: &mut fmt::Formatter<'_>, class: &CharClass) -> fmt::Result {
+    // Classes are compared by their complements, since equal classes may be split differently
+    // into ranges.
+    let complement = class.complement();
+    if complement.is_empty() {
+        return f.write_str(r"[\s\S]");
+    }
+    if complement.is_single() && complement.contains('\n') {
+        return f.write_str(".");
+    }
+    let named = [
+        (r"\d", CharClass::decimal_number()),
+        (r"\s", CharClass::whitespace()),
+        (r"\w", CharClass::word()),
+    ];
+    for (name, named) in &named {
+        if same_chars(class, &complement, named) {
+            return f.write_str(name);
+        }
+        if same_chars(&complement, class, named) {
+            return f.write_str(&name.to_uppercase());
+        }
+    }
+    if let Some(c) = class.representative().filter(|_| class.is_single()) {
+        return fmt_char(f, c, r"\()[].?*+|&~");
+    }
+
+    let (ranges, negated) = if complement.iter().count() < class.iter().count() {
+        (complement, true)
+    } else {
+        (class.clone(), false)
+    };
+
+    // Adjacent ranges are kept apart in a class, but print as one.
+    let mut merged: Vec<(char, char)> = Vec::new();
+    for range in &ranges {
+        match merged.last_mut() {
+            Some((_, end)) if *end as u32 + 1 == range.start as u32 => *end = range.end,
+            _ => merged.push((range.start, range.end)),
+        }
+    }
+
+    f.write_str(if negated { "[^" } else { "[" })?;
+    for (start, end) in merged {
+        fmt_char(f, start, r"\[]^-")?;
+        if end != start {
+            if end as u32 > start as u32 + 1 {
+                f.write_str("-")?;
+            }
+            fmt_char(f, end, r"\[]^-")?;
+        }
+    }
+    f.write_str("]")
+}
+
+/// Determine if a class, given with its complement, has the same characters as another class.
+fn same_chars(class: &CharClass, complement: &CharClass, other: &CharClass) -> bool {
+    class.intersection(&other.complement()).is_empty() && complement.intersection(other).is_empty()
+}
+
+/// Print a character, escaping it if it is one of the given special characters.
+fn fmt_char(f: &mut fmt::Formatter<'_>, c: char, special: &str) -> fmt::Result {
+    if c == '\n' {
+        f.write_str(r"\n")
+    } else if special.contains(c) {
+        write!(f, "\\{}", c)
+    } else {
+        write!(f, "{}", c)
+    }
 }
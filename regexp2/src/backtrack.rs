@@ -0,0 +1,240 @@
+use crate::class::CharClass;
+use crate::regexp::{Aborted, Budget, Engine, Match};
+
+use automata::nfa::Transition;
+use automata::NFA;
+
+/// The default size of the visited set, in bits.
+pub const DEFAULT_VISITED_CAPACITY: usize = 256 * 1024 * 8;
+
+/// A regular expression engine that searches an NFA depth-first, backtracking on failure. Every
+/// pair of NFA state and input position is explored at most once, which is tracked in a bitset,
+/// so a search takes time linear in the number of states times the length of the input.
+///
+/// Because the bitset grows with the length of the input, only inputs up to
+/// [`BoundedBacktracker::max_input_len`] characters are searched by backtracking; longer inputs
+/// fall back to NFA simulation.
+#[derive(Clone, Debug)]
+pub struct BoundedBacktracker {
+    nfa: NFA<CharClass>,
+    /// The maximum size of the visited set, in bits.
+    capacity: usize,
+}
+
+impl From<NFA<CharClass>> for BoundedBacktracker {
+    #[inline]
+    fn from(nfa: NFA<CharClass>) -> Self {
+        Self::new(nfa)
+    }
+}
+
+impl BoundedBacktracker {
+    /// Create a backtracker with the default visited set capacity.
+    #[inline]
+    pub fn new(nfa: NFA<CharClass>) -> Self {
+        Self::with_capacity(nfa, DEFAULT_VISITED_CAPACITY)
+    }
+
+    /// Create a backtracker whose visited set holds at most `capacity` bits.
+    #[inline]
+    pub fn with_capacity(nfa: NFA<CharClass>, capacity: usize) -> Self {
+        Self { nfa, capacity }
+    }
+
+    /// The longest input, in characters, that can be searched without exceeding the visited set
+    /// capacity.
+    #[inline]
+    pub fn max_input_len(&self) -> usize {
+        (self.capacity / self.nfa.total_states).saturating_sub(1)
+    }
+
+    /// Determine if an input of the given length, in characters, is searched by backtracking.
+    #[inline]
+    pub fn can_search(&self, len: usize) -> bool {
+        len <= self.max_input_len()
+    }
+
+    #[inline]
+    pub fn nfa(&self) -> &NFA<CharClass> {
+        &self.nfa
+    }
+
+    /// Search from the start of `input` for an accepting state. Returns the end of the longest
+    /// match, or the shortest if `shortest` is set. If `full` is set, only a match of the entire
+    /// input is reported.
+    ///
+    /// The first time a character is read, it takes a step from the budget, if any. Reading it
+    /// again after backtracking is free, so a search takes no more steps than there are
+    /// characters.
+    #[inline]
+    fn search(
+        &self,
+        input: &[char],
+        shortest: bool,
+        full: bool,
+        budget: Option<&Budget<'_>>,
+    ) -> Result<Option<usize>, Aborted> {
+        if let Some(budget) = budget {
+            budget.check()?;
+        }
+
+        let len = input.len();
+        // The number of characters read so far.
+        let mut read = 0;
+        let mut visited = Visited::new(self.nfa.total_states, len + 1);
+        let mut stack = vec![(self.nfa.start_state, 0)];
+        let mut result: Option<usize> = None;
+
+        while let Some((state, pos)) = stack.pop() {
+            if !visited.insert(state, pos) {
+                continue;
+            }
+
+            if self.nfa.is_accepting_state(&state) && (!full || pos == len) {
+                result = Some(match result {
+                    Some(end) if shortest => end.min(pos),
+                    Some(end) => end.max(pos),
+                    None => pos,
+                });
+
+                // No better match can be found.
+                if (shortest && pos == 0) || (!shortest && pos == len) {
+                    break;
+                }
+            }
+
+            // Push in reverse so that transitions are explored in order.
+            for (symbol, end) in self.nfa.adjacency().outgoing(state).rev() {
+                match symbol {
+                    Transition::Epsilon => stack.push((end, pos)),
+                    Transition::Some(cc) => {
+                        if let Some(&c) = input.get(pos) {
+                            if pos == read {
+                                if let Some(budget) = budget {
+                                    budget.step()?;
+                                }
+                                read += 1;
+                            }
+                            if cc.contains(c) {
+                                stack.push((end, pos + 1));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    #[inline]
+    fn is_match_impl(&self, input: &str, budget: Option<&Budget<'_>>) -> Result<bool, Aborted> {
+        let chars: Vec<char> = input.chars().collect();
+        if !self.can_search(chars.len()) {
+            return match budget {
+                Some(budget) => Engine::is_match_within(&self.nfa, input, budget),
+                None => Ok(Engine::is_match(&self.nfa, input)),
+            };
+        }
+
+        Ok(self.search(&chars, false, true, budget)?.is_some())
+    }
+
+    #[inline]
+    fn find_at_impl(
+        &self,
+        input: &str,
+        start: usize,
+        shortest: bool,
+        budget: Option<&Budget<'_>>,
+    ) -> Result<Option<Match>, Aborted> {
+        let chars: Vec<char> = input.chars().skip(start).collect();
+        if !self.can_search(chars.len()) {
+            return match (budget, shortest) {
+                (Some(budget), true) => {
+                    Engine::find_shortest_at_within(&self.nfa, input, start, budget)
+                }
+                (Some(budget), false) => Engine::find_at_within(&self.nfa, input, start, budget),
+                (None, true) => Ok(Engine::find_shortest_at(&self.nfa, input, start)),
+                (None, false) => Ok(Engine::find_at(&self.nfa, input, start)),
+            };
+        }
+
+        Ok(self.search(&chars, shortest, false, budget)?.map(|len| {
+            let span = chars[..len].iter().collect();
+            Match::new(start, start + len, span)
+        }))
+    }
+}
+
+impl Engine for BoundedBacktracker {
+    #[inline]
+    fn is_match(&self, input: &str) -> bool {
+        self.is_match_impl(input, None)
+            .unwrap_or_else(|_| unreachable!("there is no budget to run out"))
+    }
+
+    #[inline]
+    fn find_shortest_at(&self, input: &str, start: usize) -> Option<Match> {
+        self.find_at_impl(input, start, true, None)
+            .unwrap_or_else(|_| unreachable!("there is no budget to run out"))
+    }
+
+    #[inline]
+    fn find_at(&self, input: &str, start: usize) -> Option<Match> {
+        self.find_at_impl(input, start, false, None)
+            .unwrap_or_else(|_| unreachable!("there is no budget to run out"))
+    }
+
+    #[inline]
+    fn is_match_within(&self, input: &str, budget: &Budget<'_>) -> Result<bool, Aborted> {
+        self.is_match_impl(input, Some(budget))
+    }
+
+    #[inline]
+    fn find_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, Aborted> {
+        self.find_at_impl(input, start, false, Some(budget))
+    }
+
+    #[inline]
+    fn find_shortest_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, Aborted> {
+        self.find_at_impl(input, start, true, Some(budget))
+    }
+}
+
+/// A bitset of (state, position) pairs.
+struct Visited {
+    bits: Vec<u64>,
+    positions: usize,
+}
+
+impl Visited {
+    #[inline]
+    fn new(states: usize, positions: usize) -> Self {
+        let len = states * positions;
+        Self {
+            bits: vec![0; len / 64 + 1],
+            positions,
+        }
+    }
+
+    /// Mark the pair as visited. Returns false if it was already visited.
+    #[inline]
+    fn insert(&mut self, state: usize, pos: usize) -> bool {
+        let i = state * self.positions + pos;
+        let (word, bit) = (i / 64, 1 << (i % 64));
+        let unvisited = self.bits[word] & bit == 0;
+        self.bits[word] |= bit;
+        unvisited
+    }
+}
@@ -7,6 +7,7 @@ use std::hash::Hash;
 use std::iter;
 
 use automata::convert::Disjoin;
+use automata::product::Universe;
 
 /// The lowest Unicode scalar value.
 const USV_START_1: char = '\u{0}';
@@ -19,7 +20,7 @@ const USV_END_2: char = '\u{10ffff}';
 
 /// A set of character ranges that represent one character class. A CharClass contains all the
 /// ranges in a single bracketed segment of character ranges in a regular expression.
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct CharClass {
     /// The ranges included in the character class.
     pub ranges: MergeSet<char, CharRange>,
@@ -83,18 +84,23 @@ impl CharClass {
         class.ranges.into_iter().for_each(|r| self.add_range(r));
     }
 
+    /// Return some character in the class, preferring a printable ASCII character so that
+    /// examples built from classes are readable. Returns None if the class is empty.
+    #[inline]
+    pub fn representative(&self) -> Option<char> {
+        let printable = CharRange::new(' ', '~');
+        self.iter()
+            .find_map(|r| r.intersection(&printable))
+            .or_else(|| self.iter().next().cloned())
+            .map(|r| r.start)
+    }
+
     #[inline]
     pub fn is_single(&self) -> bool {
         let mut iter = self.ranges.iter();
         let c = match iter.next() {
-            Some(r) => {
-                if r.start == r.end {
-                    r.start
-                } else {
-                    return false;
-                }
-            }
-            None => return false,
+            Some(r) if r.start == r.end => r.start,
+            _ => return false,
         };
 
         iter.all(|range| c == range.start && c == range.end)
@@ -108,6 +114,16 @@ impl CharClass {
         CharRange::new('\n', '\n').into()
     }
 
+    /// Create a character class of all characters.
+    #[inline]
+    pub fn all() -> Self {
+        let ranges = vec![
+            CharRange::new(USV_START_1, USV_END_1),
+            CharRange::new(USV_START_2, USV_END_2),
+        ];
+        ranges.into()
+    }
+
     #[inline]
     pub fn all_but_newline() -> Self {
         Self::newline().complement()
@@ -308,7 +324,7 @@ impl Disjoin for CharClass {
         let mut starts: Vec<_> = ranges.iter().map(|r| (r.start as u32, 1)).collect();
         let mut ends: Vec<_> = ranges.iter().map(|r| (r.end as u32 + 1, -1)).collect();
         starts.append(&mut ends);
-        starts.sort_by(|a, b| a.0.cmp(&b.0));
+        starts.sort_by_key(|a| a.0);
 
         let mut prev = 0;
         let mut count = 0;
@@ -334,9 +350,31 @@ impl Disjoin for CharClass {
     }
 }
 
+impl Universe for CharClass {
+    #[inline]
+    fn complement_of(vec: Vec<&Self>) -> Option<Self> {
+        let mut union = CharClass::new();
+        for class in vec {
+            union.copy_from(class);
+        }
+
+        let complement = if union.is_empty() {
+            CharClass::all()
+        } else {
+            union.complement()
+        };
+
+        if complement.is_empty() {
+            None
+        } else {
+            Some(complement)
+        }
+    }
+}
+
 /// A range of characters representing all characters from the lower bound to the upper bound,
 /// inclusive.
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct CharRange {
     pub start: char,
     pub end: char,
@@ -361,6 +399,34 @@ impl CharRange {
         self.start <= c && c <= self.end
     }
 
+    /// The number of characters in the range. Surrogate code points, which are not characters,
+    /// are not counted.
+    #[inline]
+    pub fn size(&self) -> u32 {
+        let surrogates = if self.start <= USV_END_1 && self.end >= USV_START_2 {
+            USV_START_2 as u32 - USV_END_1 as u32 - 1
+        } else {
+            0
+        };
+        self.end as u32 - self.start as u32 + 1 - surrogates
+    }
+
+    /// Return the `n`th character of the range, counting from zero and skipping surrogate code
+    /// points, or None if the range has `n` or fewer characters.
+    #[inline]
+    pub fn nth(&self, n: u32) -> Option<char> {
+        if n >= self.size() {
+            return None;
+        }
+
+        let c = self.start as u32 + n;
+        if self.start <= USV_END_1 && c > USV_END_1 as u32 {
+            char::from_u32(c + (USV_START_2 as u32 - USV_END_1 as u32 - 1))
+        } else {
+            char::from_u32(c)
+        }
+    }
+
     /// Return the range that is the intersection between two ranges.
     #[inline]
     pub fn intersection(&self, other: &Self) -> Option<Self> {
@@ -392,6 +458,9 @@ impl CharRange {
 
             let r2 = Self::new(USV_START_1, USV_END_1);
             ranges.push(r2);
+        } else if self.start == USV_START_2 {
+            let r = Self::new(USV_START_1, USV_END_1);
+            ranges.push(r);
         } else if self.start > USV_START_1 {
             let r = Self::new(USV_START_1, shift_char(self.start, false));
             ranges.push(r);
@@ -403,6 +472,9 @@ impl CharRange {
 
             let r2 = Self::new(USV_START_2, USV_END_2);
             ranges.push(r2);
+        } else if self.end == USV_END_1 {
+            let r = Self::new(USV_START_2, USV_END_2);
+            ranges.push(r);
         } else if self.end < USV_END_2 {
             let r = Self::new(shift_char(self.end, true), USV_END_2);
             ranges.push(r);
@@ -0,0 +1,322 @@
+use crate::class::CharClass;
+use crate::regexp::{Aborted, Budget, Engine, Match};
+
+use std::collections::{BTreeSet, HashMap};
+use std::convert::TryInto;
+
+use automata::DFA;
+
+/// Sentinel state in the transition table for missing transitions.
+const DEAD: u32 = u32::MAX;
+
+/// The first code point of the surrogate range, which contains no Unicode scalar values.
+const SURROGATE_START: u32 = 0xd800;
+/// The first code point after the surrogate range.
+const SURROGATE_END: u32 = 0xe000;
+/// One past the highest Unicode scalar value.
+const USV_END: u32 = 0x11_0000;
+
+/// A mapping from characters to alphabet equivalence classes. Two characters are in the same
+/// class if no transition in the DFA the mapping was built from distinguishes between them.
+#[derive(Clone, Debug)]
+pub struct CharClasses {
+    /// Classes of the ASCII characters, for a lookup without searching.
+    ascii: [u32; 128],
+    /// The start of each contiguous segment of characters and the class it belongs to, sorted by
+    /// start.
+    segments: Vec<(u32, u32)>,
+    /// The number of distinct classes.
+    len: usize,
+}
+
+impl CharClasses {
+    /// Compute the coarsest equivalence classes for which every character class in `classes`
+    /// is a union of equivalence classes.
+    #[inline]
+    pub fn new(classes: &[&CharClass]) -> Self {
+        // Every point at which membership in some class may change.
+        let mut boundaries = BTreeSet::new();
+        boundaries.insert(0);
+        boundaries.insert(SURROGATE_START);
+        boundaries.insert(SURROGATE_END);
+        for cc in classes {
+            for r in cc.iter() {
+                boundaries.insert(r.start as u32);
+                boundaries.insert(r.end as u32 + 1);
+            }
+        }
+        boundaries.remove(&USV_END);
+
+        // Segments whose characters belong to exactly the same set of classes share an
+        // equivalence class.
+        let mut signatures = HashMap::new();
+        let segments: Vec<(u32, u32)> = boundaries
+            .into_iter()
+            .map(|start| {
+                let signature: Vec<bool> = match char::from_u32(start) {
+                    Some(c) => classes.iter().map(|cc| cc.contains(c)).collect(),
+                    // The surrogate segment never matches anything.
+                    None => vec![false; classes.len()],
+                };
+
+                let next = signatures.len() as u32;
+                let class = *signatures.entry(signature).or_insert(next);
+                (start, class)
+            })
+            .collect();
+
+        let mut char_classes = Self {
+            ascii: [0; 128],
+            segments,
+            len: signatures.len(),
+        };
+        for (i, c) in (0..128u8).map(char::from).enumerate() {
+            char_classes.ascii[i] = char_classes.search(c);
+        }
+
+        char_classes
+    }
+
+    /// Return the equivalence class of the given character.
+    #[inline]
+    pub fn get(&self, c: char) -> u32 {
+        match self.ascii.get(c as usize) {
+            Some(&class) => class,
+            None => self.search(c),
+        }
+    }
+
+    /// The number of distinct equivalence classes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    fn search(&self, c: char) -> u32 {
+        let i = self
+            .segments
+            .partition_point(|&(start, _)| start <= c as u32);
+        self.segments[i - 1].1
+    }
+
+    /// Return some character in the given class, or None if the class contains no characters.
+    #[inline]
+    pub(crate) fn representative(&self, class: u32) -> Option<char> {
+        self.segments
+            .iter()
+            .filter(|&&(_, cls)| cls == class)
+            .find_map(|&(start, _)| char::from_u32(start))
+    }
+}
+
+/// A compiled form of a [`DFA`] over [`CharClass`] transitions. Characters are mapped to a small
+/// number of alphabet equivalence classes, and transitions are stored in a single flat table
+/// indexed by `state * classes + class`, so each input character costs one table lookup.
+#[derive(Clone, Debug)]
+pub struct DenseDFA {
+    classes: CharClasses,
+    /// The flat transition table; [`DEAD`] marks a missing transition.
+    transitions: Vec<u32>,
+    accepting: Vec<bool>,
+    start: u32,
+}
+
+impl From<&DFA<CharClass>> for DenseDFA {
+    #[inline]
+    fn from(dfa: &DFA<CharClass>) -> Self {
+        let labels: Vec<&CharClass> = dfa.transition.into_iter().map(|(_, t, _)| t).collect();
+        let classes = CharClasses::new(&labels);
+        let stride = classes.len();
+
+        let representatives: Vec<Option<char>> = (0..stride)
+            .map(|class| classes.representative(class as u32))
+            .collect();
+
+        let mut transitions = vec![DEAD; dfa.total_states * stride];
+        for (start, t, end) in &dfa.transition {
+            let row = start * stride;
+            for (class, c) in representatives.iter().enumerate() {
+                if let Some(c) = c {
+                    if t.contains(*c) {
+                        transitions[row + class] = end.try_into().unwrap();
+                    }
+                }
+            }
+        }
+
+        let accepting = (0..dfa.total_states)
+            .map(|s| dfa.is_accepting_state(&s))
+            .collect();
+
+        Self {
+            classes,
+            transitions,
+            accepting,
+            start: dfa.start_state.try_into().unwrap(),
+        }
+    }
+}
+
+impl From<DFA<CharClass>> for DenseDFA {
+    #[inline]
+    fn from(dfa: DFA<CharClass>) -> Self {
+        (&dfa).into()
+    }
+}
+
+impl DenseDFA {
+    /// The state the DFA starts in.
+    #[inline]
+    pub fn start_state(&self) -> u32 {
+        self.start
+    }
+
+    /// The number of states in the DFA.
+    #[inline]
+    pub fn total_states(&self) -> usize {
+        self.accepting.len()
+    }
+
+    /// The alphabet equivalence classes used to index the transition table.
+    #[inline]
+    pub fn classes(&self) -> &CharClasses {
+        &self.classes
+    }
+
+    /// Return the state reached from the given state on the given character, or None if there is
+    /// no transition.
+    #[inline]
+    pub fn next_state(&self, state: u32, c: char) -> Option<u32> {
+        let class = self.classes.get(c) as usize;
+        match self.transitions[state as usize * self.classes.len() + class] {
+            DEAD => None,
+            next => Some(next),
+        }
+    }
+
+    #[inline]
+    pub fn is_accepting_state(&self, state: u32) -> bool {
+        self.accepting[state as usize]
+    }
+
+    /// Determine if the given input is accepted by the DFA.
+    #[inline]
+    pub fn is_match(&self, input: &str) -> bool {
+        self.is_match_impl(input, None)
+            .unwrap_or_else(|_| unreachable!("there is no budget to run out"))
+    }
+
+    /// Run the DFA over the input, taking a step from the budget, if any, for each character.
+    #[inline]
+    fn is_match_impl(&self, input: &str, budget: Option<&Budget<'_>>) -> Result<bool, Aborted> {
+        if let Some(budget) = budget {
+            budget.check()?;
+        }
+
+        let mut state = self.start;
+        for c in input.chars() {
+            if let Some(budget) = budget {
+                budget.step()?;
+            }
+            state = match self.next_state(state, c) {
+                Some(next) => next,
+                None => return Ok(false),
+            };
+        }
+        Ok(self.is_accepting_state(state))
+    }
+
+    #[inline]
+    fn find_at_impl(
+        &self,
+        input: &str,
+        start: usize,
+        shortest: bool,
+        budget: Option<&Budget<'_>>,
+    ) -> Result<Option<Match>, Aborted> {
+        if let Some(budget) = budget {
+            budget.check()?;
+        }
+
+        let mut last_match = None;
+        let mut state = self.start;
+        let mut end = start;
+        let mut chars = input.chars().skip(start);
+        loop {
+            if self.is_accepting_state(state) {
+                last_match = Some(end);
+                if shortest {
+                    break;
+                }
+            }
+
+            let c = match chars.next() {
+                Some(c) => c,
+                None => break,
+            };
+            if let Some(budget) = budget {
+                budget.step()?;
+            }
+            state = match self.next_state(state, c) {
+                Some(next) => next,
+                None => break,
+            };
+            end += 1;
+        }
+
+        Ok(last_match.map(|end| {
+            let span = input.chars().skip(start).take(end - start).collect();
+            Match::new(start, end, span)
+        }))
+    }
+}
+
+impl Engine for DenseDFA {
+    #[inline]
+    fn is_match(&self, input: &str) -> bool {
+        DenseDFA::is_match(self, input)
+    }
+
+    #[inline]
+    fn find_shortest_at(&self, input: &str, start: usize) -> Option<Match> {
+        self.find_at_impl(input, start, true, None)
+            .unwrap_or_else(|_| unreachable!("there is no budget to run out"))
+    }
+
+    #[inline]
+    fn find_at(&self, input: &str, start: usize) -> Option<Match> {
+        self.find_at_impl(input, start, false, None)
+            .unwrap_or_else(|_| unreachable!("there is no budget to run out"))
+    }
+
+    #[inline]
+    fn is_match_within(&self, input: &str, budget: &Budget<'_>) -> Result<bool, Aborted> {
+        self.is_match_impl(input, Some(budget))
+    }
+
+    #[inline]
+    fn find_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, Aborted> {
+        self.find_at_impl(input, start, false, Some(budget))
+    }
+
+    #[inline]
+    fn find_shortest_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, Aborted> {
+        self.find_at_impl(input, start, true, Some(budget))
+    }
+}
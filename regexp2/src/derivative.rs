@@ -0,0 +1,219 @@
+use crate::ast::Expr;
+use crate::class::CharClass;
+use crate::parser::ast::ASTParser;
+use crate::regexp::{Aborted, Budget, CompileResult, Engine, Match};
+
+use std::collections::{hash_map::Entry, BTreeMap, HashMap, VecDeque};
+
+use automata::limit::{Limits, SizeLimitExceeded};
+use automata::DFA;
+
+/// A regular expression engine that matches by taking the Brzozowski derivative of the
+/// expression with respect to each input character in turn; the input matches if the final
+/// expression matches the empty string. No automaton is built, so it is slow, but it is simple
+/// enough to serve as a reference for the other engines, and it supports intersection and
+/// complement.
+#[derive(Clone, Debug)]
+pub struct DerivativeMatcher {
+    expr: Expr,
+}
+
+impl DerivativeMatcher {
+    /// Parse a regular expression into a derivative matcher.
+    #[inline]
+    pub fn new(expr: &'_ str) -> CompileResult<'_, Self> {
+        let parser: ASTParser<CharClass> = ASTParser::new();
+        Ok(parser.parse(expr)?.into())
+    }
+
+    /// The expression matched, normalized by the smart constructors of [`Expr`].
+    #[inline]
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    /// Build a DFA whose states are the distinct derivatives of the expression. The transitions
+    /// out of each state are labeled by the classes of [`Expr::derivative_classes`], merged by
+    /// destination; derivatives that match nothing are left out.
+    #[inline]
+    pub fn to_dfa(&self) -> DFA<CharClass> {
+        self.to_dfa_with_limits(Limits::none())
+            .expect("there are no limits to exceed")
+    }
+
+    /// Build a DFA from the derivatives of the expression, as [`DerivativeMatcher::to_dfa`] does,
+    /// but fail as soon as the DFA has more states than the limit.
+    #[inline]
+    pub fn to_dfa_with_limits(&self, limits: Limits) -> Result<DFA<CharClass>, SizeLimitExceeded> {
+        let mut dfa = DFA::new();
+        let mut labels = HashMap::new();
+        let mut queue = VecDeque::new();
+        let mut transitions = Vec::new();
+
+        if self.expr == Expr::Empty {
+            return Ok(dfa);
+        }
+        labels.insert(self.expr.clone(), dfa.start_state);
+        queue.push_back(self.expr.clone());
+        while let Some(expr) = queue.pop_front() {
+            let label = labels[&expr];
+            if expr.is_nullable() {
+                dfa.accepting_states.insert(label);
+            }
+
+            // Every character in a class has the same derivative, so any one computes it.
+            let mut edges: BTreeMap<usize, CharClass> = BTreeMap::new();
+            for class in expr.derivative_classes() {
+                let c = match class.representative() {
+                    Some(c) => c,
+                    None => continue,
+                };
+                let next = expr.derivative(c);
+                if next == Expr::Empty {
+                    continue;
+                }
+
+                let next_label = match labels.entry(next) {
+                    Entry::Occupied(e) => *e.get(),
+                    Entry::Vacant(e) => {
+                        let l = dfa.add_state(false);
+                        limits.check_dfa_states(dfa.total_states)?;
+                        queue.push_back(e.key().clone());
+                        e.insert(l);
+                        l
+                    }
+                };
+                edges.entry(next_label).or_default().add_other(class);
+            }
+
+            transitions.extend(
+                edges
+                    .into_iter()
+                    .map(|(next_label, class)| (label, class, next_label)),
+            );
+        }
+
+        dfa.add_transitions(transitions);
+        Ok(dfa)
+    }
+
+    /// Take the derivative of the expression by each character of the input, taking a step from
+    /// the budget, if any, for each.
+    #[inline]
+    fn is_match_impl(&self, input: &str, budget: Option<&Budget<'_>>) -> Result<bool, Aborted> {
+        if let Some(budget) = budget {
+            budget.check()?;
+        }
+
+        let mut expr = self.expr.clone();
+        for c in input.chars() {
+            if let Some(budget) = budget {
+                budget.step()?;
+            }
+            expr = expr.derivative(c);
+            if expr == Expr::Empty {
+                return Ok(false);
+            }
+        }
+        Ok(expr.is_nullable())
+    }
+
+    #[inline]
+    fn find_at_impl(
+        &self,
+        input: &str,
+        start: usize,
+        shortest: bool,
+        budget: Option<&Budget<'_>>,
+    ) -> Result<Option<Match>, Aborted> {
+        if let Some(budget) = budget {
+            budget.check()?;
+        }
+
+        let mut last_match = None;
+
+        let mut expr = self.expr.clone();
+        let mut end = start;
+        let mut chars = input.chars().skip(start);
+        loop {
+            if expr.is_nullable() {
+                last_match = Some(end);
+                if shortest {
+                    break;
+                }
+            }
+
+            let c = match chars.next() {
+                Some(c) => c,
+                None => break,
+            };
+            if let Some(budget) = budget {
+                budget.step()?;
+            }
+            expr = expr.derivative(c);
+            if expr == Expr::Empty {
+                break;
+            }
+            end += 1;
+        }
+
+        Ok(last_match.map(|end| {
+            let span = input.chars().skip(start).take(end - start).collect();
+            Match::new(start, end, span)
+        }))
+    }
+}
+
+impl From<Expr> for DerivativeMatcher {
+    #[inline]
+    fn from(expr: Expr) -> Self {
+        Self {
+            expr: expr.without_groups(),
+        }
+    }
+}
+
+impl Engine for DerivativeMatcher {
+    #[inline]
+    fn is_match(&self, input: &str) -> bool {
+        self.is_match_impl(input, None)
+            .unwrap_or_else(|_| unreachable!("there is no budget to run out"))
+    }
+
+    #[inline]
+    fn find_shortest_at(&self, input: &str, start: usize) -> Option<Match> {
+        self.find_at_impl(input, start, true, None)
+            .unwrap_or_else(|_| unreachable!("there is no budget to run out"))
+    }
+
+    #[inline]
+    fn find_at(&self, input: &str, start: usize) -> Option<Match> {
+        self.find_at_impl(input, start, false, None)
+            .unwrap_or_else(|_| unreachable!("there is no budget to run out"))
+    }
+
+    #[inline]
+    fn is_match_within(&self, input: &str, budget: &Budget<'_>) -> Result<bool, Aborted> {
+        self.is_match_impl(input, Some(budget))
+    }
+
+    #[inline]
+    fn find_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, Aborted> {
+        self.find_at_impl(input, start, false, Some(budget))
+    }
+
+    #[inline]
+    fn find_shortest_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, Aborted> {
+        self.find_at_impl(input, start, true, Some(budget))
+    }
+}
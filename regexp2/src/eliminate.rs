@@ -0,0 +1,291 @@
+use crate::ast::{BinaryOp, Expr, UnaryOp};
+use crate::class::CharClass;
+
+use std::collections::BTreeMap;
+
+use automata::nfa::Transition;
+use automata::{DFA, NFA};
+
+impl Expr {
+    /// Build an expression that matches the strings accepted by the DFA, by state elimination.
+    /// See [`Expr::from_nfa`]. The DFA is used as given, so minimizing it first usually gives a
+    /// shorter expression.
+    #[inline]
+    pub fn from_dfa(dfa: &DFA<CharClass>) -> Self {
+        let adjacency = dfa.adjacency();
+        let edges = (0..dfa.total_states).flat_map(|s| {
+            adjacency
+                .outgoing(s)
+                .map(move |(class, t)| (s, Self::atom(class.clone()), t))
+                .collect::<Vec<_>>()
+        });
+        Eliminator::new(
+            dfa.total_states,
+            dfa.start_state,
+            &dfa.accepting_states,
+            edges,
+        )
+        .run()
+    }
+
+    /// Build an expression that matches the strings accepted by the NFA, by state elimination.
+    ///
+    /// The automaton is given a new start state and a new final state, joined to the old ones by
+    /// edges on the empty string, and its edges are relabeled with expressions. Each old state is
+    /// then removed in turn, replacing every path through it with a direct edge labeled by the
+    /// concatenation of the labels along the path, with the star of the state's loop in the
+    /// middle. The edge left between the new states is labeled by the result.
+    ///
+    /// The order of elimination does not change the strings matched but does change the size of
+    /// the expression, so the state that adds the least to the labels is removed first. The
+    /// labels are built with the smart constructors of [`Expr`], which keeps them simplified.
+    #[inline]
+    pub fn from_nfa(nfa: &NFA<CharClass>) -> Self {
+        let adjacency = nfa.adjacency();
+        let edges = (0..nfa.total_states).flat_map(|s| {
+            adjacency
+                .outgoing(s)
+                .map(move |(label, t)| match label {
+                    Transition::Some(class) => (s, Self::atom(class.clone()), t),
+                    Transition::Epsilon => (s, Expr::Epsilon, t),
+                })
+                .collect::<Vec<_>>()
+        });
+        Eliminator::new(
+            nfa.total_states,
+            nfa.start_state,
+            &nfa.accepting_states,
+            edges,
+        )
+        .run()
+    }
+
+    /// The number of nodes in the expression, counting each class as one.
+    fn size(&self) -> usize {
+        match self {
+            Expr::Empty | Expr::Epsilon | Expr::Atom(_) => 1,
+            Expr::Unary(_, inner) | Expr::Group(_, inner) => 1 + inner.size(),
+            Expr::Binary(_, lhs, rhs) => 1 + lhs.size() + rhs.size(),
+        }
+    }
+}
+
+/// The edges joining a state to its neighbours, with the neighbour and the label of each.
+type Neighbours<'a> = Vec<(usize, &'a Expr)>;
+
+/// A generalized NFA, whose edges are labeled by expressions. There is at most one edge between
+/// any two states.
+struct Eliminator {
+    /// The states left to eliminate.
+    states: Vec<usize>,
+    start: usize,
+    end: usize,
+    edges: BTreeMap<(usize, usize), Expr>,
+}
+
+impl Eliminator {
+    fn new<'a, I, A>(total_states: usize, start_state: usize, accepting_states: A, edges: I) -> Self
+    where
+        I: IntoIterator<Item = (usize, Expr, usize)>,
+        A: IntoIterator<Item = &'a usize>,
+    {
+        let start = total_states;
+        let end = total_states + 1;
+        let mut eliminator = Self {
+            states: (0..total_states).collect(),
+            start,
+            end,
+            edges: BTreeMap::new(),
+        };
+
+        eliminator.add_edge(start, start_state, Expr::Epsilon);
+        for &s in accepting_states {
+            eliminator.add_edge(s, end, Expr::Epsilon);
+        }
+        for (s, e, t) in edges {
+            eliminator.add_edge(s, t, e);
+        }
+        eliminator
+    }
+
+    /// Add an edge, as an alternative to any edge already between the states.
+    fn add_edge(&mut self, start: usize, end: usize, label: Expr) {
+        if label == Expr::Empty {
+            return;
+        }
+        let label = match self.edges.remove(&(start, end)) {
+            Some(e) => union(e, label),
+            None => label,
+        };
+        self.edges.insert((start, end), label);
+    }
+
+    fn run(mut self) -> Expr {
+        while !self.states.is_empty() {
+            let (i, _) = self
+                .states
+                .iter()
+                .enumerate()
+                .min_by_key(|&(_, &s)| self.weight(s))
+                .unwrap();
+            let state = self.states.swap_remove(i);
+            self.eliminate(state);
+        }
+
+        self.edges
+            .remove(&(self.start, self.end))
+            .unwrap_or(Expr::Empty)
+    }
+
+    /// The incoming and outgoing edges of a state, other than its loop.
+    fn neighbours(&self, state: usize) -> (Neighbours<'_>, Neighbours<'_>) {
+        let mut incoming = Vec::new();
+        let mut outgoing = Vec::new();
+        for (&(s, t), e) in &self.edges {
+            if s == state && t != state {
+                outgoing.push((t, e));
+            } else if t == state && s != state {
+                incoming.push((s, e));
+            }
+        }
+        (incoming, outgoing)
+    }
+
+    /// Estimate how much eliminating a state adds to the labels: each incoming label is copied
+    /// once per outgoing edge, each outgoing label once per incoming edge, and the loop once per
+    /// pair.
+    fn weight(&self, state: usize) -> usize {
+        let (incoming, outgoing) = self.neighbours(state);
+        let size = |edges: &Neighbours<'_>| edges.iter().map(|(_, e)| e.size()).sum::<usize>();
+        let paths = incoming.len() * outgoing.len();
+        let looped = self.edges.get(&(state, state)).map_or(0, Expr::size);
+
+        size(&incoming) * outgoing.len() + size(&outgoing) * incoming.len() + looped * paths
+    }
+
+    fn eliminate(&mut self, state: usize) {
+        let looped = match self.edges.remove(&(state, state)) {
+            Some(e) => Expr::star(e),
+            None => Expr::Epsilon,
+        };
+
+        let (incoming, outgoing) = self.neighbours(state);
+        let incoming: Vec<_> = incoming.into_iter().map(|(s, e)| (s, e.clone())).collect();
+        let outgoing: Vec<_> = outgoing.into_iter().map(|(t, e)| (t, e.clone())).collect();
+        for &(s, _) in &incoming {
+            self.edges.remove(&(s, state));
+        }
+        for &(t, _) in &outgoing {
+            self.edges.remove(&(state, t));
+        }
+
+        for (s, into) in &incoming {
+            for (t, out) in &outgoing {
+                let path =
+                    Expr::concat(into.clone(), Self::concat_loop(looped.clone(), out.clone()));
+                self.add_edge(*s, *t, path);
+            }
+        }
+    }
+
+    /// Concatenate a loop with the label that leaves it. A loop `a*` followed by `a` is written
+    /// `aa*` instead, which prints as `a+`.
+    fn concat_loop(looped: Expr, out: Expr) -> Expr {
+        match looped {
+            Expr::Unary(UnaryOp::Star, ref inner) if **inner == out => Expr::concat(out, looped),
+            _ => Expr::concat(looped, out),
+        }
+    }
+}
+
+/// Build the alternation of two expressions, factoring out common prefixes and suffixes of the
+/// alternatives, so that `ab|ac` becomes `a(b|c)` and `b+a|a` becomes `b*a`.
+fn union(lhs: Expr, rhs: Expr) -> Expr {
+    let mut operands: Vec<Expr> = lhs.operands(BinaryOp::Alternate).collect();
+    for e in rhs.operands(BinaryOp::Alternate) {
+        insert(&mut operands, e);
+    }
+
+    // The empty string is redundant beside another nullable alternative, and `()|xx*` is `x*`.
+    if operands.contains(&Expr::Epsilon) {
+        for e in operands.iter_mut() {
+            if let Some(inner) = plus_operand(e) {
+                *e = Expr::star(inner);
+            }
+        }
+        if operands
+            .iter()
+            .any(|e| *e != Expr::Epsilon && e.is_nullable())
+        {
+            operands.retain(|e| *e != Expr::Epsilon);
+        }
+    }
+
+    operands.into_iter().fold(Expr::Empty, Expr::alternate)
+}
+
+/// Add an alternative, factoring it with the first alternative that shares a prefix or suffix.
+fn insert(operands: &mut Vec<Expr>, e: Expr) {
+    for i in 0..operands.len() {
+        if let Some(factored) = factor(&operands[i], &e) {
+            operands.swap_remove(i);
+            for e in factored.operands(BinaryOp::Alternate) {
+                insert(operands, e);
+            }
+            return;
+        }
+    }
+    operands.push(e);
+}
+
+/// Factor the alternation of two expressions that start or end with the same expression.
+fn factor(lhs: &Expr, rhs: &Expr) -> Option<Expr> {
+    if lhs == rhs {
+        return Some(lhs.clone());
+    }
+
+    let (lhs_first, lhs_rest) = split_first(lhs)?;
+    let (rhs_first, rhs_rest) = split_first(rhs)?;
+    if lhs_first == rhs_first {
+        return Some(Expr::concat(lhs_first, union(lhs_rest, rhs_rest)));
+    }
+
+    let (lhs_rest, lhs_last) = split_last(lhs)?;
+    let (rhs_rest, rhs_last) = split_last(rhs)?;
+    if lhs_last == rhs_last {
+        return Some(Expr::concat(union(lhs_rest, rhs_rest), lhs_last));
+    }
+    None
+}
+
+/// Split an expression into its first factor and the rest, unless it is the empty string.
+fn split_first(e: &Expr) -> Option<(Expr, Expr)> {
+    match e {
+        Expr::Epsilon => None,
+        Expr::Binary(BinaryOp::Concat, first, rest) => Some((*first.clone(), *rest.clone())),
+        e => Some((e.clone(), Expr::Epsilon)),
+    }
+}
+
+/// Split an expression into its last factor and the rest, unless it is the empty string.
+fn split_last(e: &Expr) -> Option<(Expr, Expr)> {
+    if *e == Expr::Epsilon {
+        return None;
+    }
+    let mut factors: Vec<Expr> = e.clone().operands(BinaryOp::Concat).collect();
+    let last = factors.pop()?;
+    let rest = factors
+        .into_iter()
+        .rev()
+        .fold(Expr::Epsilon, |rest, e| Expr::concat(e, rest));
+    Some((rest, last))
+}
+
+/// If the expression is `xx*`, return `x`.
+fn plus_operand(e: &Expr) -> Option<Expr> {
+    let (rest, last) = split_last(e)?;
+    match last {
+        Expr::Unary(UnaryOp::Star, inner) if *inner == rest => Some(*inner),
+        _ => None,
+    }
+}
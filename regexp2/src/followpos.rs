@@ -0,0 +1,113 @@
+use crate::ast::Expr;
+use crate::class::CharClass;
+use crate::regexp::{FollowposError, UnsupportedOperator};
+
+use std::collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap, VecDeque};
+
+use automata::convert::Disjoin;
+use automata::limit::Limits;
+use automata::DFA;
+
+impl Expr {
+    /// Build a DFA directly from the expression, without an NFA, using Algorithm 3.36 of the
+    /// Dragon Book. The expression is augmented with an end marker that follows every string it
+    /// matches, and each DFA state is a set of positions that may be matched next; a state is
+    /// accepting if it contains the end marker. A state moves on a character to the union of
+    /// `followpos` of its positions whose classes contain the character.
+    ///
+    /// As in the subset construction, the classes of the positions in each state are disjoined
+    /// first, so the DFA moves on disjoint sets of characters. The DFA is not minimized.
+    ///
+    /// Fails if the expression contains a complement or an intersection, which have no positions.
+    /// The parser does not produce them.
+    #[inline]
+    pub fn followpos_dfa(&self) -> Result<DFA<CharClass>, UnsupportedOperator> {
+        match self.followpos_dfa_with_limits(Limits::none()) {
+            Ok(dfa) => Ok(dfa),
+            Err(FollowposError::Unsupported(err)) => Err(err),
+            Err(FollowposError::SizeLimitExceeded(_)) => {
+                unreachable!("there are no limits to exceed")
+            }
+        }
+    }
+
+    /// Build a DFA directly from the expression, as [`Expr::followpos_dfa`] does, but fail as
+    /// soon as the DFA has more states than the limit.
+    #[inline]
+    pub fn followpos_dfa_with_limits(
+        &self,
+        limits: Limits,
+    ) -> Result<DFA<CharClass>, FollowposError> {
+        let mut classes = Vec::new();
+        let mut followpos: HashMap<usize, BTreeSet<usize>> = HashMap::new();
+        let root = self.positions(&mut classes, &mut |last, first| {
+            for &l in last {
+                followpos.entry(l).or_default().extend(first);
+            }
+        })?;
+
+        // The end marker is the position after every other position.
+        let end = classes.len();
+        for &l in &root.last {
+            followpos.entry(l).or_default().insert(end);
+        }
+
+        let mut start: BTreeSet<usize> = root.first.into_iter().collect();
+        if root.nullable {
+            start.insert(end);
+        }
+
+        let mut dfa = DFA::new();
+        let mut labels = HashMap::new();
+        let mut queue = VecDeque::new();
+        let mut transitions = Vec::new();
+
+        let start: Vec<usize> = start.into_iter().collect();
+        labels.insert(start.clone(), dfa.start_state);
+        queue.push_back(start);
+        while let Some(state) = queue.pop_front() {
+            let label = labels[&state];
+
+            // The end marker sorts last and has no class.
+            let positions = match state.last() {
+                Some(&p) if p == end => {
+                    dfa.accepting_states.insert(label);
+                    &state[..state.len() - 1]
+                }
+                _ => &state[..],
+            };
+
+            let symbols = CharClass::disjoin(positions.iter().map(|&p| &classes[p]).collect());
+            let mut edges: BTreeMap<usize, CharClass> = BTreeMap::new();
+            for symbol in symbols {
+                let next: BTreeSet<usize> = positions
+                    .iter()
+                    .filter(|&&p| Disjoin::contains(&classes[p], &symbol))
+                    .flat_map(|p| followpos.get(p).into_iter().flatten().cloned())
+                    .collect();
+                let next: Vec<usize> = next.into_iter().collect();
+
+                let next_label = match labels.entry(next) {
+                    Entry::Occupied(e) => *e.get(),
+                    Entry::Vacant(e) => {
+                        let l = dfa.add_state(false);
+                        limits.check_dfa_states(dfa.total_states)?;
+                        queue.push_back(e.key().clone());
+                        e.insert(l);
+                        l
+                    }
+                };
+                edges.entry(next_label).or_default().add_other(symbol);
+            }
+
+            transitions.extend(
+                edges
+                    .into_iter()
+                    .map(|(next_label, class)| (label, class, next_label)),
+            );
+        }
+
+        dfa.add_transitions(transitions);
+        Ok(dfa)
+    }
+}
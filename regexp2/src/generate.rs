@@ -0,0 +1,299 @@
+use crate::class::{CharClass, CharRange};
+
+use std::cell::RefCell;
+use std::cmp;
+
+use automata::DFA;
+
+/// A source of random numbers for [`Generator::sample`]. Implement it to plug in any random
+/// number generator; [`SplitMix64`] is a small, seedable default.
+pub trait Rng {
+    /// Return the next random 64-bit value. Every value should be equally likely.
+    fn next_u64(&mut self) -> u64;
+
+    /// Return a random value in `0..n`, with every value equally likely. `n` must not be zero.
+    #[inline]
+    fn below(&mut self, n: u128) -> u128 {
+        // Reject values from the incomplete last multiple of n to avoid modulo bias.
+        let limit = u128::MAX - u128::MAX % n;
+        loop {
+            let r = ((self.next_u64() as u128) << 64) | self.next_u64() as u128;
+            if r < limit {
+                return r % n;
+            }
+        }
+    }
+}
+
+/// The SplitMix64 generator. It is fast and statistically sound for generating test data, but it
+/// is not cryptographically secure.
+#[derive(Clone, Debug)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    #[inline]
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+}
+
+impl Rng for SplitMix64 {
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// Produces the strings accepted by a DFA: it enumerates them in shortlex order, counts them by
+/// length and samples them uniformly at random.
+///
+/// The transitions out of each state of the DFA must be disjoint, as they are in DFAs built by
+/// this crate; otherwise strings are produced and counted more than once. Counts are computed
+/// with saturating arithmetic, so they are capped at `u128::MAX`.
+#[derive(Debug)]
+pub struct Generator {
+    start_state: usize,
+    /// The outgoing edges of each state, one per character range, sorted by the start of the
+    /// range.
+    edges: Vec<Vec<Edge>>,
+    accepting: Vec<bool>,
+    /// The number of strings of length `n` accepted from each state is `counts[n][state]`,
+    /// computed on demand.
+    counts: RefCell<Vec<Vec<u128>>>,
+}
+
+#[derive(Debug)]
+struct Edge {
+    range: CharRange,
+    size: u128,
+    target: usize,
+}
+
+impl Generator {
+    #[inline]
+    pub fn new(dfa: &DFA<CharClass>) -> Self {
+        let adjacency = dfa.adjacency();
+        let edges = (0..dfa.total_states)
+            .map(|s| {
+                let mut edges: Vec<_> = adjacency
+                    .outgoing(s)
+                    .flat_map(|(class, target)| {
+                        class.iter().map(move |range| Edge {
+                            range: range.clone(),
+                            size: range.size() as u128,
+                            target,
+                        })
+                    })
+                    .collect();
+                edges.sort_by_key(|e| e.range.start);
+                edges
+            })
+            .collect();
+        let accepting = (0..dfa.total_states)
+            .map(|s| dfa.is_accepting_state(&s))
+            .collect();
+
+        Self {
+            start_state: dfa.start_state,
+            edges,
+            accepting,
+            counts: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// The number of accepted strings of the given length, in characters. The count saturates at
+    /// `u128::MAX`.
+    #[inline]
+    pub fn count(&self, len: usize) -> u128 {
+        self.count_from(self.start_state, len)
+    }
+
+    /// Choose an accepted string of the given length, in characters, uniformly at random. Returns
+    /// None if no string of that length is accepted. If the number of such strings saturates the
+    /// count, the choice is only approximately uniform.
+    #[inline]
+    pub fn sample<R: Rng>(&self, len: usize, rng: &mut R) -> Option<String> {
+        if self.count(len) == 0 {
+            return None;
+        }
+
+        let mut string = String::with_capacity(len);
+        let mut state = self.start_state;
+        for remaining in (1..=len).rev() {
+            // Choose a string from this state, then find the edge and character it begins with.
+            let mut r = rng.below(self.count_from(state, remaining));
+            for edge in &self.edges[state] {
+                let rest = self.count_from(edge.target, remaining - 1);
+                let weight = edge.size.saturating_mul(rest);
+                if r < weight {
+                    let n = cmp::min(r / rest, edge.size - 1);
+                    string.extend(edge.range.nth(n as u32));
+                    state = edge.target;
+                    break;
+                }
+                r -= weight;
+            }
+        }
+
+        Some(string)
+    }
+
+    /// Iterate over the accepted strings in shortlex order: shorter strings first, and strings of
+    /// the same length in lexicographic order of their characters. The iterator ends only if the
+    /// language is finite.
+    #[inline]
+    pub fn strings(&self) -> Strings<'_> {
+        Strings {
+            generator: self,
+            len: 0,
+            stack: Vec::new(),
+            prefix: String::new(),
+        }
+    }
+
+    /// The number of strings of length `len` accepted from the given state.
+    #[inline]
+    fn count_from(&self, state: usize, len: usize) -> u128 {
+        let mut counts = self.counts.borrow_mut();
+        while counts.len() <= len {
+            let row = match counts.last() {
+                None => self.accepting.iter().map(|&a| a as u128).collect(),
+                Some(last) => self
+                    .edges
+                    .iter()
+                    .map(|edges| {
+                        edges.iter().fold(0u128, |sum, e| {
+                            sum.saturating_add(e.size.saturating_mul(last[e.target]))
+                        })
+                    })
+                    .collect(),
+            };
+            counts.push(row);
+        }
+        counts[len][state]
+    }
+}
+
+/// An iterator over the strings accepted by a DFA, in shortlex order. See
+/// [`Generator::strings`].
+#[derive(Debug)]
+pub struct Strings<'a> {
+    generator: &'a Generator,
+    /// One more than the length of the strings being enumerated.
+    len: usize,
+    /// The states along the current prefix, with the next character to try from each.
+    stack: Vec<Frame>,
+    prefix: String,
+}
+
+#[derive(Debug)]
+struct Frame {
+    state: usize,
+    /// The index of the next edge to try.
+    edge: usize,
+    /// The smallest character that may be tried next.
+    next: u32,
+}
+
+impl<'a> Strings<'a> {
+    /// Start enumerating the strings of the next length that has any. Returns false if no longer
+    /// strings are accepted.
+    #[inline]
+    fn next_len(&mut self) -> bool {
+        let generator = self.generator;
+        loop {
+            let len = self.len;
+            self.len += 1;
+
+            if generator.count(len) > 0 {
+                self.stack.push(Frame {
+                    state: generator.start_state,
+                    edge: 0,
+                    next: 0,
+                });
+                return true;
+            }
+
+            // An accepted string of length at least `len + total_states` repeats a state after
+            // its first `len` characters, and removing the cycle leaves a shorter accepted string
+            // that is still longer than `len`. So if none of the next `total_states` lengths has
+            // a string, no greater length does either.
+            let total_states = generator.edges.len();
+            if (self.len..self.len + total_states).all(|n| generator.count(n) == 0) {
+                return false;
+            }
+        }
+    }
+
+    /// Backtrack from the last character of the prefix.
+    #[inline]
+    fn pop(&mut self) {
+        self.stack.pop();
+        if !self.stack.is_empty() {
+            self.prefix.pop();
+        }
+    }
+}
+
+impl<'a> Iterator for Strings<'a> {
+    type Item = String;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let generator = self.generator;
+        loop {
+            let depth = match self.stack.len() {
+                0 if !self.next_len() => return None,
+                0 => continue,
+                n => n - 1,
+            };
+            let remaining = self.len - 1 - depth;
+
+            if remaining == 0 {
+                let string = self.prefix.clone();
+                self.pop();
+                return Some(string);
+            }
+
+            let frame = self.stack.last_mut().unwrap();
+            let edges = &generator.edges[frame.state];
+            let mut step = None;
+            while let Some(edge) = edges.get(frame.edge) {
+                let start = cmp::max(frame.next, edge.range.start as u32);
+                if start > edge.range.end as u32
+                    || generator.count_from(edge.target, remaining - 1) == 0
+                {
+                    frame.edge += 1;
+                    continue;
+                }
+
+                // Ranges never start inside the surrogates, and `next` skips over them.
+                let c = char::from_u32(start).unwrap();
+                frame.next = match start + 1 {
+                    0xd800 => 0xe000,
+                    next => next,
+                };
+                step = Some((c, edge.target));
+                break;
+            }
+
+            match step {
+                Some((c, target)) => {
+                    self.prefix.push(c);
+                    self.stack.push(Frame {
+                        state: target,
+                        edge: 0,
+                        next: 0,
+                    });
+                }
+                None => self.pop(),
+            }
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use crate::ast::{BinaryOp, Expr, UnaryOp};
+use crate::class::CharClass;
+use crate::regexp::{GlushkovError, UnsupportedOperator};
+
+use std::mem;
+
+use automata::limit::{Limits, SizeLimitExceeded};
+use automata::nfa::Transition;
+use automata::NFA;
+
+/// The positions of an expression that may begin and end the strings it matches. A position is
+/// an occurrence of an atom in the expression.
+pub(crate) struct Positions {
+    pub nullable: bool,
+    pub first: Vec<usize>,
+    pub last: Vec<usize>,
+}
+
+impl Expr {
+    /// Build an NFA without epsilon transitions using Glushkov's construction. Each atom of the
+    /// expression is a position with a state of its own, entered only on the atom's characters;
+    /// a transition from one position to another exists when the second may follow the first in
+    /// a matched string. With the start state, the NFA has one more state than the expression has
+    /// atoms.
+    ///
+    /// Fails if the expression contains a complement or an intersection, which have no positions.
+    /// The parser does not produce them.
+    #[inline]
+    pub fn glushkov_nfa(&self) -> Result<NFA<CharClass>, UnsupportedOperator> {
+        match self.glushkov_nfa_with_limits(Limits::none()) {
+            Ok(nfa) => Ok(nfa),
+            Err(GlushkovError::Unsupported(err)) => Err(err),
+            Err(GlushkovError::SizeLimitExceeded(_)) => {
+                unreachable!("there are no limits to exceed")
+            }
+        }
+    }
+
+    /// Build an NFA using Glushkov's construction, as [`Expr::glushkov_nfa`] does, but fail if
+    /// the NFA has more states than the limit or its positions and transitions take more memory.
+    /// The transitions are counted before they are collected, so a limit exceeded by an
+    /// expression with quadratically many of them is found without building them.
+    #[inline]
+    pub fn glushkov_nfa_with_limits(
+        &self,
+        limits: Limits,
+    ) -> Result<NFA<CharClass>, GlushkovError> {
+        let transition_bytes = mem::size_of::<(usize, Transition<CharClass>, usize)>();
+        let mut memory = 0;
+        let mut exceeded: Option<SizeLimitExceeded> = None;
+
+        // The start state takes the place of position 0.
+        let mut classes = vec![CharClass::new()];
+        let mut follows = Vec::new();
+        let positions = self.positions(&mut classes, &mut |last, first| {
+            if exceeded.is_some() {
+                return;
+            }
+            memory += last.len() * first.len() * transition_bytes;
+            if let Err(err) = limits.check_memory(memory) {
+                exceeded = Some(err);
+                return;
+            }
+            follows.extend(
+                last.iter()
+                    .flat_map(|&l| first.iter().map(move |&f| (l, f))),
+            );
+        })?;
+        if let Some(err) = exceeded {
+            return Err(err.into());
+        }
+
+        limits.check_nfa_states(classes.len())?;
+        memory +=
+            classes.len() * mem::size_of::<CharClass>() + positions.first.len() * transition_bytes;
+        limits.check_memory(memory)?;
+
+        let mut nfa = NFA::new();
+        for _ in 1..classes.len() {
+            nfa.add_state(false);
+        }
+        let start = nfa.start_state;
+        let transitions = positions
+            .first
+            .iter()
+            .map(|&p| (start, p))
+            .chain(follows)
+            .map(|(l, f)| (l, Transition::Some(classes[f].clone()), f));
+        nfa.add_transitions(transitions);
+        nfa.accepting_states.extend(positions.last);
+        if positions.nullable {
+            nfa.accepting_states.insert(nfa.start_state);
+        }
+
+        Ok(nfa)
+    }
+
+    /// Number the atoms of the expression as positions, pushing the class of each onto
+    /// `classes`, and compute which positions begin and end its strings. `follow` is called with
+    /// pairs of position sets where every position in the first may be followed by every
+    /// position in the second. Fails if the expression contains a complement or an intersection.
+    #[inline]
+    pub(crate) fn positions<F>(
+        &self,
+        classes: &mut Vec<CharClass>,
+        follow: &mut F,
+    ) -> Result<Positions, UnsupportedOperator>
+    where
+        F: FnMut(&[usize], &[usize]),
+    {
+        Ok(match self {
+            Expr::Empty => Positions {
+                nullable: false,
+                first: Vec::new(),
+                last: Vec::new(),
+            },
+            Expr::Epsilon => Positions {
+                nullable: true,
+                first: Vec::new(),
+                last: Vec::new(),
+            },
+            Expr::Atom(class) => {
+                let p = classes.len();
+                classes.push(class.clone());
+                Positions {
+                    nullable: false,
+                    first: vec![p],
+                    last: vec![p],
+                }
+            }
+            Expr::Group(_, inner) => inner.positions(classes, follow)?,
+            Expr::Unary(op, inner) => {
+                let mut positions = inner.positions(classes, follow)?;
+                if let UnaryOp::Star | UnaryOp::Plus = op {
+                    follow(&positions.last, &positions.first);
+                }
+                match op {
+                    UnaryOp::Star | UnaryOp::Optional => positions.nullable = true,
+                    UnaryOp::Plus => {}
+                    UnaryOp::Complement => return Err(UnsupportedOperator::Complement),
+                }
+                positions
+            }
+            Expr::Binary(BinaryOp::Concat, lhs, rhs) => {
+                let mut lhs = lhs.positions(classes, follow)?;
+                let mut rhs = rhs.positions(classes, follow)?;
+                follow(&lhs.last, &rhs.first);
+
+                if lhs.nullable {
+                    lhs.first.extend(&rhs.first);
+                }
+                if rhs.nullable {
+                    rhs.last.extend(&lhs.last);
+                }
+                Positions {
+                    nullable: lhs.nullable && rhs.nullable,
+                    first: lhs.first,
+                    last: rhs.last,
+                }
+            }
+            Expr::Binary(BinaryOp::Alternate, lhs, rhs) => {
+                let mut lhs = lhs.positions(classes, follow)?;
+                let rhs = rhs.positions(classes, follow)?;
+                lhs.nullable |= rhs.nullable;
+                lhs.first.extend(rhs.first);
+                lhs.last.extend(rhs.last);
+                lhs
+            }
+            Expr::Binary(BinaryOp::Intersect, _, _) => return Err(UnsupportedOperator::Intersect),
+        })
+    }
+}
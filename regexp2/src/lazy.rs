@@ -0,0 +1,380 @@
+use crate::class::CharClass;
+use crate::dense::CharClasses;
+use crate::regexp::{Aborted, Budget, Engine, Match};
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::mem;
+
+use automata::nfa::{EpsilonClosures, Transition};
+use automata::NFA;
+
+/// The default memory budget of the state cache, in bytes.
+pub const DEFAULT_CACHE_CAPACITY: usize = 1 << 21;
+
+/// The number of times the cache may be cleared during one search before it is considered to be
+/// thrashing.
+const MIN_CLEARS: usize = 3;
+/// When the cache is thrashing, the minimum number of characters that must be consumed per
+/// cached state to keep using the cache.
+const MIN_CHARS_PER_STATE: usize = 10;
+
+/// Marks a transition that has not been computed yet.
+const UNKNOWN: u32 = u32::MAX;
+/// Marks a transition to the empty set of NFA states.
+const DEAD: u32 = u32::MAX - 1;
+
+/// Approximate bookkeeping cost of a cached state, on top of its NFA states and transitions.
+const STATE_OVERHEAD: usize = 4 * mem::size_of::<usize>();
+
+/// A DFA that is constructed lazily from an NFA while searching. Each DFA state is a set of NFA
+/// states, determinized on demand and cached up to a memory budget. When the budget is exceeded,
+/// the cache is cleared; if it is cleared so often that it no longer pays for itself, the search
+/// falls back to simulating the NFA directly.
+#[derive(Debug)]
+pub struct LazyDFA {
+    nfa: NFA<CharClass>,
+    closures: EpsilonClosures,
+    classes: CharClasses,
+    /// A character from each alphabet equivalence class, used to compute transitions.
+    representatives: Vec<Option<char>>,
+    /// The memory budget of the cache, in bytes.
+    capacity: usize,
+    cache: RefCell<Cache>,
+}
+
+#[derive(Debug, Default)]
+struct Cache {
+    /// The sorted NFA states in each DFA state.
+    states: Vec<Vec<usize>>,
+    map: HashMap<Vec<usize>, u32>,
+    /// The flat transition table, indexed by `state * classes + class`.
+    transitions: Vec<u32>,
+    accepting: Vec<bool>,
+    /// Approximate memory used by the cached states, in bytes.
+    memory: usize,
+    /// The number of times the cache has been cleared.
+    clears: usize,
+}
+
+/// Bookkeeping for a single search.
+struct SearchState {
+    clears: usize,
+    chars_since_clear: usize,
+}
+
+/// The current state of a search: either a cached DFA state, or a set of NFA states when the
+/// search has fallen back to NFA simulation.
+enum Current {
+    Cached(u32),
+    Uncached(Vec<usize>),
+}
+
+impl From<NFA<CharClass>> for LazyDFA {
+    #[inline]
+    fn from(nfa: NFA<CharClass>) -> Self {
+        Self::new(nfa)
+    }
+}
+
+impl LazyDFA {
+    /// Create a lazy DFA with the default cache capacity.
+    #[inline]
+    pub fn new(nfa: NFA<CharClass>) -> Self {
+        Self::with_cache_capacity(nfa, DEFAULT_CACHE_CAPACITY)
+    }
+
+    /// Create a lazy DFA whose cache uses at most approximately `capacity` bytes.
+    #[inline]
+    pub fn with_cache_capacity(nfa: NFA<CharClass>, capacity: usize) -> Self {
+        let labels: Vec<&CharClass> = nfa
+            .adjacency()
+            .into_iter()
+            .filter_map(|(_, t, _)| match t {
+                Transition::Some(cc) => Some(cc),
+                Transition::Epsilon => None,
+            })
+            .collect();
+        let classes = CharClasses::new(&labels);
+        let representatives = (0..classes.len())
+            .map(|class| classes.representative(class as u32))
+            .collect();
+
+        Self {
+            closures: nfa.epsilon_closures(),
+            nfa,
+            classes,
+            representatives,
+            capacity,
+            cache: RefCell::new(Cache::default()),
+        }
+    }
+
+    /// The number of DFA states currently cached.
+    #[inline]
+    pub fn cached_states(&self) -> usize {
+        self.cache.borrow().states.len()
+    }
+
+    /// The total number of times the cache has been cleared.
+    #[inline]
+    pub fn cache_clears(&self) -> usize {
+        self.cache.borrow().clears
+    }
+
+    /// Determine if the given input is accepted by the automaton.
+    #[inline]
+    pub fn is_match(&self, input: &str) -> bool {
+        self.is_match_impl(input, None)
+            .unwrap_or_else(|_| unreachable!("there is no budget to run out"))
+    }
+
+    #[inline]
+    fn is_match_impl(&self, input: &str, budget: Option<&Budget<'_>>) -> Result<bool, Aborted> {
+        let mut is_final = false;
+        let consumed = self.run(input.chars(), budget, |accepting| {
+            is_final = accepting;
+            true
+        })?;
+        Ok(consumed && is_final)
+    }
+
+    #[inline]
+    fn find_at_impl(
+        &self,
+        input: &str,
+        start: usize,
+        shortest: bool,
+        budget: Option<&Budget<'_>>,
+    ) -> Result<Option<Match>, Aborted> {
+        let mut last_match = None;
+        let mut end = start;
+        self.run(input.chars().skip(start), budget, |accepting| {
+            if accepting {
+                last_match = Some(end);
+            }
+            end += 1;
+            !(accepting && shortest)
+        })?;
+
+        Ok(last_match.map(|end| {
+            let span = input.chars().skip(start).take(end - start).collect();
+            Match::new(start, end, span)
+        }))
+    }
+
+    /// Run the automaton over the input. `visit` is called with whether the current state is
+    /// accepting for the start state and after each consumed character. The run stops when
+    /// `visit` returns false, when there is no transition on the next character, or at the end of
+    /// input. Returns true if all input was consumed. Each character read takes a step from the
+    /// budget, if any.
+    #[inline]
+    fn run<I, F>(
+        &self,
+        mut input: I,
+        budget: Option<&Budget<'_>>,
+        mut visit: F,
+    ) -> Result<bool, Aborted>
+    where
+        I: Iterator<Item = char>,
+        F: FnMut(bool) -> bool,
+    {
+        if let Some(budget) = budget {
+            budget.check()?;
+        }
+
+        let mut cache = self.cache.borrow_mut();
+        let mut search = SearchState {
+            clears: 0,
+            chars_since_clear: 0,
+        };
+
+        let start = self.closure(vec![self.nfa.start_state]);
+        let mut current = self.intern(&mut cache, &mut search, start);
+
+        loop {
+            let accepting = match &current {
+                Current::Cached(id) => cache.accepting[*id as usize],
+                Current::Uncached(set) => self.is_accepting(set),
+            };
+            if !visit(accepting) {
+                return Ok(false);
+            }
+
+            let c = match input.next() {
+                Some(c) => c,
+                None => return Ok(true),
+            };
+            if let Some(budget) = budget {
+                budget.step()?;
+            }
+            let class = self.classes.get(c) as usize;
+            search.chars_since_clear += 1;
+
+            current = match current {
+                Current::Cached(id) => {
+                    let index = id as usize * self.classes.len() + class;
+                    match cache.transitions[index] {
+                        DEAD => return Ok(false),
+                        UNKNOWN => {
+                            let next = self.step(&cache.states[id as usize], class);
+                            if next.is_empty() {
+                                cache.transitions[index] = DEAD;
+                                return Ok(false);
+                            }
+
+                            let clears = cache.clears;
+                            let next = self.intern(&mut cache, &mut search, next);
+                            // The current state is only valid if the cache was not cleared.
+                            if let Current::Cached(next_id) = next {
+                                if cache.clears == clears {
+                                    cache.transitions[index] = next_id;
+                                }
+                            }
+                            next
+                        }
+                        next_id => Current::Cached(next_id),
+                    }
+                }
+                Current::Uncached(set) => {
+                    let next = self.step(&set, class);
+                    if next.is_empty() {
+                        return Ok(false);
+                    }
+                    Current::Uncached(next)
+                }
+            };
+        }
+    }
+
+    /// Look up or add the DFA state for the given set of NFA states. If the cache is full, it is
+    /// cleared; if the cache is thrashing or the state does not fit at all, the state is not
+    /// cached.
+    #[inline]
+    fn intern(&self, cache: &mut Cache, search: &mut SearchState, set: Vec<usize>) -> Current {
+        if let Some(&id) = cache.map.get(&set) {
+            return Current::Cached(id);
+        }
+
+        let cost = self.state_cost(&set);
+        if cache.memory + cost > self.capacity {
+            let thrashing = search.clears >= MIN_CLEARS
+                && search.chars_since_clear < MIN_CHARS_PER_STATE * cache.states.len();
+            if thrashing || cost > self.capacity {
+                return Current::Uncached(set);
+            }
+
+            cache.clear();
+            search.clears += 1;
+            search.chars_since_clear = 0;
+        }
+
+        let id: u32 = cache.states.len().try_into().unwrap();
+        cache.accepting.push(self.is_accepting(&set));
+        let len = cache.transitions.len() + self.classes.len();
+        cache.transitions.resize(len, UNKNOWN);
+        cache.map.insert(set.clone(), id);
+        cache.states.push(set);
+        cache.memory += cost;
+
+        Current::Cached(id)
+    }
+
+    #[inline]
+    fn state_cost(&self, set: &[usize]) -> usize {
+        2 * set.len() * mem::size_of::<usize>()
+            + self.classes.len() * mem::size_of::<u32>()
+            + STATE_OVERHEAD
+    }
+
+    #[inline]
+    fn is_accepting(&self, set: &[usize]) -> bool {
+        set.iter().any(|s| self.nfa.is_accepting_state(s))
+    }
+
+    /// Compute the sorted epsilon-closure of the given NFA states.
+    #[inline]
+    fn closure(&self, set: Vec<usize>) -> Vec<usize> {
+        let mut closure: Vec<usize> = self.closures.closure_set(set).into_iter().collect();
+        closure.sort_unstable();
+        closure
+    }
+
+    /// Compute the set of NFA states reachable from the given states on any character in the
+    /// given alphabet class.
+    #[inline]
+    fn step(&self, set: &[usize], class: usize) -> Vec<usize> {
+        let c = match self.representatives[class] {
+            Some(c) => c,
+            None => return Vec::new(),
+        };
+
+        let moved = set
+            .iter()
+            .flat_map(|&s| self.nfa.adjacency().outgoing(s))
+            .filter(|(t, _)| match t {
+                Transition::Some(cc) => cc.contains(c),
+                Transition::Epsilon => false,
+            })
+            .map(|(_, dest)| dest)
+            .collect();
+        self.closure(moved)
+    }
+}
+
+impl Cache {
+    #[inline]
+    fn clear(&mut self) {
+        self.states.clear();
+        self.map.clear();
+        self.transitions.clear();
+        self.accepting.clear();
+        self.memory = 0;
+        self.clears += 1;
+    }
+}
+
+impl Engine for LazyDFA {
+    #[inline]
+    fn is_match(&self, input: &str) -> bool {
+        LazyDFA::is_match(self, input)
+    }
+
+    #[inline]
+    fn find_shortest_at(&self, input: &str, start: usize) -> Option<Match> {
+        self.find_at_impl(input, start, true, None)
+            .unwrap_or_else(|_| unreachable!("there is no budget to run out"))
+    }
+
+    #[inline]
+    fn find_at(&self, input: &str, start: usize) -> Option<Match> {
+        self.find_at_impl(input, start, false, None)
+            .unwrap_or_else(|_| unreachable!("there is no budget to run out"))
+    }
+
+    #[inline]
+    fn is_match_within(&self, input: &str, budget: &Budget<'_>) -> Result<bool, Aborted> {
+        self.is_match_impl(input, Some(budget))
+    }
+
+    #[inline]
+    fn find_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, Aborted> {
+        self.find_at_impl(input, start, false, Some(budget))
+    }
+
+    #[inline]
+    fn find_shortest_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, Aborted> {
+        self.find_at_impl(input, start, true, Some(budget))
+    }
+}
@@ -24,12 +24,23 @@
 
 mod regexp;
 
-mod ast;
+mod eliminate;
+mod followpos;
+mod glushkov;
 mod mergeset;
 mod ranges;
 
+pub mod ast;
+pub mod backtrack;
 pub mod class;
+pub mod dense;
+pub mod derivative;
+pub mod generate;
+pub mod lazy;
 pub mod parser;
+pub mod pikevm;
+pub mod reverse;
+pub mod tagged;
 
 pub use automata;
 pub use regexp::*;
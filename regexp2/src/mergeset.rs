@@ -16,7 +16,7 @@ pub trait Value<K>: Clone {
 
 // A data structure to maintain a minimal set of disjoint elements. It is implemented using a
 // binary search tree.
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct MergeSet<K, V>
 where
     K: Key,
@@ -135,7 +135,7 @@ where
     }
 }
 
-impl<'a, K, V> IntoIterator for MergeSet<K, V>
+impl<K, V> IntoIterator for MergeSet<K, V>
 where
     K: Key,
     V: Value<K>,
@@ -1,9 +1,12 @@
 use crate::class::CharClass;
+use crate::regexp::CompileResult;
 
 use std::iter::Peekable;
 use std::marker::PhantomData;
 use std::str::CharIndices;
 
+use automata::limit::{Limits, SizeLimitExceeded};
+
 /// Alias for [`Result`] for [`ParseError`].
 pub type ParseResult<'r, T> = std::result::Result<T, ParseError<'r>>;
 
@@ -12,6 +15,7 @@ pub struct Parser<E>
 where
     E: ParserEngine,
 {
+    limits: Limits,
     _phantom: PhantomData<E>,
 }
 
@@ -22,14 +26,22 @@ where
     #[inline]
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
+        Self::with_limits(Limits::none())
+    }
+
+    /// Create a parser whose engine keeps what it builds within the limits.
+    #[inline]
+    pub fn with_limits(limits: Limits) -> Self {
         Self {
+            limits,
             _phantom: PhantomData,
         }
     }
 
+    /// Parse a regular expression, failing if it is invalid or if the engine exceeds its limits.
     #[inline]
-    pub fn parse<'r>(&self, expr: &'r str) -> ParseResult<'r, E::Output> {
-        let mut state: ParserState<E> = ParserState::new();
+    pub fn parse<'r>(&self, expr: &'r str) -> CompileResult<'r, E::Output> {
+        let mut state: ParserState<E> = ParserState::with_limits(self.limits);
         state.parse(expr)
     }
 }
@@ -40,23 +52,52 @@ where
     E: ParserEngine,
 {
     engine: E,
+    /// The number of groups opened so far.
+    groups: usize,
 }
 
 pub trait ParserEngine {
+    /// The result of parsing a whole regular expression.
     type Output;
+    /// The result of parsing a subexpression.
+    type Fragment;
 
     fn new() -> Self;
 
-    fn handle_char<C>(&mut self, c: C) -> Self::Output
+    /// Create an engine that keeps what it builds within the limits. Engines that build nothing
+    /// that the limits cover ignore them.
+    #[inline]
+    fn with_limits(_limits: Limits) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new()
+    }
+
+    /// Check that what has been built so far is within the limits. The parser checks after each
+    /// operator, so that it stops as soon as the limits are exceeded.
+    #[inline]
+    fn check_limits(&self) -> Result<(), SizeLimitExceeded> {
+        Ok(())
+    }
+
+    fn handle_char<C>(&mut self, c: C) -> Self::Fragment
     where
         C: Into<CharClass>;
-    fn handle_wildcard(&mut self) -> Self::Output;
+    fn handle_wildcard(&mut self) -> Self::Fragment;
+
+    fn handle_star(&mut self, lhs: Self::Fragment) -> Self::Fragment;
+    fn handle_plus(&mut self, lhs: Self::Fragment) -> Self::Fragment;
+    fn handle_optional(&mut self, lhs: Self::Fragment) -> Self::Fragment;
+    fn handle_concat(&mut self, lhs: Self::Fragment, rhs: Self::Fragment) -> Self::Fragment;
+    fn handle_alternate(&mut self, lhs: Self::Fragment, rhs: Self::Fragment) -> Self::Fragment;
 
-    fn handle_star(&mut self, lhs: Self::Output) -> Self::Output;
-    fn handle_plus(&mut self, lhs: Self::Output) -> Self::Output;
-    fn handle_optional(&mut self, lhs: Self::Output) -> Self::Output;
-    fn handle_concat(&mut self, lhs: Self::Output, rhs: Self::Output) -> Self::Output;
-    fn handle_alternate(&mut self, lhs: Self::Output, rhs: Self::Output) -> Self::Output;
+    /// Handle a parenthesized group. Groups are numbered from 1 in the order of their opening
+    /// parentheses.
+    fn handle_group(&mut self, index: usize, inner: Self::Fragment) -> Self::Fragment;
+
+    /// Produce the output from the fragment for the whole regular expression.
+    fn finish(&mut self, expr: Self::Fragment) -> Self::Output;
 }
 
 impl<E> ParserState<E>
@@ -68,14 +109,28 @@ where
     #[inline]
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
-        Self { engine: E::new() }
+        Self {
+            engine: E::new(),
+            groups: 0,
+        }
+    }
+
+    #[inline]
+    pub fn with_limits(limits: Limits) -> Self {
+        Self {
+            engine: E::with_limits(limits),
+            groups: 0,
+        }
     }
 
     /// Compile a regular expresion.
     #[inline]
-    pub fn parse<'r>(&mut self, expr: &'r str) -> ParseResult<'r, E::Output> {
+    pub fn parse<'r>(&mut self, expr: &'r str) -> CompileResult<'r, E::Output> {
+        self.groups = 0;
         let input = &mut ParseInput::new(expr);
-        self.parse_expr(input, 0, false)
+        let expr = self.parse_expr(input, 0, false)?;
+        self.engine.check_limits()?;
+        Ok(self.engine.finish(expr))
     }
 
     #[inline]
@@ -84,7 +139,7 @@ where
         input: &mut ParseInput<'r>,
         min_bp: u8,
         parenthesized: bool,
-    ) -> ParseResult<'r, E::Output> {
+    ) -> CompileResult<'r, E::Fragment> {
         let mut lhs = None;
         while lhs.is_none() {
             lhs = match input.peek() {
@@ -98,7 +153,8 @@ where
                             span: input.current_span(),
                             token: c,
                             expected: Self::EXPR_START_EXPECTED.into(),
-                        });
+                        }
+                        .into());
                     }
                     '[' => self.parse_class(input)?,
                     '.' => Some(self.parse_wildcard(input)?),
@@ -108,14 +164,16 @@ where
                             span: input.current_span(),
                             token: c,
                             expected: Self::EXPR_START_EXPECTED.into(),
-                        });
+                        }
+                        .into());
                     }
                     _ => Some(self.parse_single(input)?),
                 },
                 None => {
                     return Err(ParseError::EmptyExpression {
                         span: input.current_span(),
-                    })
+                    }
+                    .into())
                 }
             };
         }
@@ -167,7 +225,8 @@ where
                     let rhs = self.parse_expr(input, rbp, parenthesized)?;
                     self.engine.handle_concat(lhs, rhs)
                 }
-            }
+            };
+            self.engine.check_limits()?;
         }
 
         Ok(lhs)
@@ -198,7 +257,7 @@ where
     }
 
     #[inline]
-    fn parse_single<'r>(&mut self, input: &mut ParseInput<'r>) -> ParseResult<'r, E::Output> {
+    fn parse_single<'r>(&mut self, input: &mut ParseInput<'r>) -> ParseResult<'r, E::Fragment> {
         let c = self.parse_single_char(input)?;
         Ok(self.engine.handle_char(c))
     }
@@ -231,7 +290,7 @@ where
     }
 
     #[inline]
-    fn parse_escaped<'r>(&mut self, input: &mut ParseInput<'r>) -> ParseResult<'r, E::Output> {
+    fn parse_escaped<'r>(&mut self, input: &mut ParseInput<'r>) -> ParseResult<'r, E::Fragment> {
         let c = self.parse_escaped_class(input)?;
         Ok(self.engine.handle_char(c))
     }
@@ -275,7 +334,7 @@ where
     fn parse_single_or_escaped<'r>(
         &mut self,
         input: &mut ParseInput<'r>,
-    ) -> ParseResult<'r, E::Output> {
+    ) -> ParseResult<'r, E::Fragment> {
         match input.peek() {
             Some((_, '\\')) => self.parse_escaped(input),
             Some((_, _)) => self.parse_single(input),
@@ -290,12 +349,14 @@ where
     fn parse_group<'r>(
         &mut self,
         input: &mut ParseInput<'r>,
-    ) -> ParseResult<'r, Option<E::Output>> {
+    ) -> CompileResult<'r, Option<E::Fragment>> {
         let _lp = input.next_checked('(', || vec!['('])?;
+        self.groups += 1;
+        let index = self.groups;
 
         let expr = if !input.peek_is(')') {
             let expr = self.parse_expr(input, 0, true)?;
-            Some(expr)
+            Some(self.engine.handle_group(index, expr))
         } else {
             None
         };
@@ -309,7 +370,7 @@ where
     fn parse_class<'r>(
         &mut self,
         input: &mut ParseInput<'r>,
-    ) -> ParseResult<'r, Option<E::Output>> {
+    ) -> ParseResult<'r, Option<E::Fragment>> {
         let _lb = input.next_checked('[', || vec!['['])?;
 
         let negate = match input.peek() {
@@ -391,7 +452,7 @@ where
     }
 
     #[inline]
-    fn parse_wildcard<'r>(&mut self, input: &mut ParseInput<'r>) -> ParseResult<'r, E::Output> {
+    fn parse_wildcard<'r>(&mut self, input: &mut ParseInput<'r>) -> ParseResult<'r, E::Fragment> {
         let _ = self.parse_wildcard_char(input)?;
         Ok(self.engine.handle_wildcard())
     }
@@ -585,8 +646,10 @@ pub mod nfa {
     use crate::class::CharClass;
 
     use std::hash::Hash;
-    use std::marker::PhantomData;
+    use std::mem;
 
+    use automata::builder::{Fragment, NFABuilder};
+    use automata::limit::{Limits, SizeLimitExceeded};
     use automata::nfa::Transition;
     use automata::NFA;
 
@@ -599,7 +662,8 @@ pub mod nfa {
         T: Clone + Eq + Hash,
         Transition<T>: From<CharClass>,
     {
-        _phantom: PhantomData<T>,
+        builder: NFABuilder<T>,
+        limits: Limits,
     }
 
     impl<T> NFAParserEngine<T>
@@ -611,8 +675,15 @@ pub mod nfa {
         #[inline]
         #[allow(clippy::new_without_default)]
         pub fn new() -> Self {
+            Self::with_limits(Limits::none())
+        }
+
+        /// Create a new NFAParser that stops when the NFA has more states than the limit.
+        #[inline]
+        pub fn with_limits(limits: Limits) -> Self {
             NFAParserEngine {
-                _phantom: PhantomData,
+                builder: NFABuilder::with_limits(limits),
+                limits,
             }
         }
     }
@@ -623,6 +694,7 @@ pub mod nfa {
         Transition<T>: From<CharClass>,
     {
         type Output = NFA<T>;
+        type Fragment = Fragment<T>;
 
         #[inline]
         fn new() -> Self {
@@ -630,49 +702,63 @@ pub mod nfa {
         }
 
         #[inline]
-        fn handle_char<C>(&mut self, c: C) -> Self::Output
+        fn with_limits(limits: Limits) -> Self {
+            Self::with_limits(limits)
+        }
+
+        #[inline]
+        fn check_limits(&self) -> Result<(), SizeLimitExceeded> {
+            self.builder.check_limits()
+        }
+
+        #[inline]
+        fn handle_char<C>(&mut self, c: C) -> Self::Fragment
         where
             C: Into<CharClass>,
         {
             let class: CharClass = c.into();
-            let transition = class.into();
-
-            let mut nfa = NFA::new();
-            let f = nfa.add_state(true);
-            nfa.add_transition(nfa.start_state, f, transition);
-            nfa
+            self.builder.transition(class.into())
         }
 
         #[inline]
-        fn handle_wildcard(&mut self) -> Self::Output {
+        fn handle_wildcard(&mut self) -> Self::Fragment {
             let class = CharClass::all_but_newline();
             self.handle_char(class)
         }
 
         #[inline]
-        fn handle_star(&mut self, lhs: Self::Output) -> Self::Output {
-            NFA::kleene_star(&lhs)
+        fn handle_star(&mut self, lhs: Self::Fragment) -> Self::Fragment {
+            self.builder.kleene_star(lhs)
         }
 
         #[inline]
-        fn handle_plus(&mut self, lhs: Self::Output) -> Self::Output {
-            NFA::concatenation(&NFA::kleene_star(&lhs), &lhs)
+        fn handle_plus(&mut self, lhs: Self::Fragment) -> Self::Fragment {
+            self.builder.plus(lhs)
         }
 
         #[inline]
-        fn handle_optional(&mut self, lhs: Self::Output) -> Self::Output {
-            let c1 = NFA::new_epsilon();
-            NFA::union(&c1, &lhs)
+        fn handle_optional(&mut self, lhs: Self::Fragment) -> Self::Fragment {
+            self.builder.optional(lhs)
         }
 
         #[inline]
-        fn handle_concat(&mut self, lhs: Self::Output, rhs: Self::Output) -> Self::Output {
-            NFA::concatenation(&lhs, &rhs)
+        fn handle_concat(&mut self, lhs: Self::Fragment, rhs: Self::Fragment) -> Self::Fragment {
+            self.builder.concatenation(lhs, rhs)
         }
 
         #[inline]
-        fn handle_alternate(&mut self, lhs: Self::Output, rhs: Self::Output) -> Self::Output {
-            NFA::union(&lhs, &rhs)
+        fn handle_alternate(&mut self, lhs: Self::Fragment, rhs: Self::Fragment) -> Self::Fragment {
+            self.builder.union(lhs, rhs)
+        }
+
+        #[inline]
+        fn handle_group(&mut self, _index: usize, inner: Self::Fragment) -> Self::Fragment {
+            inner
+        }
+
+        #[inline]
+        fn finish(&mut self, expr: Self::Fragment) -> Self::Output {
+            mem::replace(&mut self.builder, NFABuilder::with_limits(self.limits)).build(expr)
         }
     }
 }
@@ -715,6 +801,7 @@ pub mod ast {
         T: Clone + Eq + Hash,
     {
         type Output = ast::Expr;
+        type Fragment = ast::Expr;
 
         #[inline]
         fn new() -> Self {
@@ -722,7 +809,7 @@ pub mod ast {
         }
 
         #[inline]
-        fn handle_char<C>(&mut self, c: C) -> Self::Output
+        fn handle_char<C>(&mut self, c: C) -> Self::Fragment
         where
             C: Into<CharClass>,
         {
@@ -731,35 +818,44 @@ pub mod ast {
         }
 
         #[inline]
-        fn handle_wildcard(&mut self) -> Self::Output {
+        fn handle_wildcard(&mut self) -> Self::Fragment {
             let class = CharClass::all_but_newline();
             self.handle_char(class)
         }
 
         #[inline]
-        fn handle_star(&mut self, lhs: Self::Output) -> Self::Output {
+        fn handle_star(&mut self, lhs: Self::Fragment) -> Self::Fragment {
             ast::Expr::Unary(ast::UnaryOp::Star, Box::new(lhs))
         }
 
         #[inline]
-        fn handle_plus(&mut self, rhs: Self::Output) -> Self::Output {
-            let lhs = self.handle_star(rhs.clone());
-            self.handle_concat(lhs, rhs)
+        fn handle_plus(&mut self, lhs: Self::Fragment) -> Self::Fragment {
+            ast::Expr::Unary(ast::UnaryOp::Plus, Box::new(lhs))
         }
 
         #[inline]
-        fn handle_optional(&mut self, lhs: Self::Output) -> Self::Output {
+        fn handle_optional(&mut self, lhs: Self::Fragment) -> Self::Fragment {
             ast::Expr::Unary(ast::UnaryOp::Optional, Box::new(lhs))
         }
 
         #[inline]
-        fn handle_concat(&mut self, lhs: Self::Output, rhs: Self::Output) -> Self::Output {
+        fn handle_concat(&mut self, lhs: Self::Fragment, rhs: Self::Fragment) -> Self::Fragment {
             ast::Expr::Binary(ast::BinaryOp::Concat, Box::new(lhs), Box::new(rhs))
         }
 
         #[inline]
-        fn handle_alternate(&mut self, lhs: Self::Output, rhs: Self::Output) -> Self::Output {
+        fn handle_alternate(&mut self, lhs: Self::Fragment, rhs: Self::Fragment) -> Self::Fragment {
             ast::Expr::Binary(ast::BinaryOp::Alternate, Box::new(lhs), Box::new(rhs))
         }
+
+        #[inline]
+        fn handle_group(&mut self, index: usize, inner: Self::Fragment) -> Self::Fragment {
+            ast::Expr::Group(index, Box::new(inner))
+        }
+
+        #[inline]
+        fn finish(&mut self, expr: Self::Fragment) -> Self::Output {
+            expr
+        }
     }
 }
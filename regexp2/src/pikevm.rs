@@ -0,0 +1,458 @@
+use crate::ast::{BinaryOp, Expr, UnaryOp};
+use crate::class::CharClass;
+use crate::parser::ast::ASTParser;
+use crate::regexp::{
+    find_kind_by_start, find_kind_by_start_within, Aborted, Budget, CompileResult, Engine, Match,
+    MatchKind, SearchError, UnsupportedMatchKind,
+};
+
+use automata::sparse::SparseSet;
+
+/// An instruction of a [`Program`].
+#[derive(Clone, Debug)]
+pub enum Inst {
+    /// Consume one character in the class and continue at the next instruction.
+    Char(CharClass),
+    /// Continue at both instructions; threads at the first have higher priority.
+    Split(usize, usize),
+    /// Continue at the given instruction.
+    Jmp(usize),
+    /// Record the current position in the given capture slot.
+    Save(usize),
+    /// The input up to the current position is matched.
+    Match,
+}
+
+/// A sequence of instructions compiled from a regular expression, in the style of Thompson's
+/// construction. Alternatives are ordered by [`Inst::Split`], so every path through the program
+/// has a priority.
+#[derive(Clone, Debug)]
+pub struct Program {
+    pub insts: Vec<Inst>,
+    /// The number of capture slots; two per group, including the implicit group 0 for the whole
+    /// match.
+    pub slots: usize,
+}
+
+impl Program {
+    #[inline]
+    pub(crate) fn new(expr: &Expr) -> Self {
+        let mut program = Self {
+            insts: Vec::new(),
+            slots: 2 * (max_group(expr) + 1),
+        };
+
+        program.insts.push(Inst::Save(0));
+        program.compile(expr);
+        program.insts.push(Inst::Save(1));
+        program.insts.push(Inst::Match);
+        program
+    }
+
+    #[inline]
+    fn compile(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Empty => self.insts.push(Inst::Char(CharClass::new())),
+            Expr::Epsilon => {}
+            Expr::Atom(class) => self.insts.push(Inst::Char(class.clone())),
+            Expr::Group(index, inner) => {
+                self.insts.push(Inst::Save(2 * index));
+                self.compile(inner);
+                self.insts.push(Inst::Save(2 * index + 1));
+            }
+            Expr::Binary(BinaryOp::Concat, lhs, rhs) => {
+                self.compile(lhs);
+                self.compile(rhs);
+            }
+            Expr::Binary(BinaryOp::Alternate, lhs, rhs) => {
+                let split = self.placeholder();
+                self.compile(lhs);
+                let jmp = self.placeholder();
+                let rhs_start = self.insts.len();
+                self.compile(rhs);
+
+                self.insts[split] = Inst::Split(split + 1, rhs_start);
+                self.insts[jmp] = Inst::Jmp(self.insts.len());
+            }
+            Expr::Unary(UnaryOp::Star, inner) => {
+                let split = self.placeholder();
+                self.compile(inner);
+                self.insts.push(Inst::Jmp(split));
+
+                self.insts[split] = Inst::Split(split + 1, self.insts.len());
+            }
+            Expr::Unary(UnaryOp::Plus, inner) => {
+                let start = self.insts.len();
+                self.compile(inner);
+                let split = self.insts.len();
+                self.insts.push(Inst::Split(start, split + 1));
+            }
+            Expr::Unary(UnaryOp::Optional, inner) => {
+                let split = self.placeholder();
+                self.compile(inner);
+
+                self.insts[split] = Inst::Split(split + 1, self.insts.len());
+            }
+            Expr::Unary(UnaryOp::Complement, _) | Expr::Binary(BinaryOp::Intersect, _, _) => {
+                unreachable!("the parser does not produce complement or intersection")
+            }
+        }
+    }
+
+    /// Push an instruction to be replaced once its targets are known.
+    #[inline]
+    fn placeholder(&mut self) -> usize {
+        self.insts.push(Inst::Match);
+        self.insts.len() - 1
+    }
+}
+
+/// The highest group index in the expression, or 0 if there are no groups.
+#[inline]
+fn max_group(expr: &Expr) -> usize {
+    match expr {
+        Expr::Empty | Expr::Epsilon | Expr::Atom(_) => 0,
+        Expr::Group(index, inner) => (*index).max(max_group(inner)),
+        Expr::Unary(_, inner) => max_group(inner),
+        Expr::Binary(_, lhs, rhs) => max_group(lhs).max(max_group(rhs)),
+    }
+}
+
+/// The positions matched by each group of a regular expression. Group 0 is the whole match.
+#[derive(Debug)]
+pub struct Captures {
+    groups: Vec<Option<Match>>,
+}
+
+impl Captures {
+    /// Build the captures from the capture slots of a match, two for each group.
+    #[inline]
+    pub(crate) fn from_slots(input: &str, slots: &[Option<usize>]) -> Self {
+        let groups = slots
+            .chunks(2)
+            .map(|pair| match *pair {
+                [Some(s), Some(e)] => {
+                    let span = input.chars().skip(s).take(e - s).collect();
+                    Some(Match::new(s, e, span))
+                }
+                _ => None,
+            })
+            .collect();
+        Captures { groups }
+    }
+
+    /// Return the match of the given group, or None if the group did not participate in the
+    /// match.
+    #[inline]
+    pub fn get(&self, i: usize) -> Option<&Match> {
+        self.groups.get(i).and_then(|m| m.as_ref())
+    }
+
+    /// The number of groups, including group 0.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+}
+
+/// A regular expression engine that simulates a [`Program`] with a list of threads, each
+/// carrying its own capture slots. Threads are kept in priority order, so when several threads
+/// match at the same position, the captures of the highest priority one are reported. The
+/// number of threads is bounded by the number of instructions, so searching takes time linear in
+/// the length of the input.
+#[derive(Clone, Debug)]
+pub struct PikeVM {
+    program: Program,
+}
+
+impl PikeVM {
+    /// Compile a regular expression into a Pike VM.
+    #[inline]
+    pub fn new(expr: &'_ str) -> CompileResult<'_, Self> {
+        let parser: ASTParser<CharClass> = ASTParser::new();
+        let ast = parser.parse(expr)?;
+        Ok(Self {
+            program: Program::new(&ast),
+        })
+    }
+
+    #[inline]
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    /// Find the longest match starting at the given character position and return the positions
+    /// of each group.
+    #[inline]
+    pub fn captures_at(&self, input: &str, start: usize) -> Option<Captures> {
+        self.search(input, start, false, false, None)
+            .unwrap_or_else(|_| unreachable!("there is no budget to run out"))
+            .map(|slots| Captures::from_slots(input, &slots))
+    }
+
+    #[inline]
+    pub fn captures(&self, input: &str) -> Option<Captures> {
+        self.captures_at(input, 0)
+    }
+
+    /// Run the program anchored at the given character position. Returns the capture slots of
+    /// the highest priority thread at the last (or first, if `shortest`) position a match was
+    /// found. If `first` is set, threads of lower priority than a thread that matched are
+    /// dropped, so the match is the one a backtracking search would find. Each character read
+    /// takes a step from the budget, if any.
+    #[inline]
+    fn search(
+        &self,
+        input: &str,
+        start: usize,
+        shortest: bool,
+        first: bool,
+        budget: Option<&Budget<'_>>,
+    ) -> Result<Option<Vec<Option<usize>>>, Aborted> {
+        if let Some(budget) = budget {
+            budget.check()?;
+        }
+
+        let n = self.program.insts.len();
+        let mut clist = Threads::new(n, self.program.slots);
+        let mut nlist = Threads::new(n, self.program.slots);
+        let mut stack = Vec::new();
+        let mut slots = vec![None; self.program.slots];
+        let mut matched = None;
+
+        self.add_thread(&mut clist, &mut stack, 0, start, &mut slots);
+
+        let mut chars = input.chars().skip(start);
+        let mut pos = start;
+        loop {
+            let c = chars.next();
+            if let (Some(budget), Some(_)) = (budget, c) {
+                budget.step()?;
+            }
+            for &pc in clist.set.iter() {
+                match &self.program.insts[pc] {
+                    // Only the highest priority thread reaches the match instruction at each
+                    // position. Lower priority threads may still go on to find longer matches.
+                    Inst::Match => {
+                        matched = Some(clist.slots(pc).to_vec());
+                        if first {
+                            break;
+                        }
+                    }
+                    Inst::Char(class) => {
+                        if let Some(c) = c {
+                            if class.contains(c) {
+                                slots.copy_from_slice(clist.slots(pc));
+                                self.add_thread(
+                                    &mut nlist,
+                                    &mut stack,
+                                    pc + 1,
+                                    pos + 1,
+                                    &mut slots,
+                                );
+                            }
+                        }
+                    }
+                    Inst::Split(..) | Inst::Jmp(_) | Inst::Save(_) => {}
+                }
+            }
+
+            if (shortest && matched.is_some()) || nlist.set.is_empty() || c.is_none() {
+                break;
+            }
+
+            std::mem::swap(&mut clist, &mut nlist);
+            nlist.set.clear();
+            pos += 1;
+        }
+
+        Ok(matched)
+    }
+
+    /// Find the leftmost-first match by running the program from each start position in turn.
+    #[inline]
+    fn find_first_at(
+        &self,
+        input: &str,
+        start: usize,
+        budget: Option<&Budget<'_>>,
+    ) -> Result<Option<Match>, Aborted> {
+        let len = input.chars().count();
+        for s in start..=len {
+            if let Some(slots) = self.search(input, s, false, true, budget)? {
+                return Ok(Some(to_match(input, &slots)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Add a thread at `pc` and every thread reachable from it without consuming input, in
+    /// priority order. `slots` holds the capture slots of the thread and is restored before
+    /// returning.
+    #[inline]
+    fn add_thread(
+        &self,
+        list: &mut Threads,
+        stack: &mut Vec<Frame>,
+        pc: usize,
+        pos: usize,
+        slots: &mut [Option<usize>],
+    ) {
+        stack.push(Frame::Step(pc));
+        while let Some(frame) = stack.pop() {
+            let pc = match frame {
+                Frame::Step(pc) => pc,
+                Frame::Restore(slot, old) => {
+                    slots[slot] = old;
+                    continue;
+                }
+            };
+
+            if !list.set.insert(pc) {
+                continue;
+            }
+
+            match self.program.insts[pc] {
+                Inst::Jmp(next) => stack.push(Frame::Step(next)),
+                Inst::Split(first, second) => {
+                    stack.push(Frame::Step(second));
+                    stack.push(Frame::Step(first));
+                }
+                Inst::Save(slot) => {
+                    stack.push(Frame::Restore(slot, slots[slot]));
+                    slots[slot] = Some(pos);
+                    stack.push(Frame::Step(pc + 1));
+                }
+                Inst::Char(_) | Inst::Match => list.slots_mut(pc).copy_from_slice(slots),
+            }
+        }
+    }
+}
+
+impl Engine for PikeVM {
+    #[inline]
+    fn is_match(&self, input: &str) -> bool {
+        let len = input.chars().count();
+        matches!(self.find_at(input, 0), Some(m) if m.end() == len)
+    }
+
+    #[inline]
+    fn find_shortest_at(&self, input: &str, start: usize) -> Option<Match> {
+        self.search(input, start, true, false, None)
+            .unwrap_or_else(|_| unreachable!("there is no budget to run out"))
+            .map(|slots| to_match(input, &slots))
+    }
+
+    #[inline]
+    fn find_at(&self, input: &str, start: usize) -> Option<Match> {
+        self.search(input, start, false, false, None)
+            .unwrap_or_else(|_| unreachable!("there is no budget to run out"))
+            .map(|slots| to_match(input, &slots))
+    }
+
+    #[inline]
+    fn is_match_within(&self, input: &str, budget: &Budget<'_>) -> Result<bool, Aborted> {
+        let len = input.chars().count();
+        Ok(matches!(self.find_at_within(input, 0, budget)?, Some(m) if m.end() == len))
+    }
+
+    #[inline]
+    fn find_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, Aborted> {
+        Ok(self
+            .search(input, start, false, false, Some(budget))?
+            .map(|slots| to_match(input, &slots)))
+    }
+
+    #[inline]
+    fn find_shortest_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, Aborted> {
+        Ok(self
+            .search(input, start, true, false, Some(budget))?
+            .map(|slots| to_match(input, &slots)))
+    }
+
+    #[inline]
+    fn find_kind_at(
+        &self,
+        input: &str,
+        start: usize,
+        kind: MatchKind,
+    ) -> Result<Option<Match>, UnsupportedMatchKind> {
+        match kind {
+            MatchKind::LeftmostFirst => Ok(self
+                .find_first_at(input, start, None)
+                .unwrap_or_else(|_| unreachable!("there is no budget to run out"))),
+            kind => find_kind_by_start(self, input, start, kind),
+        }
+    }
+
+    #[inline]
+    fn find_kind_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        kind: MatchKind,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, SearchError> {
+        match kind {
+            MatchKind::LeftmostFirst => Ok(self.find_first_at(input, start, Some(budget))?),
+            kind => find_kind_by_start_within(self, input, start, kind, budget),
+        }
+    }
+}
+
+#[inline]
+pub(crate) fn to_match(input: &str, slots: &[Option<usize>]) -> Match {
+    let (start, end) = (slots[0].unwrap(), slots[1].unwrap());
+    let span = input.chars().skip(start).take(end - start).collect();
+    Match::new(start, end, span)
+}
+
+enum Frame {
+    /// Follow the instruction at the given index.
+    Step(usize),
+    /// Restore a capture slot to its previous value.
+    Restore(usize, Option<usize>),
+}
+
+/// A list of threads in priority order, each with its own capture slots.
+struct Threads {
+    set: SparseSet,
+    slots: Vec<Option<usize>>,
+    stride: usize,
+}
+
+impl Threads {
+    #[inline]
+    fn new(insts: usize, stride: usize) -> Self {
+        Self {
+            set: SparseSet::new(insts),
+            slots: vec![None; insts * stride],
+            stride,
+        }
+    }
+
+    #[inline]
+    fn slots(&self, pc: usize) -> &[Option<usize>] {
+        &self.slots[pc * self.stride..(pc + 1) * self.stride]
+    }
+
+    #[inline]
+    fn slots_mut(&mut self, pc: usize) -> &mut [Option<usize>] {
+        &mut self.slots[pc * self.stride..(pc + 1) * self.stride]
+    }
+}
@@ -1,12 +1,88 @@
+use crate::ast::Expr;
+use crate::backtrack::BoundedBacktracker;
 use crate::class::CharClass;
-use crate::parser::{self, nfa::NFAParser};
+use crate::dense::DenseDFA;
+use crate::derivative::DerivativeMatcher;
+use crate::generate::Generator;
+use crate::lazy::LazyDFA;
+use crate::parser::{self, ast::ASTParser, nfa::NFAParser, ParseError};
+use crate::pikevm::{Captures, PikeVM};
+use crate::tagged::TaggedDFA;
 
 use std::ops::Range;
 
-use automata::{self, nfa::Transition, DFA, NFA};
+use automata::{self, convert::DFAFromNFA, nfa::Transition, DFA, NFA};
 
+pub use automata::budget::{Aborted, Budget};
+pub use automata::limit::{Limit, Limits, SizeLimitExceeded};
 pub use parser::ParseResult;
 
+/// Alias for [`Result`] for [`CompileError`].
+pub type CompileResult<'r, T> = std::result::Result<T, CompileError<'r>>;
+
+/// Error returned when a regular expression cannot be compiled, either because it does not parse
+/// or because an automaton built for it grows past its [`Limits`].
+/// Limits are checked while parsing as well as after, so a limit exceeded by the automaton the
+/// parser builds is reported as [`CompileError::SizeLimitExceeded`] too.
+#[derive(Debug, thiserror::Error)]
+pub enum CompileError<'r> {
+    #[error(transparent)]
+    Parse(ParseError<'r>),
+    #[error(transparent)]
+    SizeLimitExceeded(#[from] SizeLimitExceeded),
+}
+
+impl<'r> From<ParseError<'r>> for CompileError<'r> {
+    #[inline]
+    fn from(err: ParseError<'r>) -> Self {
+        CompileError::Parse(err)
+    }
+}
+
+/// Error returned when an automaton is built from the positions of an expression that contains an
+/// operator without positions. The parser never produces these operators.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum UnsupportedOperator {
+    #[error("positions are not defined for complement")]
+    Complement,
+    #[error("positions are not defined for intersection")]
+    Intersect,
+}
+
+/// Error returned when an NFA cannot be built from an expression with Glushkov's construction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum GlushkovError {
+    #[error(transparent)]
+    Unsupported(#[from] UnsupportedOperator),
+    #[error(transparent)]
+    SizeLimitExceeded(#[from] SizeLimitExceeded),
+}
+
+/// Error returned when a DFA cannot be built directly from an expression with `followpos`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum FollowposError {
+    #[error(transparent)]
+    Unsupported(#[from] UnsupportedOperator),
+    #[error(transparent)]
+    SizeLimitExceeded(#[from] SizeLimitExceeded),
+}
+
+/// Error returned when a search asks for a [`MatchKind`] the engine cannot tell apart from
+/// other matches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("the engine cannot find {0:?} matches")]
+pub struct UnsupportedMatchKind(pub MatchKind);
+
+/// Error returned when a search for a given [`MatchKind`] cannot be done, either because the
+/// engine does not support the kind or because the budget ran out.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum SearchError {
+    #[error(transparent)]
+    Unsupported(#[from] UnsupportedMatchKind),
+    #[error(transparent)]
+    Aborted(#[from] Aborted),
+}
+
 #[derive(Debug)]
 pub struct Match {
     start: usize,
@@ -48,16 +124,26 @@ impl From<automata::Match<char>> for Match {
 /// strings are within the language described by the regular expression.
 #[derive(Debug)]
 pub struct RegExp<E: Engine> {
-    /// The regular expression represented by this structure.
-    expr: String,
+    /// The regular expression represented by this structure, if it has one the parser accepts.
+    expr: Option<String>,
     /// The compiled backend of the regular expression used to evaluate input strings.
     engine: E,
 }
 
 impl<E: Engine> RegExp<E> {
+    /// The regular expression this was compiled from, or the empty string, which the parser
+    /// rejects, if it has none. See [`RegExp::pattern`].
     #[inline]
     pub fn as_str(&self) -> &str {
-        &self.expr
+        self.pattern().unwrap_or("")
+    }
+
+    /// The regular expression this was compiled from. Regular expressions built by the product
+    /// operations other than [`RegExp::union`], or by complement, have none, since the parser
+    /// has no operators for them; [`RegExp::to_pattern`] writes one for their DFA instead.
+    #[inline]
+    pub fn pattern(&self) -> Option<&str> {
+        self.expr.as_deref()
     }
 
     /// Determine if the given input string is within the language described by the regular
@@ -77,6 +163,29 @@ impl<E: Engine> RegExp<E> {
         self.engine.find_at(input, start)
     }
 
+    /// Find a match anywhere in the input, chosen as `kind` describes, or fail if the engine
+    /// does not support `kind`.
+    #[inline]
+    pub fn find_kind(
+        &self,
+        input: &str,
+        kind: MatchKind,
+    ) -> Result<Option<Match>, UnsupportedMatchKind> {
+        self.find_kind_at(input, 0, kind)
+    }
+
+    /// Find a match starting at or after the given position, chosen as `kind` describes, or fail
+    /// if the engine does not support `kind`.
+    #[inline]
+    pub fn find_kind_at(
+        &self,
+        input: &str,
+        start: usize,
+        kind: MatchKind,
+    ) -> Result<Option<Match>, UnsupportedMatchKind> {
+        self.engine.find_kind_at(input, start, kind)
+    }
+
     #[inline]
     pub fn find_shortest(&self, input: &str) -> Option<Match> {
         self.find_shortest_at(input, 0)
@@ -86,23 +195,146 @@ impl<E: Engine> RegExp<E> {
     pub fn find_shortest_at(&self, input: &str, start: usize) -> Option<Match> {
         self.engine.find_shortest_at(input, start)
     }
+
+    /// Determine if the given input string matches, as [`RegExp::is_match`] does, or abort if the
+    /// budget runs out first. The budget bounds the steps taken or lets another thread cancel
+    /// the search.
+    #[inline]
+    pub fn is_match_within(&self, input: &str, budget: &Budget<'_>) -> Result<bool, Aborted> {
+        self.engine.is_match_within(input, budget)
+    }
+
+    #[inline]
+    pub fn find_within(&self, input: &str, budget: &Budget<'_>) -> Result<Option<Match>, Aborted> {
+        self.find_at_within(input, 0, budget)
+    }
+
+    #[inline]
+    pub fn find_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, Aborted> {
+        self.engine.find_at_within(input, start, budget)
+    }
+
+    #[inline]
+    pub fn find_shortest_within(
+        &self,
+        input: &str,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, Aborted> {
+        self.find_shortest_at_within(input, 0, budget)
+    }
+
+    #[inline]
+    pub fn find_shortest_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, Aborted> {
+        self.engine.find_shortest_at_within(input, start, budget)
+    }
+
+    /// Find a match starting at or after the given position, as [`RegExp::find_kind_at`] does,
+    /// or abort if the budget runs out first.
+    #[inline]
+    pub fn find_kind_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        kind: MatchKind,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, SearchError> {
+        self.engine.find_kind_at_within(input, start, kind, budget)
+    }
 }
 
 impl RegExp<NFA<CharClass>> {
-    /// Create a compiled regular expression that uses an NFA to evaluate input strings.
+    /// Create a compiled regular expression that uses an NFA to evaluate input strings, within
+    /// the default [`Limits`].
+    #[inline]
+    pub fn new_nfa(expr: &'_ str) -> CompileResult<'_, Self> {
+        RegExp::new_nfa_with_limits(expr, Limits::default())
+    }
+
+    /// Create a compiled regular expression that uses an NFA to evaluate input strings. Parsing
+    /// stops as soon as the NFA has more states than the limit.
     #[inline]
-    pub fn new_nfa(expr: &'_ str) -> ParseResult<'_, Self> {
-        let parser = NFAParser::new();
+    pub fn new_nfa_with_limits(expr: &'_ str, limits: Limits) -> CompileResult<'_, Self> {
+        let parser = NFAParser::with_limits(limits);
         let nfa: NFA<CharClass> = parser.parse(expr)?;
 
         Ok(RegExp {
-            expr: expr.to_owned(),
+            expr: Some(expr.to_owned()),
             engine: nfa,
         })
     }
 
+    /// Create a compiled regular expression that uses an NFA built by Glushkov's construction,
+    /// which has no epsilon transitions and one state per character class in the expression, plus
+    /// a start state, within the default [`Limits`].
+    #[inline]
+    pub fn new_glushkov(expr: &'_ str) -> CompileResult<'_, Self> {
+        RegExp::new_glushkov_with_limits(expr, Limits::default())
+    }
+
+    /// Create a compiled regular expression that uses an NFA built by Glushkov's construction,
+    /// failing if the NFA has more states than the limit or its transitions take more memory.
+    #[inline]
+    pub fn new_glushkov_with_limits(expr: &'_ str, limits: Limits) -> CompileResult<'_, Self> {
+        let parser: ASTParser<CharClass> = ASTParser::new();
+        let ast = parser.parse(expr)?;
+
+        Ok(RegExp {
+            expr: Some(expr.to_owned()),
+            engine: match ast.glushkov_nfa_with_limits(limits) {
+                Ok(nfa) => nfa,
+                Err(GlushkovError::SizeLimitExceeded(err)) => return Err(err.into()),
+                Err(GlushkovError::Unsupported(_)) => {
+                    unreachable!("the parser does not produce complement or intersection")
+                }
+            },
+        })
+    }
+
+    /// Convert the NFA backend into a minimal DFA. Epsilon transitions and useless states are
+    /// removed first, so that the subset construction has fewer states to track.
     #[inline]
     pub fn with_dfa(self) -> RegExp<DFA<CharClass>> {
+        let dfa: DFA<CharClass> = self.engine.remove_epsilons().trim().into();
+        RegExp {
+            expr: self.expr,
+            engine: dfa.minimize(),
+        }
+    }
+
+    /// Convert the NFA backend into a minimal DFA, as [`RegExp::with_dfa`] does, but fail if the
+    /// subset construction exceeds the limits.
+    #[inline]
+    pub fn try_with_dfa(self, limits: Limits) -> Result<RegExp<DFA<CharClass>>, SizeLimitExceeded> {
+        let nfa = self.engine.remove_epsilons().trim();
+        let dfa: DFA<CharClass> = DFAFromNFA::with_limits(nfa, limits)?.into();
+        Ok(RegExp {
+            expr: self.expr,
+            engine: dfa.minimize(),
+        })
+    }
+
+    /// Use the NFA backend with a bounded backtracker, which is faster for short inputs.
+    #[inline]
+    pub fn with_backtracker(self) -> RegExp<BoundedBacktracker> {
+        RegExp {
+            expr: self.expr,
+            engine: self.engine.into(),
+        }
+    }
+
+    /// Use the NFA backend to construct a DFA lazily during searches.
+    #[inline]
+    pub fn with_lazy_dfa(self) -> RegExp<LazyDFA> {
         RegExp {
             expr: self.expr,
             engine: self.engine.into(),
@@ -110,11 +342,280 @@ impl RegExp<NFA<CharClass>> {
     }
 }
 
+impl RegExp<BoundedBacktracker> {
+    /// Create a compiled regular expression that uses a bounded backtracker to evaluate input
+    /// strings.
+    #[inline]
+    pub fn new_backtracker(expr: &'_ str) -> CompileResult<'_, Self> {
+        Ok(RegExp::new_nfa(expr)?.with_backtracker())
+    }
+}
+
+impl RegExp<LazyDFA> {
+    /// Create a compiled regular expression that uses a lazily constructed DFA to evaluate input
+    /// strings.
+    #[inline]
+    pub fn new_lazy(expr: &'_ str) -> CompileResult<'_, Self> {
+        Ok(RegExp::new_nfa(expr)?.with_lazy_dfa())
+    }
+}
+
 impl RegExp<DFA<CharClass>> {
-    /// Create a compiled regular expression that uses a DFA to evaluate input strings.
+    /// Create a compiled regular expression that uses a DFA to evaluate input strings, within the
+    /// default [`Limits`].
+    #[inline]
+    pub fn new(expr: &'_ str) -> CompileResult<'_, Self> {
+        RegExp::new_with_limits(expr, Limits::default())
+    }
+
+    /// Create a compiled regular expression that uses a DFA to evaluate input strings, failing
+    /// if the NFA or the DFA built for it exceeds the limits.
+    #[inline]
+    pub fn new_with_limits(expr: &'_ str, limits: Limits) -> CompileResult<'_, Self> {
+        Ok(RegExp::new_nfa_with_limits(expr, limits)?.try_with_dfa(limits)?)
+    }
+
+    /// Create a compiled regular expression that uses a DFA built directly from the syntax tree
+    /// with `followpos`, without an intermediate NFA, within the default [`Limits`]. The DFA is
+    /// not minimized.
+    #[inline]
+    pub fn new_followpos(expr: &'_ str) -> CompileResult<'_, Self> {
+        RegExp::new_followpos_with_limits(expr, Limits::default())
+    }
+
+    /// Create a compiled regular expression that uses a DFA built directly from the syntax tree
+    /// with `followpos`, failing as soon as the DFA has more states than the limit.
+    #[inline]
+    pub fn new_followpos_with_limits(expr: &'_ str, limits: Limits) -> CompileResult<'_, Self> {
+        let parser: ASTParser<CharClass> = ASTParser::new();
+        let ast = parser.parse(expr)?;
+
+        Ok(RegExp {
+            expr: Some(expr.to_owned()),
+            engine: match ast.followpos_dfa_with_limits(limits) {
+                Ok(dfa) => dfa,
+                Err(FollowposError::SizeLimitExceeded(err)) => return Err(err.into()),
+                Err(FollowposError::Unsupported(_)) => {
+                    unreachable!("the parser does not produce complement or intersection")
+                }
+            },
+        })
+    }
+
+    /// Create a regular expression that matches the strings matched by both regular expressions.
+    /// The result has no expression of its own.
+    #[inline]
+    pub fn intersection(&self, other: &Self) -> Self {
+        RegExp {
+            expr: None,
+            engine: self.engine.intersection(&other.engine).minimize(),
+        }
+    }
+
+    /// Create a regular expression that matches the strings matched by either regular
+    /// expression. The expression of the result is the alternation of both, if both have one.
+    #[inline]
+    pub fn union(&self, other: &Self) -> Self {
+        let expr = self.expr.as_ref().zip(other.expr.as_ref());
+        RegExp {
+            expr: expr.map(|(e1, e2)| format!("({})|({})", e1, e2)),
+            engine: self.engine.union(&other.engine).minimize(),
+        }
+    }
+
+    /// Create a regular expression that matches the strings matched by this regular expression
+    /// but not the other. The result has no expression of its own.
     #[inline]
-    pub fn new(expr: &'_ str) -> ParseResult<'_, Self> {
-        Ok(RegExp::new_nfa(expr)?.with_dfa())
+    pub fn difference(&self, other: &Self) -> Self {
+        RegExp {
+            expr: None,
+            engine: self.engine.difference(&other.engine).minimize(),
+        }
+    }
+
+    /// Create a regular expression that matches the strings matched by exactly one of the
+    /// regular expressions. The result has no expression of its own.
+    #[inline]
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        RegExp {
+            expr: None,
+            engine: self.engine.symmetric_difference(&other.engine).minimize(),
+        }
+    }
+
+    /// Create a regular expression that matches exactly the strings this regular expression does
+    /// not match. The result has no expression of its own.
+    #[inline]
+    pub fn complement(&self) -> Self {
+        RegExp {
+            expr: None,
+            engine: self.engine.complement().minimize(),
+        }
+    }
+
+    /// Determine if every string matched by this regular expression is matched by the other. If
+    /// not, returns a shortest string matched by this regular expression but not the other.
+    #[inline]
+    pub fn is_subset_of(&self, other: &Self) -> Result<(), String> {
+        self.engine
+            .is_subset_of(&other.engine)
+            .map_err(|symbols| Self::example(&symbols))
+    }
+
+    /// Determine if both regular expressions match the same strings. If not, returns a shortest
+    /// string matched by exactly one of them.
+    #[inline]
+    pub fn equivalent(&self, other: &Self) -> Result<(), String> {
+        self.engine
+            .is_equivalent_to(&other.engine)
+            .map_err(|symbols| Self::example(&symbols))
+    }
+
+    /// Build a string from one character of each class.
+    #[inline]
+    fn example(symbols: &[CharClass]) -> String {
+        symbols.iter().filter_map(|c| c.representative()).collect()
+    }
+
+    /// Write a pattern that matches the same strings as the DFA, by state elimination. This
+    /// gives a pattern for the results of [`RegExp::intersection`] and the other product
+    /// operations, for which [`RegExp::pattern`] has none. See
+    /// [`Expr::from_dfa`] and the [`Display`](std::fmt::Display) impl of [`Expr`].
+    #[inline]
+    pub fn to_pattern(&self) -> String {
+        Expr::from_dfa(&self.engine).to_string()
+    }
+
+    /// Create a generator that enumerates, counts and samples the strings matched by the regular
+    /// expression.
+    #[inline]
+    pub fn generator(&self) -> Generator {
+        Generator::new(&self.engine)
+    }
+
+    /// Compile the DFA backend into a dense transition table.
+    #[inline]
+    pub fn with_dense(self) -> RegExp<DenseDFA> {
+        RegExp {
+            expr: self.expr,
+            engine: (&self.engine).into(),
+        }
+    }
+}
+
+impl RegExp<DenseDFA> {
+    /// Create a compiled regular expression that uses a DFA with a dense transition table to
+    /// evaluate input strings.
+    #[inline]
+    pub fn new_dense(expr: &'_ str) -> CompileResult<'_, Self> {
+        Ok(RegExp::new(expr)?.with_dense())
+    }
+}
+
+impl RegExp<DerivativeMatcher> {
+    /// Create a regular expression that evaluates input strings by taking derivatives of the
+    /// expression.
+    #[inline]
+    pub fn new_derivative(expr: &'_ str) -> CompileResult<'_, Self> {
+        Ok(RegExp {
+            expr: Some(expr.to_owned()),
+            engine: DerivativeMatcher::new(expr)?,
+        })
+    }
+
+    /// Create a regular expression that matches the strings matched by both regular expressions.
+    /// The result has no expression of its own.
+    #[inline]
+    pub fn intersection(&self, other: &Self) -> Self {
+        let expr = Expr::intersect(self.engine.expr().clone(), other.engine.expr().clone());
+        RegExp {
+            expr: None,
+            engine: expr.into(),
+        }
+    }
+
+    /// Create a regular expression that matches exactly the strings this regular expression does
+    /// not match. The result has no expression of its own.
+    #[inline]
+    pub fn complement(&self) -> Self {
+        RegExp {
+            expr: None,
+            engine: Expr::complement(self.engine.expr().clone()).into(),
+        }
+    }
+
+    /// Build a minimal DFA from the derivatives of the expression.
+    #[inline]
+    pub fn with_dfa(self) -> RegExp<DFA<CharClass>> {
+        RegExp {
+            expr: self.expr,
+            engine: self.engine.to_dfa().minimize(),
+        }
+    }
+
+    /// Build a minimal DFA from the derivatives of the expression, as [`RegExp::with_dfa`] does,
+    /// but fail if there are more distinct derivatives than the limit on DFA states.
+    #[inline]
+    pub fn try_with_dfa(self, limits: Limits) -> Result<RegExp<DFA<CharClass>>, SizeLimitExceeded> {
+        let dfa = self.engine.to_dfa_with_limits(limits)?;
+        Ok(RegExp {
+            expr: self.expr,
+            engine: dfa.minimize(),
+        })
+    }
+}
+
+impl RegExp<PikeVM> {
+    /// Create a compiled regular expression that uses a Pike VM to evaluate input strings.
+    #[inline]
+    pub fn new_pikevm(expr: &'_ str) -> CompileResult<'_, Self> {
+        Ok(RegExp {
+            expr: Some(expr.to_owned()),
+            engine: PikeVM::new(expr)?,
+        })
+    }
+
+    /// Find the longest match at the start of the input string and return the positions
+    /// matched by each group.
+    #[inline]
+    pub fn captures(&self, input: &str) -> Option<Captures> {
+        self.captures_at(input, 0)
+    }
+
+    #[inline]
+    pub fn captures_at(&self, input: &str, start: usize) -> Option<Captures> {
+        self.engine.captures_at(input, start)
+    }
+}
+
+impl RegExp<TaggedDFA> {
+    /// Create a compiled regular expression that uses a tagged DFA to evaluate input strings and
+    /// extract captures, within the default [`Limits`].
+    #[inline]
+    pub fn new_tagged(expr: &'_ str) -> CompileResult<'_, Self> {
+        RegExp::new_tagged_with_limits(expr, Limits::default())
+    }
+
+    /// Create a compiled regular expression that uses a tagged DFA, failing as soon as the DFA
+    /// has more states than the limit.
+    #[inline]
+    pub fn new_tagged_with_limits(expr: &'_ str, limits: Limits) -> CompileResult<'_, Self> {
+        Ok(RegExp {
+            expr: Some(expr.to_owned()),
+            engine: TaggedDFA::with_limits(expr, limits)?,
+        })
+    }
+
+    /// Find the longest match at the start of the input string and return the positions
+    /// matched by each group, as [`RegExp::<PikeVM>::captures`] does.
+    #[inline]
+    pub fn captures(&self, input: &str) -> Option<Captures> {
+        self.captures_at(input, 0)
+    }
+
+    #[inline]
+    pub fn captures_at(&self, input: &str, start: usize) -> Option<Captures> {
+        self.engine.captures_at(input, start)
     }
 }
 
@@ -132,6 +633,28 @@ impl From<CharClass> for Transition<CharClass> {
     }
 }
 
+/// Which match a search reports when several matches start at or after the search position.
+///
+/// Every kind searches for a match starting anywhere from the search position on, unlike
+/// [`RegExp::find_at`] and [`RegExp::find_shortest_at`], which only report matches starting
+/// exactly at it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MatchKind {
+    /// Of the matches that start leftmost, the longest, as POSIX specifies.
+    LeftmostLongest,
+    /// Of the matches that start leftmost, the one a backtracking search finds first, as in Perl:
+    /// earlier alternatives are preferred to later ones, and repetition is greedy. In `ab` the
+    /// expression `a|ab` matches `a`.
+    ///
+    /// Only engines that keep the order of alternatives, the [`PikeVM`] and the [`TaggedDFA`],
+    /// can tell which match that is. The others fail with [`UnsupportedMatchKind`].
+    LeftmostFirst,
+    /// The match that ends earliest, extended as far left as possible, as
+    /// [`ReverseSearcher::find_earliest`](crate::reverse::ReverseSearcher::find_earliest) finds.
+    /// A search can stop as soon as any match ends.
+    Earliest,
+}
+
 /// A trait implemented by regular expression backends, used to evaluate input strings.
 pub trait Engine {
     fn is_match(&self, input: &str) -> bool;
@@ -139,6 +662,160 @@ pub trait Engine {
     fn find_at(&self, input: &str, start: usize) -> Option<Match>;
 
     fn find_shortest_at(&self, input: &str, start: usize) -> Option<Match>;
+
+    /// Determine if the whole input matches, as [`Engine::is_match`] does, or abort if the budget
+    /// runs out first. Each character read takes a step.
+    ///
+    /// By default the budget is charged for every character of the input before matching, so the
+    /// search aborts without starting if the budget is too small or already cancelled, and
+    /// cannot be cancelled once started. Engines that check the budget as they search override
+    /// this.
+    #[inline]
+    fn is_match_within(&self, input: &str, budget: &Budget<'_>) -> Result<bool, Aborted> {
+        budget.take(input.chars().count())?;
+        Ok(self.is_match(input))
+    }
+
+    /// Find the longest match at the given position, as [`Engine::find_at`] does, or abort if the
+    /// budget runs out first. See [`Engine::is_match_within`].
+    #[inline]
+    fn find_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, Aborted> {
+        budget.take(input.chars().count().saturating_sub(start))?;
+        Ok(self.find_at(input, start))
+    }
+
+    /// Find the shortest match at the given position, as [`Engine::find_shortest_at`] does, or
+    /// abort if the budget runs out first. See [`Engine::is_match_within`].
+    #[inline]
+    fn find_shortest_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, Aborted> {
+        budget.take(input.chars().count().saturating_sub(start))?;
+        Ok(self.find_shortest_at(input, start))
+    }
+
+    /// Find a match starting at or after the given position, chosen as `kind` describes. By
+    /// default each start position is tried in turn, and [`MatchKind::LeftmostFirst`] is not
+    /// supported; see [`find_kind_by_start`].
+    #[inline]
+    fn find_kind_at(
+        &self,
+        input: &str,
+        start: usize,
+        kind: MatchKind,
+    ) -> Result<Option<Match>, UnsupportedMatchKind> {
+        find_kind_by_start(self, input, start, kind)
+    }
+
+    /// Find a match of the given kind, as [`Engine::find_kind_at`] does, or abort if the budget
+    /// runs out first. By default each start position is tried in turn with
+    /// [`Engine::find_at_within`] or [`Engine::find_shortest_at_within`], so the steps of every
+    /// attempt are taken from the budget.
+    #[inline]
+    fn find_kind_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        kind: MatchKind,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, SearchError> {
+        find_kind_by_start_within(self, input, start, kind, budget)
+    }
+}
+
+/// Find a match of the given kind by trying each start position in turn with the anchored
+/// searches of the engine. The leftmost start with a match gives the leftmost-longest match, and
+/// the shortest match at each start gives the one that ends earliest. The anchored searches find
+/// the longest match, so [`MatchKind::LeftmostFirst`] fails.
+#[inline]
+pub(crate) fn find_kind_by_start<E: Engine + ?Sized>(
+    engine: &E,
+    input: &str,
+    start: usize,
+    kind: MatchKind,
+) -> Result<Option<Match>, UnsupportedMatchKind> {
+    find_kind_by(
+        input,
+        start,
+        kind,
+        |s| Ok(engine.find_at(input, s)),
+        |s| Ok(engine.find_shortest_at(input, s)),
+    )
+}
+
+/// Find a match of the given kind as [`find_kind_by_start`] does, taking the steps of each
+/// anchored search from the budget.
+#[inline]
+pub(crate) fn find_kind_by_start_within<E: Engine + ?Sized>(
+    engine: &E,
+    input: &str,
+    start: usize,
+    kind: MatchKind,
+    budget: &Budget<'_>,
+) -> Result<Option<Match>, SearchError> {
+    find_kind_by(
+        input,
+        start,
+        kind,
+        |s| Ok(engine.find_at_within(input, s, budget)?),
+        |s| Ok(engine.find_shortest_at_within(input, s, budget)?),
+    )
+}
+
+/// Find a match of the given kind from the longest and shortest anchored matches at each start
+/// position.
+#[inline]
+fn find_kind_by<F, G, Err>(
+    input: &str,
+    start: usize,
+    kind: MatchKind,
+    mut find_at: F,
+    mut find_shortest_at: G,
+) -> Result<Option<Match>, Err>
+where
+    F: FnMut(usize) -> Result<Option<Match>, Err>,
+    G: FnMut(usize) -> Result<Option<Match>, Err>,
+    Err: From<UnsupportedMatchKind>,
+{
+    let len = input.chars().count();
+    match kind {
+        MatchKind::LeftmostLongest => {
+            for s in start..=len {
+                if let Some(m) = find_at(s)? {
+                    return Ok(Some(m));
+                }
+            }
+            Ok(None)
+        }
+        MatchKind::LeftmostFirst => Err(UnsupportedMatchKind(kind).into()),
+        MatchKind::Earliest => {
+            let mut earliest: Option<Match> = None;
+            for s in start..=len {
+                // A match that starts later cannot end earlier.
+                if matches!(&earliest, Some(m) if m.end() < s) {
+                    break;
+                }
+                if let Some(m) = find_shortest_at(s)? {
+                    let earlier = match &earliest {
+                        Some(e) => m.end() < e.end(),
+                        None => true,
+                    };
+                    if earlier {
+                        earliest = Some(m);
+                    }
+                }
+            }
+            Ok(earliest)
+        }
+    }
 }
 
 impl Engine for NFA<CharClass> {
@@ -156,6 +833,31 @@ impl Engine for NFA<CharClass> {
     fn find_at(&self, input: &str, start: usize) -> Option<Match> {
         NFA::find_at(self, input.chars(), start).map(From::from)
     }
+
+    #[inline]
+    fn is_match_within(&self, input: &str, budget: &Budget<'_>) -> Result<bool, Aborted> {
+        NFA::is_match_within(self, input.chars(), budget)
+    }
+
+    #[inline]
+    fn find_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, Aborted> {
+        Ok(NFA::find_at_within(self, input.chars(), start, budget)?.map(From::from))
+    }
+
+    #[inline]
+    fn find_shortest_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, Aborted> {
+        Ok(NFA::find_shortest_at_within(self, input.chars(), start, budget)?.map(From::from))
+    }
 }
 
 impl Engine for DFA<CharClass> {
@@ -173,4 +875,29 @@ impl Engine for DFA<CharClass> {
     fn find_at(&self, input: &str, start: usize) -> Option<Match> {
         DFA::find_at(self, input.chars(), start).map(From::from)
     }
+
+    #[inline]
+    fn is_match_within(&self, input: &str, budget: &Budget<'_>) -> Result<bool, Aborted> {
+        DFA::is_match_within(self, input.chars(), budget)
+    }
+
+    #[inline]
+    fn find_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, Aborted> {
+        Ok(DFA::find_at_within(self, input.chars(), start, budget)?.map(From::from))
+    }
+
+    #[inline]
+    fn find_shortest_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, Aborted> {
+        Ok(DFA::find_shortest_at_within(self, input.chars(), start, budget)?.map(From::from))
+    }
 }
@@ -0,0 +1,243 @@
+use crate::class::CharClass;
+use crate::dense::DenseDFA;
+use crate::parser::nfa::NFAParser;
+use crate::regexp::{CompileResult, Match};
+
+use automata::convert::DFAFromNFA;
+use automata::limit::{Limits, SizeLimitExceeded};
+use automata::nfa::Transition;
+use automata::{DFA, NFA};
+
+/// Searches for matches anywhere in the input, rather than only at a given position.
+///
+/// [`ReverseSearcher::find`] reports the leftmost-longest match. A DFA for the reversed language
+/// prefixed by any string scans the input backward from its end to find the leftmost position at
+/// which a match starts, and an anchored DFA then scans forward from there to find where the
+/// longest match ends.
+///
+/// [`ReverseSearcher::find_earliest`] reports the match that ends earliest, extended as far left
+/// as possible. A forward DFA for the language prefixed by any string finds where the first match
+/// ends, and a DFA for the reversed language then scans backward from there to find where it
+/// starts. This is not always the leftmost-longest match: in `abc` the expression `abc|b` matches
+/// `b`, since it ends before `abc` does.
+#[derive(Clone, Debug)]
+pub struct ReverseSearcher {
+    /// Accepts every match.
+    anchored: DenseDFA,
+    /// Accepts every string that ends with a match.
+    forward: DenseDFA,
+    /// Accepts the reverse of every match.
+    reverse: DenseDFA,
+    /// Accepts the reverse of every string that starts with a match.
+    reverse_unanchored: DenseDFA,
+}
+
+impl ReverseSearcher {
+    /// Create a searcher for the given regular expression, within the default [`Limits`].
+    #[inline]
+    pub fn new(expr: &'_ str) -> CompileResult<'_, Self> {
+        Self::with_limits(expr, Limits::default())
+    }
+
+    /// Create a searcher for the given regular expression, failing if the NFA or any of the DFAs
+    /// built for it exceeds the limits.
+    #[inline]
+    pub fn with_limits(expr: &'_ str, limits: Limits) -> CompileResult<'_, Self> {
+        let nfa: NFA<CharClass> = NFAParser::with_limits(limits).parse(expr)?;
+        Ok(Self::from_nfa(&nfa, limits)?)
+    }
+
+    #[inline]
+    fn from_nfa(nfa: &NFA<CharClass>, limits: Limits) -> Result<Self, SizeLimitExceeded> {
+        let dfa = |nfa: NFA<CharClass>| -> Result<DenseDFA, SizeLimitExceeded> {
+            let dfa: DFA<CharClass> = DFAFromNFA::with_limits(nfa, limits)?.into();
+            Ok(dfa.minimize().into())
+        };
+
+        let reversed = nfa.reverse();
+        Ok(Self {
+            anchored: dfa(nfa.clone())?,
+            forward: dfa(unanchored(nfa))?,
+            reverse_unanchored: dfa(unanchored(&reversed))?,
+            reverse: dfa(reversed)?,
+        })
+    }
+
+    /// Find the leftmost-longest match in the input. See [`ReverseSearcher::find_at`].
+    #[inline]
+    pub fn find(&self, input: &str) -> Option<Match> {
+        self.find_at(input, 0)
+    }
+
+    /// Find the leftmost-longest match that starts at or after the given position. The leftmost
+    /// start is found by scanning backward from the end of the input, and the longest match by
+    /// scanning forward from that start.
+    #[inline]
+    pub fn find_at(&self, input: &str, start: usize) -> Option<Match> {
+        let chars: Vec<char> = input.chars().collect();
+        if start > chars.len() {
+            return None;
+        }
+
+        let start = self.leftmost_start(&chars, start)?;
+        let end = self.longest_end(&chars, start)?;
+        Some(Self::span(&chars, start, end))
+    }
+
+    /// Find the match in the input that ends earliest. See [`ReverseSearcher::find_earliest_at`].
+    #[inline]
+    pub fn find_earliest(&self, input: &str) -> Option<Match> {
+        self.find_earliest_at(input, 0)
+    }
+
+    /// Find the match that ends earliest among those that start at or after the given position,
+    /// extended as far left as possible. The earliest end is found by scanning forward, and the
+    /// start by scanning backward from that end.
+    #[inline]
+    pub fn find_earliest_at(&self, input: &str, start: usize) -> Option<Match> {
+        let chars: Vec<char> = input.chars().collect();
+        if start > chars.len() {
+            return None;
+        }
+
+        let end = self.forward_end(&chars, start)?;
+        let start = self.reverse_start(&chars, start, end)?;
+        Some(Self::span(&chars, start, end))
+    }
+
+    /// Find the longest match that ends at the end of the input, as if the expression ended with
+    /// `$`. Only the end of the input is scanned, backward, until no match can start any earlier.
+    #[inline]
+    pub fn find_suffix(&self, input: &str) -> Option<Match> {
+        let chars: Vec<char> = input.chars().collect();
+        let start = self.reverse_start(&chars, 0, chars.len())?;
+        Some(Self::span(&chars, start, chars.len()))
+    }
+
+    /// Determine if any part of the input matches.
+    #[inline]
+    pub fn is_match(&self, input: &str) -> bool {
+        let chars: Vec<char> = input.chars().collect();
+        self.forward_end(&chars, 0).is_some()
+    }
+
+    /// Scan forward from `start` for the earliest position at which a match ends.
+    #[inline]
+    fn forward_end(&self, chars: &[char], start: usize) -> Option<usize> {
+        let dfa = &self.forward;
+        let mut state = dfa.start_state();
+        let mut end = start;
+        loop {
+            if dfa.is_accepting_state(state) {
+                return Some(end);
+            }
+
+            state = chars.get(end).and_then(|&c| dfa.next_state(state, c))?;
+            end += 1;
+        }
+    }
+
+    /// Scan backward from the end of the input, but not past `start`, for the leftmost position at
+    /// which a match starts.
+    #[inline]
+    fn leftmost_start(&self, chars: &[char], start: usize) -> Option<usize> {
+        let dfa = &self.reverse_unanchored;
+        let mut state = dfa.start_state();
+        let mut leftmost = None;
+        let mut i = chars.len();
+        loop {
+            if dfa.is_accepting_state(state) {
+                leftmost = Some(i);
+            }
+            if i == start {
+                break;
+            }
+
+            i -= 1;
+            state = match dfa.next_state(state, chars[i]) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        leftmost
+    }
+
+    /// Scan forward from `start` for the last position at which a match starting at `start`
+    /// ends.
+    #[inline]
+    fn longest_end(&self, chars: &[char], start: usize) -> Option<usize> {
+        let dfa = &self.anchored;
+        let mut state = dfa.start_state();
+        let mut last_match = None;
+        let mut i = start;
+        loop {
+            if dfa.is_accepting_state(state) {
+                last_match = Some(i);
+            }
+
+            state = match chars.get(i).and_then(|&c| dfa.next_state(state, c)) {
+                Some(next) => next,
+                None => break,
+            };
+            i += 1;
+        }
+        last_match
+    }
+
+    /// Scan backward from `end`, but not past `start`, for the earliest position at which a match
+    /// ending at `end` starts.
+    #[inline]
+    fn reverse_start(&self, chars: &[char], start: usize, end: usize) -> Option<usize> {
+        let dfa = &self.reverse;
+        let mut state = dfa.start_state();
+        let mut last_match = None;
+        let mut i = end;
+        loop {
+            if dfa.is_accepting_state(state) {
+                last_match = Some(i);
+            }
+            if i == start {
+                break;
+            }
+
+            i -= 1;
+            state = match dfa.next_state(state, chars[i]) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        last_match
+    }
+
+    #[inline]
+    fn span(chars: &[char], start: usize, end: usize) -> Match {
+        Match::new(start, end, chars[start..end].iter().collect())
+    }
+}
+
+impl From<&NFA<CharClass>> for ReverseSearcher {
+    #[inline]
+    fn from(nfa: &NFA<CharClass>) -> Self {
+        Self::from_nfa(nfa, Limits::none()).expect("there are no limits to exceed")
+    }
+}
+
+/// Prefix the language of an NFA with a loop on every character, so a match may start anywhere.
+#[inline]
+fn unanchored(nfa: &NFA<CharClass>) -> NFA<CharClass> {
+    let mut unanchored = nfa.clone();
+    let start = unanchored.add_state(false);
+    unanchored.add_transitions(vec![
+        (start, Transition::Some(CharClass::all()), start),
+        (start, Transition::Epsilon, nfa.start_state),
+    ]);
+    unanchored.start_state = start;
+    unanchored
+}
+
+impl From<NFA<CharClass>> for ReverseSearcher {
+    #[inline]
+    fn from(nfa: NFA<CharClass>) -> Self {
+        (&nfa).into()
+    }
+}
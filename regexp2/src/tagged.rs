@@ -0,0 +1,606 @@
+use crate::class::CharClass;
+use crate::parser::ast::ASTParser;
+use crate::pikevm::{to_match, Captures, Inst, Program};
+use crate::regexp::{
+    find_kind_by_start, find_kind_by_start_within, Aborted, Budget, CompileResult, Engine, Match,
+    MatchKind, SearchError, UnsupportedMatchKind,
+};
+
+use std::cell::RefCell;
+use std::collections::{hash_map::Entry, HashMap};
+
+use automata::convert::Disjoin;
+use automata::limit::{Limits, SizeLimitExceeded};
+
+/// An operation on the registers of a [`TaggedDFA`], run when a transition is taken. Each
+/// operation writes one register of the destination state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    /// Set the register to the current position.
+    Set(usize),
+    /// Copy the second register of the source state into the first register.
+    Copy(usize, usize),
+    /// Mark the register as unset.
+    Clear(usize),
+}
+
+/// A tagged DFA, which finds the same matches and captures as the [`PikeVM`](crate::pikevm::PikeVM)
+/// in a single deterministic pass, in the style of Laurikari's TDFA.
+///
+/// A state of the DFA is a list of threads of the [`Program`] in priority order, as in the
+/// subset construction, except that each thread also names the register holding each of its
+/// capture slots. Threads that agree on where a slot was saved share a register, and registers
+/// are numbered by their first use, so states with the same threads and the same sharing are the
+/// same state and there are finitely many states. Each transition carries the register
+/// operations that set up the registers of its destination: the position is saved where the
+/// program saves it, and every other register is copied from the source state.
+///
+/// A second DFA, whose states drop the threads of lower priority than a thread that matched and
+/// start a new thread at each position until a match is found, finds [`MatchKind::LeftmostFirst`]
+/// matches in a single pass. Its states are only built as searches reach them.
+#[derive(Clone, Debug)]
+pub struct TaggedDFA {
+    program: Program,
+    /// Finds the longest match, as [`Engine::find_at`] does.
+    longest: Automaton,
+    /// Finds the match of highest priority.
+    first: RefCell<Automaton>,
+}
+
+#[derive(Clone, Debug)]
+struct Automaton {
+    /// Whether this automaton finds the match of highest priority: its states keep no threads
+    /// after the first thread that matched, and each transition starts a new thread until a match
+    /// is found. Its states are added and expanded as searches reach them, and are cleared
+    /// rather than limited when there are too many.
+    first: bool,
+    limits: Limits,
+    states: Vec<TaggedState>,
+    /// The key of each state.
+    keys: Vec<Key>,
+    labels: HashMap<Key, usize>,
+    /// The key of the start state, which is always the first state.
+    start: Key,
+    /// The operations that set up the registers of the start state.
+    start_ops: Vec<Op>,
+    /// The most registers used by any state.
+    registers: usize,
+}
+
+#[derive(Clone, Debug)]
+struct TaggedState {
+    /// The disjoint classes of characters leaving the state, with the destination and the
+    /// register operations of each, or `None` if the state has not been expanded yet.
+    transitions: Option<Vec<(CharClass, usize, Vec<Op>)>>,
+    /// If the state is accepting, the register holding each capture slot of the highest priority
+    /// thread that matched.
+    accept: Option<Vec<usize>>,
+}
+
+/// Where a capture slot of a thread under construction gets its value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Source {
+    /// The slot has not been saved.
+    Unset,
+    /// The slot is saved at the current position.
+    Position,
+    /// The slot is held in a register of the source state.
+    Register(usize),
+}
+
+/// A thread of a state: a program counter and the register of each capture slot.
+type Thread = (usize, Vec<usize>);
+
+/// What makes a state: its threads in priority order, and whether a match has been found at or
+/// before it.
+type Key = (Vec<Thread>, bool);
+
+impl TaggedDFA {
+    /// Compile a regular expression into a tagged DFA, within the default [`Limits`].
+    #[inline]
+    pub fn new(expr: &'_ str) -> CompileResult<'_, Self> {
+        Self::with_limits(expr, Limits::default())
+    }
+
+    /// Compile a regular expression into a tagged DFA, failing as soon as either DFA has more
+    /// states than the limit.
+    #[inline]
+    pub fn with_limits(expr: &'_ str, limits: Limits) -> CompileResult<'_, Self> {
+        let parser: ASTParser<CharClass> = ASTParser::new();
+        let ast = parser.parse(expr)?;
+        Ok(Self::from_program(&Program::new(&ast), limits)?)
+    }
+
+    #[inline]
+    fn from_program(program: &Program, limits: Limits) -> Result<Self, SizeLimitExceeded> {
+        Ok(Self {
+            program: program.clone(),
+            longest: Automaton::new(program, false, limits)?,
+            first: RefCell::new(Automaton::new(program, true, limits)?),
+        })
+    }
+
+    /// The number of states of the DFA that finds the longest match.
+    #[inline]
+    pub fn total_states(&self) -> usize {
+        self.longest.states.len()
+    }
+
+    /// The number of registers used to track the capture slots.
+    #[inline]
+    pub fn registers(&self) -> usize {
+        self.longest.registers
+    }
+
+    /// Find the longest match starting at the given character position and return the positions
+    /// of each group.
+    #[inline]
+    pub fn captures_at(&self, input: &str, start: usize) -> Option<Captures> {
+        self.longest
+            .search(input, start, false, None)
+            .unwrap_or_else(|_| unreachable!("there is no budget to run out"))
+            .map(|slots| Captures::from_slots(input, &slots))
+    }
+
+    #[inline]
+    pub fn captures(&self, input: &str) -> Option<Captures> {
+        self.captures_at(input, 0)
+    }
+
+    /// Find the leftmost-first match in a single pass of the DFA that starts a new thread at
+    /// each position.
+    #[inline]
+    fn find_first_at(
+        &self,
+        input: &str,
+        start: usize,
+        budget: Option<&Budget<'_>>,
+    ) -> Result<Option<Match>, Aborted> {
+        Ok(self
+            .first
+            .borrow_mut()
+            .search_first(&self.program, input, start, budget)?
+            .map(|slots| to_match(input, &slots)))
+    }
+}
+
+impl From<&Program> for TaggedDFA {
+    #[inline]
+    fn from(program: &Program) -> Self {
+        Self::from_program(program, Limits::none()).expect("there are no limits to exceed")
+    }
+}
+
+impl Automaton {
+    /// Build a tagged DFA by the subset construction over the threads of the program, failing as
+    /// soon as there are more states than the limit. If `first` is set, only the start state is
+    /// added.
+    #[inline]
+    fn new(program: &Program, first: bool, limits: Limits) -> Result<Self, SizeLimitExceeded> {
+        let mut automaton = Self {
+            first,
+            limits,
+            states: Vec::new(),
+            keys: Vec::new(),
+            labels: HashMap::new(),
+            start: (Vec::new(), false),
+            start_ops: Vec::new(),
+            registers: 0,
+        };
+
+        let start = automaton.closure(program, vec![(0, vec![Source::Unset; program.slots])]);
+        let (_, start_ops) = automaton.state(program, start, false)?;
+        automaton.start = automaton.keys[0].clone();
+        automaton.start_ops = start_ops;
+        if !first {
+            let mut label = 0;
+            while label < automaton.states.len() {
+                automaton.expand(program, label)?;
+                label += 1;
+            }
+        }
+        Ok(automaton)
+    }
+
+    /// Run the DFA anchored at the given character position, and return the capture slots at the
+    /// last (or first, if `shortest`) position a match was found. Each character read takes a
+    /// step from the budget, if any.
+    #[inline]
+    fn search(
+        &self,
+        input: &str,
+        start: usize,
+        shortest: bool,
+        budget: Option<&Budget<'_>>,
+    ) -> Result<Option<Vec<Option<usize>>>, Aborted> {
+        if let Some(budget) = budget {
+            budget.check()?;
+        }
+
+        let mut registers = vec![None; self.registers];
+        let mut scratch = vec![None; self.registers];
+        let mut matched = None;
+
+        Self::run_ops(&self.start_ops, &registers, &mut scratch, start);
+        std::mem::swap(&mut registers, &mut scratch);
+
+        let mut state = 0;
+        let mut chars = input.chars().skip(start);
+        let mut pos = start;
+        loop {
+            if let Some(accept) = &self.states[state].accept {
+                matched = Some(accept.iter().map(|&r| registers[r]).collect());
+                if shortest {
+                    break;
+                }
+            }
+
+            let c = match chars.next() {
+                Some(c) => c,
+                None => break,
+            };
+            if let Some(budget) = budget {
+                budget.step()?;
+            }
+            let (next, ops) = match self.states[state]
+                .transitions
+                .iter()
+                .flatten()
+                .find(|(class, _, _)| class.contains(c))
+            {
+                Some((_, next, ops)) => (*next, ops),
+                None => break,
+            };
+
+            pos += 1;
+            Self::run_ops(ops, &registers, &mut scratch, pos);
+            std::mem::swap(&mut registers, &mut scratch);
+            state = next;
+        }
+
+        Ok(matched)
+    }
+
+    /// Run the DFA that finds the match of highest priority from the given character position,
+    /// expanding the states it reaches, and return the capture slots of the match. Each
+    /// character read takes a step from the budget, if any.
+    #[inline]
+    fn search_first(
+        &mut self,
+        program: &Program,
+        input: &str,
+        start: usize,
+        budget: Option<&Budget<'_>>,
+    ) -> Result<Option<Vec<Option<usize>>>, Aborted> {
+        if let Some(budget) = budget {
+            budget.check()?;
+        }
+
+        let mut registers = vec![None; self.registers];
+        let mut scratch = vec![None; self.registers];
+        let mut matched = None;
+
+        Self::run_ops(&self.start_ops, &registers, &mut scratch, start);
+        std::mem::swap(&mut registers, &mut scratch);
+
+        let mut state = 0;
+        let mut pos = start;
+        for c in input.chars().skip(start) {
+            if let Some(accept) = &self.states[state].accept {
+                matched = Some(accept.iter().map(|&r| registers[r]).collect());
+            }
+
+            if let Some(budget) = budget {
+                budget.step()?;
+            }
+            if self.states[state].transitions.is_none() {
+                if self.limits.check_dfa_states(self.states.len() + 1).is_err() {
+                    state = self.reset(program, state);
+                }
+                self.expand(program, state)
+                    .expect("the states are cleared rather than limited");
+                registers.resize(self.registers, None);
+                scratch.resize(self.registers, None);
+            }
+
+            pos += 1;
+            let found = self.states[state]
+                .transitions
+                .iter()
+                .flatten()
+                .find(|(class, _, _)| class.contains(c));
+            match found {
+                Some((_, next, ops)) => {
+                    Self::run_ops(ops, &registers, &mut scratch, pos);
+                    state = *next;
+                }
+                // No thread reads the character, so only the thread started after it is left.
+                None if !self.keys[state].1 => {
+                    Self::run_ops(&self.start_ops, &registers, &mut scratch, pos);
+                    state = 0;
+                }
+                None => return Ok(matched),
+            }
+            std::mem::swap(&mut registers, &mut scratch);
+        }
+
+        if let Some(accept) = &self.states[state].accept {
+            matched = Some(accept.iter().map(|&r| registers[r]).collect());
+        }
+        Ok(matched)
+    }
+
+    /// Write the registers of the destination state from those of the source state.
+    #[inline]
+    fn run_ops(ops: &[Op], from: &[Option<usize>], to: &mut [Option<usize>], pos: usize) {
+        for op in ops {
+            match *op {
+                Op::Set(r) => to[r] = Some(pos),
+                Op::Copy(r, s) => to[r] = from[s],
+                Op::Clear(r) => to[r] = None,
+            }
+        }
+    }
+
+    /// Drop every state but the start state and the given state, which becomes the second state,
+    /// and return its new label.
+    fn reset(&mut self, program: &Program, label: usize) -> usize {
+        let key = self.keys[label].clone();
+        self.states.clear();
+        self.keys.clear();
+        self.labels.clear();
+        self.insert(program, self.start.clone());
+        self.insert(program, key)
+    }
+
+    /// Add the transitions out of a state. Until a match is found, the automaton that finds the
+    /// match of highest priority starts a new thread after each character, behind the others.
+    fn expand(&mut self, program: &Program, label: usize) -> Result<(), SizeLimitExceeded> {
+        let (threads, matched) = self.keys[label].clone();
+        let classes: Vec<&CharClass> = threads
+            .iter()
+            .filter_map(|&(pc, _)| match &program.insts[pc] {
+                Inst::Char(class) => Some(class),
+                _ => None,
+            })
+            .collect();
+
+        let mut transitions = Vec::new();
+        for symbol in CharClass::disjoin(classes) {
+            // The threads that read the symbol move past it, keeping their priority order.
+            let mut moved: Vec<_> = threads
+                .iter()
+                .filter(|&&(pc, _)| match &program.insts[pc] {
+                    Inst::Char(class) => Disjoin::contains(class, &symbol),
+                    _ => false,
+                })
+                .map(|(pc, regs)| (pc + 1, regs.iter().map(|&r| Source::Register(r)).collect()))
+                .collect();
+            if self.first && !matched {
+                moved.push((0, vec![Source::Unset; program.slots]));
+            }
+
+            let next = self.closure(program, moved);
+            if next.is_empty() {
+                continue;
+            }
+            let (next_label, ops) = self.state(program, next, matched)?;
+            transitions.push((symbol, next_label, ops));
+        }
+        self.states[label].transitions = Some(transitions);
+        Ok(())
+    }
+
+    /// Find the state with the given threads, adding it if it is new, where `matched` tells
+    /// whether a match was found before it. The registers are numbered by first use, and the
+    /// returned operations set them up from their sources. Fail if the new state is one more than
+    /// the limit.
+    fn state(
+        &mut self,
+        program: &Program,
+        threads: Vec<(usize, Vec<Source>)>,
+        matched: bool,
+    ) -> Result<(usize, Vec<Op>), SizeLimitExceeded> {
+        let mut numbering: HashMap<Source, usize> = HashMap::new();
+        let mut ops = Vec::new();
+        let threads: Vec<Thread> = threads
+            .into_iter()
+            .map(|(pc, sources)| {
+                let regs = sources
+                    .into_iter()
+                    .map(|source| {
+                        let next = numbering.len();
+                        *numbering.entry(source).or_insert_with(|| {
+                            ops.push(match source {
+                                Source::Unset => Op::Clear(next),
+                                Source::Position => Op::Set(next),
+                                Source::Register(r) => Op::Copy(next, r),
+                            });
+                            next
+                        })
+                    })
+                    .collect();
+                (pc, regs)
+            })
+            .collect();
+
+        let matched = self.first
+            && (matched
+                || threads
+                    .iter()
+                    .any(|&(pc, _)| matches!(program.insts[pc], Inst::Match)));
+        let label = self.insert(program, (threads, matched));
+        if !self.first {
+            self.limits.check_dfa_states(self.states.len())?;
+        }
+        Ok((label, ops))
+    }
+
+    /// Find the state with the given key, adding it unexpanded if it is new.
+    fn insert(&mut self, program: &Program, key: Key) -> usize {
+        match self.labels.entry(key) {
+            Entry::Occupied(e) => *e.get(),
+            Entry::Vacant(e) => {
+                let label = self.states.len();
+                let accept = e
+                    .key()
+                    .0
+                    .iter()
+                    .find(|&&(pc, _)| matches!(program.insts[pc], Inst::Match))
+                    .map(|(_, regs)| regs.clone());
+                self.states.push(TaggedState {
+                    transitions: None,
+                    accept,
+                });
+                let used = e.key().0.iter().flat_map(|(_, regs)| regs.iter());
+                self.registers = used.map(|&r| r + 1).fold(self.registers, usize::max);
+                self.keys.push(e.key().clone());
+                e.insert(label);
+                label
+            }
+        }
+    }
+
+    /// Follow the instructions that read no input from each thread, in priority order, as the
+    /// Pike VM does. Only threads at a [`Inst::Char`] or [`Inst::Match`] are kept, and only the
+    /// first thread to reach each instruction. If `first` is set, no threads are kept after the
+    /// first thread that matched.
+    fn closure(
+        &self,
+        program: &Program,
+        threads: Vec<(usize, Vec<Source>)>,
+    ) -> Vec<(usize, Vec<Source>)> {
+        let mut seen = vec![false; program.insts.len()];
+        let mut closure = Vec::new();
+        for (pc, mut slots) in threads {
+            let mut stack = vec![Frame::Step(pc)];
+            while let Some(frame) = stack.pop() {
+                let pc = match frame {
+                    Frame::Step(pc) => pc,
+                    Frame::Restore(slot, old) => {
+                        slots[slot] = old;
+                        continue;
+                    }
+                };
+
+                if seen[pc] {
+                    continue;
+                }
+                seen[pc] = true;
+
+                match program.insts[pc] {
+                    Inst::Jmp(next) => stack.push(Frame::Step(next)),
+                    Inst::Split(first, second) => {
+                        stack.push(Frame::Step(second));
+                        stack.push(Frame::Step(first));
+                    }
+                    Inst::Save(slot) => {
+                        stack.push(Frame::Restore(slot, slots[slot]));
+                        slots[slot] = Source::Position;
+                        stack.push(Frame::Step(pc + 1));
+                    }
+                    Inst::Char(_) => closure.push((pc, slots.clone())),
+                    Inst::Match => {
+                        closure.push((pc, slots.clone()));
+                        if self.first {
+                            return closure;
+                        }
+                    }
+                }
+            }
+        }
+        closure
+    }
+}
+
+enum Frame {
+    /// Follow the instruction at the given index.
+    Step(usize),
+    /// Restore a capture slot to its previous source.
+    Restore(usize, Source),
+}
+
+impl Engine for TaggedDFA {
+    #[inline]
+    fn is_match(&self, input: &str) -> bool {
+        let len = input.chars().count();
+        matches!(self.find_at(input, 0), Some(m) if m.end() == len)
+    }
+
+    #[inline]
+    fn find_shortest_at(&self, input: &str, start: usize) -> Option<Match> {
+        self.longest
+            .search(input, start, true, None)
+            .unwrap_or_else(|_| unreachable!("there is no budget to run out"))
+            .map(|slots| to_match(input, &slots))
+    }
+
+    #[inline]
+    fn find_at(&self, input: &str, start: usize) -> Option<Match> {
+        self.longest
+            .search(input, start, false, None)
+            .unwrap_or_else(|_| unreachable!("there is no budget to run out"))
+            .map(|slots| to_match(input, &slots))
+    }
+
+    #[inline]
+    fn is_match_within(&self, input: &str, budget: &Budget<'_>) -> Result<bool, Aborted> {
+        let len = input.chars().count();
+        Ok(matches!(self.find_at_within(input, 0, budget)?, Some(m) if m.end() == len))
+    }
+
+    #[inline]
+    fn find_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, Aborted> {
+        Ok(self
+            .longest
+            .search(input, start, false, Some(budget))?
+            .map(|slots| to_match(input, &slots)))
+    }
+
+    #[inline]
+    fn find_shortest_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, Aborted> {
+        Ok(self
+            .longest
+            .search(input, start, true, Some(budget))?
+            .map(|slots| to_match(input, &slots)))
+    }
+
+    #[inline]
+    fn find_kind_at(
+        &self,
+        input: &str,
+        start: usize,
+        kind: MatchKind,
+    ) -> Result<Option<Match>, UnsupportedMatchKind> {
+        match kind {
+            MatchKind::LeftmostFirst => Ok(self
+                .find_first_at(input, start, None)
+                .unwrap_or_else(|_| unreachable!("there is no budget to run out"))),
+            kind => find_kind_by_start(self, input, start, kind),
+        }
+    }
+
+    #[inline]
+    fn find_kind_at_within(
+        &self,
+        input: &str,
+        start: usize,
+        kind: MatchKind,
+        budget: &Budget<'_>,
+    ) -> Result<Option<Match>, SearchError> {
+        match kind {
+            MatchKind::LeftmostFirst => Ok(self.find_first_at(input, start, Some(budget))?),
+            kind => find_kind_by_start_within(self, input, start, kind, budget),
+        }
+    }
+}
@@ -1,10 +1,17 @@
 #[allow(unused_macros)]
-
 macro_rules! run_tests {
     ($exprs:expr, $valids:expr, $invalids:expr) => {{
         $exprs.iter().for_each(|&expr| {
             let nfa_re = RegExp::new_nfa(expr).unwrap();
+            let glushkov_re = RegExp::new_glushkov(expr).unwrap();
             let dfa_re = RegExp::new(expr).unwrap();
+            let dense_re = RegExp::new_dense(expr).unwrap();
+            let lazy_re = RegExp::new_lazy(expr).unwrap();
+            let pikevm_re = RegExp::new_pikevm(expr).unwrap();
+            let tagged_re = RegExp::new_tagged(expr).unwrap();
+            let backtracker_re = RegExp::new_backtracker(expr).unwrap();
+            let derivative_re = RegExp::new_derivative(expr).unwrap();
+            let derivative_dfa_re = RegExp::new_derivative(expr).unwrap().with_dfa();
             $valids.iter().for_each(|s| {
                 assert!(
                     nfa_re.is_match(s),
@@ -13,12 +20,68 @@ macro_rules! run_tests {
                     s
                 );
 
+                assert!(
+                    glushkov_re.is_match(s),
+                    r#""{}" failed to match "{}" using glushkov nfa"#,
+                    expr,
+                    s
+                );
+
                 assert!(
                     dfa_re.is_match(s),
                     r#""{}" failed to match "{}" using dfa"#,
                     expr,
                     s
                 );
+
+                assert!(
+                    dense_re.is_match(s),
+                    r#""{}" failed to match "{}" using dense dfa"#,
+                    expr,
+                    s
+                );
+
+                assert!(
+                    lazy_re.is_match(s),
+                    r#""{}" failed to match "{}" using lazy dfa"#,
+                    expr,
+                    s
+                );
+
+                assert!(
+                    pikevm_re.is_match(s),
+                    r#""{}" failed to match "{}" using pike vm"#,
+                    expr,
+                    s
+                );
+
+                assert!(
+                    tagged_re.is_match(s),
+                    r#""{}" failed to match "{}" using tagged dfa"#,
+                    expr,
+                    s
+                );
+
+                assert!(
+                    backtracker_re.is_match(s),
+                    r#""{}" failed to match "{}" using backtracker"#,
+                    expr,
+                    s
+                );
+
+                assert!(
+                    derivative_re.is_match(s),
+                    r#""{}" failed to match "{}" using derivatives"#,
+                    expr,
+                    s
+                );
+
+                assert!(
+                    derivative_dfa_re.is_match(s),
+                    r#""{}" failed to match "{}" using derivative dfa"#,
+                    expr,
+                    s
+                );
             });
             $invalids.iter().for_each(|s| {
                 assert_eq!(
@@ -28,6 +91,13 @@ macro_rules! run_tests {
                     expr,
                     s
                 );
+                assert_eq!(
+                    glushkov_re.is_match(s),
+                    false,
+                    r#""{}" matched "{}" using glushkov nfa"#,
+                    expr,
+                    s
+                );
                 assert_eq!(
                     dfa_re.is_match(s),
                     false,
@@ -35,7 +105,119 @@ macro_rules! run_tests {
                     expr,
                     s
                 );
+                assert_eq!(
+                    dense_re.is_match(s),
+                    false,
+                    r#""{}" matched "{}" using dense dfa"#,
+                    expr,
+                    s
+                );
+                assert_eq!(
+                    lazy_re.is_match(s),
+                    false,
+                    r#""{}" matched "{}" using lazy dfa"#,
+                    expr,
+                    s
+                );
+                assert_eq!(
+                    pikevm_re.is_match(s),
+                    false,
+                    r#""{}" matched "{}" using pike vm"#,
+                    expr,
+                    s
+                );
+                assert_eq!(
+                    tagged_re.is_match(s),
+                    false,
+                    r#""{}" matched "{}" using tagged dfa"#,
+                    expr,
+                    s
+                );
+                assert_eq!(
+                    backtracker_re.is_match(s),
+                    false,
+                    r#""{}" matched "{}" using backtracker"#,
+                    expr,
+                    s
+                );
+                assert_eq!(
+                    derivative_re.is_match(s),
+                    false,
+                    r#""{}" matched "{}" using derivatives"#,
+                    expr,
+                    s
+                );
+                assert_eq!(
+                    derivative_dfa_re.is_match(s),
+                    false,
+                    r#""{}" matched "{}" using derivative dfa"#,
+                    expr,
+                    s
+                );
+            });
+        })
+    }};
+}
+
+#[allow(unused_macros)]
+macro_rules! assert_finds_agree {
+    ($exprs:expr, $inputs:expr, $compile:expr) => {{
+        $exprs.iter().for_each(|&expr| {
+            let dfa_re = RegExp::new(expr).unwrap();
+            let re = ($compile)(expr).unwrap();
+            $inputs.iter().for_each(|&input| {
+                for start in 0..=input.chars().count() {
+                    assert_eq!(
+                        dfa_re.find_at(input, start).map(|m| m.range()),
+                        re.find_at(input, start).map(|m| m.range()),
+                        r#""{}" on "{}" at {}"#,
+                        expr,
+                        input,
+                        start
+                    );
+                    assert_eq!(
+                        dfa_re.find_shortest_at(input, start).map(|m| m.range()),
+                        re.find_shortest_at(input, start).map(|m| m.range()),
+                        r#""{}" shortest on "{}" at {}"#,
+                        expr,
+                        input,
+                        start
+                    );
+                }
             });
         })
     }};
 }
+
+/// Parse an expression into an NFA.
+#[allow(dead_code)]
+fn compile_nfa(expr: &str) -> regexp2::automata::NFA<regexp2::class::CharClass> {
+    regexp2::parser::nfa::NFAParser::new().parse(expr).unwrap()
+}
+
+/// Parse an expression into an NFA and convert it into a DFA.
+#[allow(dead_code)]
+fn compile(expr: &str) -> regexp2::automata::DFA<regexp2::class::CharClass> {
+    compile_nfa(expr).into()
+}
+
+/// All strings over the given alphabet up to the given length.
+#[allow(dead_code)]
+fn strings(alphabet: &[char], max_len: usize) -> Vec<String> {
+    let mut all = vec![String::new()];
+    let mut last = vec![String::new()];
+    for _ in 0..max_len {
+        last = last
+            .iter()
+            .flat_map(|s| {
+                alphabet.iter().map(move |c| {
+                    let mut s = s.clone();
+                    s.push(*c);
+                    s
+                })
+            })
+            .collect();
+        all.extend(last.iter().cloned());
+    }
+    all
+}
@@ -0,0 +1,41 @@
+use regexp2::backtrack::BoundedBacktracker;
+use regexp2::{Engine, RegExp};
+
+include!("macros.rs");
+
+#[test]
+fn test_size_check() {
+    let nfa = compile_nfa("(a|b)*abb");
+    let states = nfa.total_states;
+
+    let backtracker = BoundedBacktracker::with_capacity(nfa, states * 11);
+    assert_eq!(10, backtracker.max_input_len());
+    assert!(backtracker.can_search(10));
+    assert!(!backtracker.can_search(11));
+
+    // Inputs too long to backtrack over fall back to NFA simulation.
+    assert!(backtracker.is_match("abababbabb"));
+    assert!(backtracker.is_match("abababababbabb"));
+    assert!(!backtracker.is_match("abababababbab"));
+    assert_eq!(
+        0..14,
+        backtracker.find_at("abababababbabb", 0).unwrap().range()
+    );
+}
+
+#[test]
+fn test_backtracker_find() {
+    let exprs = [
+        "(a|b)*abb",
+        "a*",
+        "ab|abc",
+        r"\d+\w?",
+        "[^a]*b",
+        "(a|ab)(c|bcd)",
+    ];
+    let inputs = [
+        "", "a", "abb", "aababbab", "abcd", "aaa", "08m", "999_x", "xyzb", "abcdx",
+    ];
+
+    assert_finds_agree!(&exprs, &inputs, RegExp::new_backtracker);
+}
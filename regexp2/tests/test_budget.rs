@@ -0,0 +1,192 @@
+use regexp2::{Aborted, Budget, Engine, MatchKind, RegExp, SearchError, UnsupportedMatchKind};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+const EXPRS: [&str; 4] = ["(a|b)*abb", "a*", r"\d+\w?", "x(a|ab)*y?"];
+
+const INPUTS: [&str; 5] = ["", "abb", "aababb", "z08m", "xababy"];
+
+fn check_engine<E: Engine>(re: &RegExp<E>) {
+    for input in &INPUTS {
+        let len = input.chars().count();
+
+        // A budget as long as the input is enough for any search.
+        let budget = Budget::steps(len);
+        assert_eq!(Ok(re.is_match(input)), re.is_match_within(input, &budget));
+        let budget = Budget::steps(len);
+        let found = re.find_within(input, &budget).unwrap();
+        assert_eq!(
+            re.find(input).map(|m| m.range()),
+            found.map(|m| m.range()),
+            "{} on {:?}",
+            re.as_str(),
+            input
+        );
+        let budget = Budget::steps(len);
+        let found = re.find_shortest_within(input, &budget).unwrap();
+        assert_eq!(
+            re.find_shortest(input).map(|m| m.range()),
+            found.map(|m| m.range())
+        );
+
+        // A search that reads the whole input cannot do it with fewer steps.
+        if len > 0 && re.is_match(input) {
+            let budget = Budget::steps(len - 1);
+            assert_eq!(Err(Aborted::OutOfSteps), re.is_match_within(input, &budget));
+        }
+
+        // A longest match search reads at least one character, so it notices the flag.
+        if len > 0 {
+            let cancel = AtomicBool::new(true);
+            let budget = Budget::cancellable(&cancel);
+            assert_eq!(
+                Aborted::Cancelled,
+                re.find_within(input, &budget).unwrap_err()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_budget_engines() {
+    for expr in &EXPRS {
+        check_engine(&RegExp::new(expr).unwrap());
+        check_engine(&RegExp::new_nfa(expr).unwrap());
+        check_engine(&RegExp::new_glushkov(expr).unwrap());
+        check_engine(&RegExp::new_dense(expr).unwrap());
+        check_engine(&RegExp::new_lazy(expr).unwrap());
+        check_engine(&RegExp::new_backtracker(expr).unwrap());
+        check_engine(&RegExp::new_derivative(expr).unwrap());
+        check_engine(&RegExp::new_pikevm(expr).unwrap());
+        check_engine(&RegExp::new_tagged(expr).unwrap());
+    }
+}
+
+#[test]
+fn test_budget_stops_early() {
+    let re = RegExp::new("a*b").unwrap();
+    let input = "a".repeat(10_000);
+
+    // The DFA engines stop as soon as the budget runs out, however long the input.
+    let budget = Budget::steps(100);
+    assert_eq!(
+        Aborted::OutOfSteps,
+        re.find_within(&input, &budget).unwrap_err()
+    );
+    assert_eq!(Some(0), budget.remaining());
+
+    let dense = RegExp::new_dense("a*b").unwrap();
+    let budget = Budget::steps(100);
+    assert_eq!(
+        Err(Aborted::OutOfSteps),
+        dense.is_match_within(&input, &budget)
+    );
+    assert_eq!(Some(0), budget.remaining());
+
+    // So do the engines that simulate the automaton or build it as they go.
+    check_stops_early(&RegExp::new_pikevm("a*b").unwrap(), &input);
+    check_stops_early(&RegExp::new_backtracker("a*b").unwrap(), &input);
+    check_stops_early(&RegExp::new_lazy("a*b").unwrap(), &input);
+    check_stops_early(&RegExp::new_tagged("a*b").unwrap(), &input);
+    check_stops_early(&RegExp::new_derivative("a*b").unwrap(), &input);
+}
+
+fn check_stops_early<E: Engine>(re: &RegExp<E>, input: &str) {
+    let budget = Budget::steps(100);
+    assert_eq!(
+        Aborted::OutOfSteps,
+        re.find_within(input, &budget).unwrap_err()
+    );
+    assert_eq!(Some(0), budget.remaining());
+}
+
+#[test]
+fn test_find_kind_within() {
+    let kinds = [
+        MatchKind::Earliest,
+        MatchKind::LeftmostLongest,
+        MatchKind::LeftmostFirst,
+    ];
+    let pikevm = RegExp::new_pikevm("(a|ab)(c|bcd)").unwrap();
+    let tagged = RegExp::new_tagged("(a|ab)(c|bcd)").unwrap();
+    for &kind in &kinds {
+        for input in &["abcd", "xxabcd", "abc"] {
+            let expected = pikevm.find_kind_at(input, 0, kind).unwrap();
+            let expected = expected.map(|m| m.range());
+            let budget = Budget::unlimited();
+            let found = pikevm.find_kind_at_within(input, 0, kind, &budget).unwrap();
+            assert_eq!(
+                expected,
+                found.map(|m| m.range()),
+                "{:?} on {:?}",
+                kind,
+                input
+            );
+            let budget = Budget::unlimited();
+            let found = tagged.find_kind_at_within(input, 0, kind, &budget).unwrap();
+            assert_eq!(
+                expected,
+                found.map(|m| m.range()),
+                "{:?} on {:?}",
+                kind,
+                input
+            );
+
+            let budget = Budget::steps(1);
+            assert_eq!(
+                SearchError::Aborted(Aborted::OutOfSteps),
+                pikevm
+                    .find_kind_at_within(input, 0, kind, &budget)
+                    .unwrap_err()
+            );
+        }
+    }
+
+    // Engines that only find the longest match refuse before they take any step.
+    let dfa = RegExp::new("(a|ab)(c|bcd)").unwrap();
+    let budget = Budget::steps(0);
+    assert_eq!(
+        SearchError::Unsupported(UnsupportedMatchKind(MatchKind::LeftmostFirst)),
+        dfa.find_kind_at_within("abcd", 0, MatchKind::LeftmostFirst, &budget)
+            .unwrap_err()
+    );
+    let budget = Budget::steps(4);
+    assert_eq!(
+        Some(0..4),
+        dfa.find_kind_at_within("abcd", 0, MatchKind::LeftmostLongest, &budget)
+            .unwrap()
+            .map(|m| m.range())
+    );
+}
+
+#[test]
+fn test_cancel_from_another_thread() {
+    let re = RegExp::new_nfa("(a|b)*abb").unwrap();
+    let input = "ab".repeat(1000);
+    let cancel = Arc::new(AtomicBool::new(false));
+
+    let canceller = {
+        let cancel = Arc::clone(&cancel);
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            cancel.store(true, Ordering::Relaxed);
+        })
+    };
+
+    // Search until the flag is noticed; a cancelled budget stays cancelled.
+    let budget = Budget::cancellable(&cancel);
+    let aborted = loop {
+        if let Err(aborted) = re.is_match_within(&input, &budget) {
+            break aborted;
+        }
+    };
+    assert_eq!(Aborted::Cancelled, aborted);
+    assert_eq!(
+        Aborted::Cancelled,
+        re.find_within("abb", &budget).unwrap_err()
+    );
+    canceller.join().unwrap();
+}
@@ -0,0 +1,86 @@
+use regexp2::ast::Expr;
+use regexp2::class::CharClass;
+use regexp2::parser::ast::ASTParserEngine;
+use regexp2::parser::ParserState;
+use regexp2::RegExp;
+
+include!("macros.rs");
+
+#[test]
+fn test_captures() {
+    let re = RegExp::new_pikevm("(a|b)*(abb)").unwrap();
+    let caps = re.captures("aababb").unwrap();
+    assert_eq!(3, caps.len());
+    assert_eq!(0..6, caps.get(0).unwrap().range());
+    assert_eq!("aababb", caps.get(0).unwrap().span);
+    // The last iteration of a repeated group is reported.
+    assert_eq!(2..3, caps.get(1).unwrap().range());
+    assert_eq!(3..6, caps.get(2).unwrap().range());
+    assert_eq!("abb", caps.get(2).unwrap().span);
+
+    // Groups are numbered by their opening parentheses.
+    let re = RegExp::new_pikevm(r"((\d+)-(\d+))x?").unwrap();
+    let caps = re.captures("12-345x").unwrap();
+    assert_eq!(4, caps.len());
+    assert_eq!("12-345", caps.get(1).unwrap().span);
+    assert_eq!("12", caps.get(2).unwrap().span);
+    assert_eq!("345", caps.get(3).unwrap().span);
+
+    assert!(re.captures("-345").is_none());
+}
+
+#[test]
+fn test_captures_optional_groups() {
+    let re = RegExp::new_pikevm("a(b)?(c)?").unwrap();
+
+    let caps = re.captures("ac").unwrap();
+    assert_eq!(0..2, caps.get(0).unwrap().range());
+    assert!(caps.get(1).is_none());
+    assert_eq!(1..2, caps.get(2).unwrap().range());
+
+    let caps = re.captures_at("xabc", 1).unwrap();
+    assert_eq!(1..4, caps.get(0).unwrap().range());
+    assert_eq!(2..3, caps.get(1).unwrap().range());
+    assert_eq!(3..4, caps.get(2).unwrap().range());
+}
+
+#[test]
+fn test_captures_priority() {
+    // Both alternatives match the same text; the first one has priority.
+    let re = RegExp::new_pikevm("(a)b|a(b)").unwrap();
+    let caps = re.captures("ab").unwrap();
+    assert_eq!(0..1, caps.get(1).unwrap().range());
+    assert!(caps.get(2).is_none());
+
+    // A lower priority alternative still produces a longer match.
+    let re = RegExp::new_pikevm("(a)|(ab)").unwrap();
+    let caps = re.captures("ab").unwrap();
+    assert!(caps.get(1).is_none());
+    assert_eq!(0..2, caps.get(2).unwrap().range());
+
+    // Nested stars that can match the empty string do not loop forever.
+    let re = RegExp::new_pikevm("((a*)*)b").unwrap();
+    assert_eq!(0..4, re.find("aaab").unwrap().range());
+}
+
+#[test]
+fn test_pikevm_find() {
+    let exprs = ["(a|b)*abb", "a*", "ab|abc", r"\d+\w?", "[^a]*b"];
+    let inputs = [
+        "", "a", "abb", "aababbab", "abcd", "aaa", "08m", "999_x", "xyzb",
+    ];
+
+    assert_finds_agree!(&exprs, &inputs, RegExp::new_pikevm);
+}
+
+#[test]
+fn test_group_numbers_restart() {
+    // A parser state numbers the groups of every expression it parses from one.
+    let mut state: ParserState<ASTParserEngine<CharClass>> = ParserState::new();
+    for _ in 0..2 {
+        match state.parse("(a)").unwrap() {
+            Expr::Group(index, _) => assert_eq!(1, index),
+            expr => panic!("expected a group, got {:?}", expr),
+        }
+    }
+}
@@ -116,3 +116,24 @@ fn test_special_classes() {
     let invalids = ["", "a", "A", "5", "_"];
     run_tests!(&exprs, &valids, &invalids);
 }
+
+#[test]
+fn test_complement_around_surrogates() {
+    use regexp2::class::{CharClass, CharRange};
+
+    // Ranges that end just before or start just after the surrogate code points.
+    let low: CharClass = CharRange::new('a', '\u{d7ff}').into();
+    let complement = low.complement();
+    assert!(complement.contains('\u{e000}'));
+    assert!(complement.contains('A'));
+    assert!(!complement.contains('\u{d7ff}'));
+
+    let high: CharClass = CharRange::new('\u{e000}', '\u{e0ff}').into();
+    let complement = high.complement();
+    assert!(complement.contains('\u{d7ff}'));
+    assert!(!complement.contains('\u{e000}'));
+
+    let all = CharClass::all();
+    assert!(all.contains('\0') && all.contains('\u{10ffff}'));
+    assert!(all.complement().is_empty());
+}
@@ -0,0 +1,57 @@
+use regexp2::class::{CharClass, CharRange};
+use regexp2::dense::CharClasses;
+use regexp2::RegExp;
+
+include!("macros.rs");
+
+#[test]
+fn test_char_classes() {
+    let a: CharClass = CharRange::new('a', 'z').into();
+    let b: CharClass = CharRange::new('m', 'p').into();
+    let classes = CharClasses::new(&[&a, &b]);
+
+    // [a-l] and [q-z] are only in the first class, so they are equivalent.
+    assert_eq!(classes.get('a'), classes.get('l'));
+    assert_eq!(classes.get('a'), classes.get('z'));
+    assert_eq!(classes.get('m'), classes.get('p'));
+    assert_ne!(classes.get('a'), classes.get('m'));
+
+    // Everything outside both classes is in one class.
+    assert_eq!(classes.get('A'), classes.get('何'));
+    assert_eq!(classes.get('\0'), classes.get('\u{10ffff}'));
+    assert_ne!(classes.get('A'), classes.get('a'));
+
+    assert_eq!(3, classes.len());
+}
+
+#[test]
+fn test_dense_find() {
+    let exprs = ["(a|b)*abb", "a*", "ab|abc", r"\d+\w?", "[^a]*b"];
+    let inputs = [
+        "", "a", "abb", "aababbab", "abcd", "aaa", "08m", "999_x", "xyzb", "bbb",
+    ];
+
+    assert_finds_agree!(&exprs, &inputs, RegExp::new_dense);
+}
+
+#[test]
+fn test_find_positions() {
+    let re = RegExp::new_dense("ab|abc").unwrap();
+
+    let m = re.find("abcd").unwrap();
+    assert_eq!(0..3, m.range());
+    assert_eq!("abc", m.span);
+
+    let m = re.find_shortest("abcd").unwrap();
+    assert_eq!(0..2, m.range());
+    assert_eq!("ab", m.span);
+
+    let m = re.find_at("xabc", 1).unwrap();
+    assert_eq!(1..4, m.range());
+    assert_eq!("abc", m.span);
+    assert!(re.find("xabc").is_none());
+
+    // Empty matches end where they start.
+    let re = RegExp::new_dense("a*").unwrap();
+    assert_eq!(2..2, re.find_at("bb", 2).unwrap().range());
+}
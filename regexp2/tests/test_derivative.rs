@@ -0,0 +1,176 @@
+use regexp2::ast::Expr;
+use regexp2::class::CharClass;
+use regexp2::parser::ast::ASTParser;
+use regexp2::RegExp;
+
+fn parse(expr: &str) -> Expr {
+    let parser: ASTParser<CharClass> = ASTParser::new();
+    parser.parse(expr).unwrap().without_groups()
+}
+
+fn atom(c: char) -> Expr {
+    Expr::atom(c.into())
+}
+
+#[test]
+fn test_smart_constructors() {
+    let a = atom('a');
+    let b = atom('b');
+
+    assert_eq!(Expr::atom(CharClass::new()), Expr::Empty);
+    assert_eq!(Expr::concat(Expr::Epsilon, a.clone()), a);
+    assert_eq!(Expr::concat(a.clone(), Expr::Empty), Expr::Empty);
+    assert_eq!(Expr::alternate(a.clone(), Expr::Empty), a);
+    assert_eq!(Expr::alternate(a.clone(), a.clone()), a);
+    assert_eq!(
+        Expr::alternate(a.clone(), b.clone()),
+        Expr::atom(CharClass::from(vec!['a', 'b']))
+    );
+    assert_eq!(Expr::star(Expr::star(a.clone())), Expr::star(a.clone()));
+    assert_eq!(Expr::star(Expr::Empty), Expr::Epsilon);
+    assert_eq!(Expr::complement(Expr::complement(a.clone())), a);
+    assert_eq!(Expr::intersect(a.clone(), Expr::everything()), a);
+    assert_eq!(Expr::intersect(a.clone(), Expr::Empty), Expr::Empty);
+
+    // Alternation is associative, commutative and idempotent.
+    let x = Expr::star(a.clone());
+    let y = Expr::concat(a.clone(), b.clone());
+    let z = Expr::Epsilon;
+    let xyz = Expr::alternate(x.clone(), Expr::alternate(y.clone(), z.clone()));
+    let zyx = Expr::alternate(Expr::alternate(z.clone(), y.clone()), x.clone());
+    assert_eq!(xyz, zyx);
+    assert_eq!(Expr::alternate(xyz.clone(), y), xyz);
+
+    // Concatenation nests to the right.
+    let ab_c = Expr::concat(Expr::concat(a.clone(), b.clone()), atom('c'));
+    let a_bc = Expr::concat(a, Expr::concat(b, atom('c')));
+    assert_eq!(ab_c, a_bc);
+}
+
+#[test]
+fn test_derivative() {
+    let expr = parse("abc");
+    assert_eq!(expr.derivative('a'), parse("bc"));
+    assert_eq!(expr.derivative('b'), Expr::Empty);
+    assert_eq!(
+        expr.derivative('a').derivative('b').derivative('c'),
+        Expr::Epsilon
+    );
+
+    let expr = parse("(ab)*");
+    assert!(expr.is_nullable());
+    assert_eq!(expr.derivative('a'), Expr::concat(atom('b'), expr.clone()));
+
+    let expr = parse("a?b");
+    assert!(!expr.is_nullable());
+    assert_eq!(expr.derivative('a'), atom('b'));
+    assert_eq!(expr.derivative('b'), Expr::Epsilon);
+
+    // Derivatives of a loop repeat once normalized.
+    let expr = parse("a+");
+    let derivative = expr.derivative('a');
+    assert!(derivative.is_nullable());
+    assert_eq!(derivative.derivative('a'), derivative);
+}
+
+#[test]
+fn test_derivative_classes() {
+    let classes = parse("[a-c]x|[b-d]y").derivative_classes();
+    for c in "abcdxyz".chars() {
+        assert_eq!(
+            classes.iter().filter(|class| class.contains(c)).count(),
+            1,
+            "{}",
+            c
+        );
+    }
+}
+
+#[test]
+fn test_dfa_equivalent() {
+    let exprs = [
+        "abc",
+        "(a|b)*abb",
+        "a?b+c*",
+        "(ab|a)(bc|c)",
+        "[a-z]+[0-9]?",
+        "((a*)*b)*",
+        "(x|y|z)(x|y|z)?",
+    ];
+    for expr in exprs.iter() {
+        let re = RegExp::new(expr).unwrap();
+        let derivative_re = RegExp::new_derivative(expr).unwrap().with_dfa();
+        assert_eq!(re.equivalent(&derivative_re), Ok(()), "{}", expr);
+    }
+}
+
+#[test]
+fn test_dfa_states() {
+    // Every derivative is a distinct state, so the DFA is already small before minimization.
+    let re = RegExp::new_derivative("(a|b)*a(a|b)(a|b)(a|b)").unwrap();
+    let dfa = re.with_dfa();
+    let minimal = RegExp::new("(a|b)*a(a|b)(a|b)(a|b)").unwrap();
+    assert_eq!(dfa.equivalent(&minimal), Ok(()));
+}
+
+#[test]
+fn test_intersection_and_complement() {
+    let pairs = [
+        ("(a|b)*", "(a|b)*abb"),
+        ("a*", "(aa)*"),
+        ("[a-z]+", "x[0-9]*"),
+    ];
+    for (e1, e2) in pairs.iter() {
+        let (d1, d2) = (
+            RegExp::new_derivative(e1).unwrap(),
+            RegExp::new_derivative(e2).unwrap(),
+        );
+        let (r1, r2) = (RegExp::new(e1).unwrap(), RegExp::new(e2).unwrap());
+
+        let intersection = d1.intersection(&d2);
+        assert_eq!(intersection.pattern(), None);
+        assert_eq!(
+            intersection.with_dfa().equivalent(&r1.intersection(&r2)),
+            Ok(()),
+            "{} & {}",
+            e1,
+            e2
+        );
+
+        assert_eq!(
+            d1.complement().with_dfa().equivalent(&r1.complement()),
+            Ok(()),
+            "~{}",
+            e1
+        );
+    }
+
+    let even = RegExp::new_derivative("(aa)*").unwrap();
+    let odd = even
+        .complement()
+        .intersection(&RegExp::new_derivative("a*").unwrap());
+    assert!(odd.is_match("a"));
+    assert!(odd.is_match("aaa"));
+    assert!(!odd.is_match(""));
+    assert!(!odd.is_match("aa"));
+    assert!(!odd.is_match("b"));
+}
+
+#[test]
+fn test_find() {
+    let exprs = ["a+", "ab|abcd", "(ab)*", "x?"];
+    let inputs = ["", "a", "aaab", "abcd", "ababx", "xx"];
+    for expr in exprs.iter() {
+        let re = RegExp::new(expr).unwrap();
+        let derivative_re = RegExp::new_derivative(expr).unwrap();
+        for input in inputs.iter() {
+            let expected = re.find(input).map(|m| m.range());
+            let actual = derivative_re.find(input).map(|m| m.range());
+            assert_eq!(actual, expected, "{} on {}", expr, input);
+
+            let expected = re.find_shortest(input).map(|m| m.range());
+            let actual = derivative_re.find_shortest(input).map(|m| m.range());
+            assert_eq!(actual, expected, "{} on {}", expr, input);
+        }
+    }
+}
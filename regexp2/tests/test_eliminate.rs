@@ -0,0 +1,143 @@
+use regexp2::ast::Expr;
+use regexp2::automata::NFA;
+use regexp2::class::CharClass;
+use regexp2::parser::nfa::NFAParser;
+use regexp2::RegExp;
+
+/// Convert the minimal DFA of the expression back to a pattern, and check that the pattern
+/// parses to an equivalent regular expression.
+fn round_trip(expr: &str) -> String {
+    let re = RegExp::new(expr).unwrap();
+    let pattern = re.to_pattern();
+    let back = RegExp::new(&pattern).expect(&pattern);
+    assert_eq!(re.equivalent(&back), Ok(()), "{} became {}", expr, pattern);
+    pattern
+}
+
+#[test]
+fn test_round_trip() {
+    for expr in &[
+        "a",
+        "abc",
+        "a|b|c",
+        "a*",
+        "a+",
+        "a?",
+        "(ab)*",
+        "(a|b)*abb",
+        "(a|b)*a(a|b)(a|b)",
+        "a(b|c)*d",
+        r"\d+(\.\d+)?",
+        "[^a-z]*x",
+        "(a*b*)*c",
+        "((ab|c)*d)+",
+    ] {
+        round_trip(expr);
+    }
+}
+
+#[test]
+fn test_readable() {
+    assert_eq!(round_trip("a"), "a");
+    assert_eq!(round_trip("abc"), "abc");
+    assert_eq!(round_trip("a|b|c"), "[a-c]");
+    assert_eq!(round_trip("a*"), "a*");
+    assert_eq!(round_trip("a+"), "a+");
+    assert_eq!(round_trip("ab?"), "ab?");
+    assert_eq!(round_trip("(ab)*"), "(ab)*");
+    assert_eq!(round_trip("a(b|c)*d"), "a[bc]*d");
+}
+
+#[test]
+fn test_escapes() {
+    assert_eq!(round_trip(r"\.\*\+\?\|\(\)\[\]\\"), r"\.\*\+\?\|\(\)\[\]\\");
+    assert_eq!(round_trip(r"[\-\]\^]"), r"[\-\]\^]");
+    assert_eq!(round_trip(r"\n"), r"\n");
+    assert_eq!(round_trip("."), ".");
+    assert_eq!(round_trip("[^a]"), "[^a]");
+    assert_eq!(round_trip(r"[\s\S]"), r"[\s\S]");
+}
+
+#[test]
+fn test_product_to_pattern() {
+    let ab = RegExp::new("(a|b)*").unwrap();
+    let ends_with_b = RegExp::new("(a|b)*b").unwrap();
+    let a_star = RegExp::new("a*").unwrap();
+
+    let difference = ab.difference(&a_star);
+    let pattern = difference.to_pattern();
+    let back = RegExp::new(&pattern).unwrap();
+    assert_eq!(difference.equivalent(&back), Ok(()), "{}", pattern);
+
+    let intersection = ends_with_b.intersection(&RegExp::new("a(a|b)*").unwrap());
+    let pattern = intersection.to_pattern();
+    let back = RegExp::new(&pattern).unwrap();
+    assert_eq!(intersection.equivalent(&back), Ok(()), "{}", pattern);
+
+    // Every product, including those of the empty language or the empty string alone, has a
+    // pattern the parser accepts.
+    let pairs = [("a", "b"), ("a?", "b?"), ("a*", "(aa)*"), ("(a|b)*b", "a+")];
+    for (e1, e2) in pairs.iter() {
+        let (r1, r2) = (RegExp::new(e1).unwrap(), RegExp::new(e2).unwrap());
+        for re in &[
+            r1.intersection(&r2),
+            r1.union(&r2),
+            r1.difference(&r2),
+            r1.symmetric_difference(&r2),
+            r1.complement(),
+        ] {
+            let pattern = re.to_pattern();
+            let back = RegExp::new(&pattern).expect(&pattern);
+            assert_eq!(re.equivalent(&back), Ok(()), "{}", pattern);
+        }
+    }
+}
+
+#[test]
+fn test_from_nfa() {
+    for expr in &["a", "(a|b)*abb", "a?b*c+", "(ab|a)(bc|c)"] {
+        let nfa: NFA<CharClass> = NFAParser::new().parse(expr).unwrap();
+        let pattern = Expr::from_nfa(&nfa).to_string();
+        let back = RegExp::new(&pattern).unwrap();
+        assert_eq!(
+            RegExp::new(expr).unwrap().equivalent(&back),
+            Ok(()),
+            "{} became {}",
+            expr,
+            pattern
+        );
+    }
+}
+
+#[test]
+fn test_empty_languages() {
+    assert_eq!(Expr::Empty.to_string(), r"[^\s\S]");
+    assert_eq!(Expr::Epsilon.to_string(), r"[^\s\S]*");
+
+    // The patterns of the empty language and of the empty string parse back to them.
+    let nothing = RegExp::new("a")
+        .unwrap()
+        .intersection(&RegExp::new("b").unwrap());
+    let pattern = nothing.to_pattern();
+    assert_eq!(pattern, r"[^\s\S]");
+    let back = RegExp::new(&pattern).unwrap();
+    assert_eq!(nothing.equivalent(&back), Ok(()));
+    assert!(!back.is_match(""));
+
+    let empty_string = RegExp::new("a?")
+        .unwrap()
+        .intersection(&RegExp::new("b?").unwrap());
+    let pattern = empty_string.to_pattern();
+    assert_eq!(pattern, r"[^\s\S]*");
+    let back = RegExp::new(&pattern).unwrap();
+    assert_eq!(empty_string.equivalent(&back), Ok(()));
+    assert!(back.is_match(""));
+    assert!(!back.is_match("a"));
+
+    // The empty string may be an alternative of a larger expression.
+    let a_or_empty = RegExp::new("a?b*")
+        .unwrap()
+        .intersection(&RegExp::new("a*").unwrap());
+    let back = RegExp::new(&a_or_empty.to_pattern()).unwrap();
+    assert_eq!(a_or_empty.equivalent(&back), Ok(()));
+}
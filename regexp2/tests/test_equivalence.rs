@@ -0,0 +1,122 @@
+use regexp2::automata::DFA;
+use regexp2::class::CharClass;
+use regexp2::RegExp;
+
+include!("macros.rs");
+
+#[test]
+fn test_is_empty() {
+    assert!(DFA::<CharClass>::new().is_empty());
+    assert!(!compile("a").is_empty());
+    assert!(!compile("a*").is_empty());
+    assert!(compile("a").intersection(&compile("b")).is_empty());
+    assert!(compile("(a|b)*").difference(&compile("(a*b*)*")).is_empty());
+    assert!(!compile("(a|b)*").difference(&compile("a*")).is_empty());
+}
+
+#[test]
+fn test_shortest_accepted() {
+    assert_eq!(compile("a*").shortest_accepted(), Some(vec![]));
+    assert_eq!(
+        compile("abc|ab*").shortest_accepted(),
+        Some(vec![CharClass::from('a')])
+    );
+    assert_eq!(DFA::<CharClass>::new().shortest_accepted(), None);
+}
+
+#[test]
+fn test_is_subset_of() {
+    let ab_star = compile("(ab)*");
+    let any = compile("(a|b)*");
+
+    assert_eq!(ab_star.is_subset_of(&any), Ok(()));
+
+    let counterexample = any.is_subset_of(&ab_star).unwrap_err();
+    assert_eq!(counterexample.len(), 1);
+}
+
+#[test]
+fn test_equivalent() {
+    let equivalent = [
+        ("(a|b)*", "(a*b*)*"),
+        ("a(ba)*", "(ab)*a"),
+        ("a+", "aa*"),
+        ("(a|b)c", "ac|bc"),
+        ("[a-c]", "a|b|c"),
+    ];
+    for (e1, e2) in equivalent.iter() {
+        let (re1, re2) = (RegExp::new(e1).unwrap(), RegExp::new(e2).unwrap());
+        assert_eq!(re1.equivalent(&re2), Ok(()), "{} and {}", e1, e2);
+        assert_eq!(re2.equivalent(&re1), Ok(()), "{} and {}", e2, e1);
+    }
+}
+
+#[test]
+fn test_counterexample() {
+    let cases = [
+        ("a*", "a+", ""),
+        ("ab|ac", "ab", "ac"),
+        ("a(a|b)*", "a(a|b)*b", "a"),
+        ("a*", "(aa)*", "a"),
+        ("abc*", "ab", "abc"),
+    ];
+    for (e1, e2, expected) in cases.iter() {
+        let (re1, re2) = (RegExp::new(e1).unwrap(), RegExp::new(e2).unwrap());
+        let counterexample = re1.equivalent(&re2).unwrap_err();
+        assert_eq!(&counterexample, expected, "{} and {}", e1, e2);
+
+        // The counterexample is matched by exactly one of the expressions.
+        assert_ne!(
+            re1.is_match(&counterexample),
+            re2.is_match(&counterexample),
+            "{} and {} on {:?}",
+            e1,
+            e2,
+            counterexample
+        );
+
+        // Inclusion holds in one direction only.
+        assert_eq!(re2.is_subset_of(&re1), Ok(()), "{} in {}", e2, e1);
+        assert!(re1.is_subset_of(&re2).is_err(), "{} in {}", e1, e2);
+    }
+}
+
+#[test]
+fn test_counterexample_is_printable() {
+    let re1 = RegExp::new("[^a]").unwrap();
+    let re2 = RegExp::new("[b-z]").unwrap();
+    let counterexample = re1.equivalent(&re2).unwrap_err();
+
+    assert_eq!(counterexample.chars().count(), 1);
+    assert!(counterexample
+        .chars()
+        .all(|c| c.is_ascii_graphic() || c == ' '));
+    assert!(re1.is_match(&counterexample));
+    assert!(!re2.is_match(&counterexample));
+}
+
+#[test]
+fn test_counterexample_is_deterministic() {
+    // Edges are followed in order of their symbols, so the counterexample does not depend on the
+    // numbering of the states.
+    for _ in 0..10 {
+        let re1 = RegExp::new("[^a]").unwrap();
+        let re2 = RegExp::new("[b-z]").unwrap();
+        assert_eq!(re1.equivalent(&re2), Err(" ".into()));
+
+        let re1 = RegExp::new("(a|b)*abb").unwrap();
+        let re2 = RegExp::new("(a|b)*ab").unwrap();
+        assert_eq!(re1.equivalent(&re2), Err("ab".into()));
+    }
+}
+
+#[test]
+fn test_representative() {
+    assert_eq!(CharClass::new().representative(), None);
+    assert_eq!(CharClass::from('\u{1}').representative(), Some('\u{1}'));
+    assert_eq!(CharClass::all().representative(), Some(' '));
+    assert_eq!(
+        CharClass::from(vec!['\u{1}', 'x']).representative(),
+        Some('x')
+    );
+}
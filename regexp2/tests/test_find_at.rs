@@ -0,0 +1,36 @@
+use regexp2::RegExp;
+
+#[test]
+fn test_find_at_offsets() {
+    let nfa_re = RegExp::new_nfa("ab|abc").unwrap();
+    let dfa_re = RegExp::new("ab|abc").unwrap();
+
+    // The search starts at the given position, and the match ends after the symbols it read.
+    let m = nfa_re.find_at("xxabcd", 2).unwrap();
+    assert_eq!(2..5, m.range());
+    assert_eq!("abc", m.span);
+    let m = dfa_re.find_at("xxabcd", 2).unwrap();
+    assert_eq!(2..5, m.range());
+    assert_eq!("abc", m.span);
+
+    let m = nfa_re.find_shortest_at("xxabcd", 2).unwrap();
+    assert_eq!(2..4, m.range());
+    let m = dfa_re.find_shortest_at("xxabcd", 2).unwrap();
+    assert_eq!(2..4, m.range());
+
+    // The match is anchored at the start position.
+    assert!(nfa_re.find_at("xxabcd", 1).is_none());
+    assert!(dfa_re.find_at("xxabcd", 1).is_none());
+}
+
+#[test]
+fn test_find_at_empty() {
+    let nfa_re = RegExp::new_nfa("a*").unwrap();
+    let dfa_re = RegExp::new("a*").unwrap();
+
+    // Empty matches end where they start.
+    assert_eq!(2..2, nfa_re.find_at("bb", 2).unwrap().range());
+    assert_eq!(2..2, dfa_re.find_at("bb", 2).unwrap().range());
+    assert_eq!(1..3, nfa_re.find_at("baa", 1).unwrap().range());
+    assert_eq!(1..3, dfa_re.find_at("baa", 1).unwrap().range());
+}
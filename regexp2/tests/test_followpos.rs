@@ -0,0 +1,103 @@
+use regexp2::ast::{BinaryOp, Expr, UnaryOp};
+use regexp2::class::CharClass;
+use regexp2::parser::ast::ASTParser;
+use regexp2::{FollowposError, Limits, RegExp, UnsupportedOperator};
+
+use std::collections::HashSet;
+
+#[test]
+fn test_followpos_dragon_book() {
+    // Example 3.37: the DFA for (a|b)*abb has four states, and the state after abb accepts.
+    let parser: ASTParser<CharClass> = ASTParser::new();
+    let dfa = parser.parse("(a|b)*abb").unwrap().followpos_dfa().unwrap();
+    assert_eq!(dfa.total_states, 4);
+    assert_eq!(
+        dfa.accepting_states,
+        vec![3].into_iter().collect::<HashSet<_>>()
+    );
+    assert!(dfa.is_match("abb".chars()));
+    assert!(dfa.is_match("babaabb".chars()));
+    assert!(!dfa.is_match("abba".chars()));
+}
+
+#[test]
+fn test_followpos_deterministic() {
+    let parser: ASTParser<CharClass> = ASTParser::new();
+    let dfa = parser
+        .parse("[a-m]x|[h-z]y|[a-z]*z")
+        .unwrap()
+        .followpos_dfa()
+        .unwrap();
+    let adjacency = dfa.adjacency();
+    for s in 0..dfa.total_states {
+        let labels = adjacency.labels(s);
+        for (i, l1) in labels.iter().enumerate() {
+            for l2 in &labels[i + 1..] {
+                assert!(
+                    l1.intersection(l2).is_empty(),
+                    "{:?} and {:?} overlap",
+                    l1,
+                    l2
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_followpos_equivalent() {
+    let exprs = [
+        "abc",
+        "(a|b)*abb",
+        "a?b+c*",
+        "(ab|a)(bc|c)",
+        "[a-z]+[0-9]?",
+        "((a*)*b)*",
+        "(x|y|z)(x|y|z)?",
+        "a*",
+        "(a|b)*a(a|b)(a|b)(a|b)",
+    ];
+    for expr in exprs.iter() {
+        let re = RegExp::new(expr).unwrap();
+        let followpos_re = RegExp::new_followpos(expr).unwrap();
+        assert_eq!(followpos_re.equivalent(&re), Ok(()), "{}", expr);
+
+        let inputs = ["", "a", "ab", "abb", "aab", "abc", "bc", "x9", "xyz"];
+        for input in inputs.iter() {
+            assert_eq!(
+                followpos_re.find(input).map(|m| m.range()),
+                re.find(input).map(|m| m.range()),
+                "{} on {}",
+                expr,
+                input
+            );
+        }
+    }
+}
+
+#[test]
+fn test_followpos_nullable() {
+    let re = RegExp::new_followpos("(ab)*c?").unwrap();
+    assert!(re.is_match(""));
+    assert!(re.is_match("ababc"));
+    assert!(!re.is_match("aba"));
+}
+
+#[test]
+fn test_followpos_unsupported() {
+    let parser: ASTParser<CharClass> = ASTParser::new();
+    let a = Box::new(parser.parse("a*").unwrap());
+    let b = Box::new(parser.parse("(ab)*").unwrap());
+    let complement = Expr::Unary(UnaryOp::Complement, a.clone());
+    assert_eq!(
+        UnsupportedOperator::Complement,
+        complement.followpos_dfa().unwrap_err()
+    );
+    let intersect = Expr::Binary(BinaryOp::Intersect, a, b);
+    assert_eq!(
+        FollowposError::Unsupported(UnsupportedOperator::Intersect),
+        intersect
+            .followpos_dfa_with_limits(Limits::none())
+            .unwrap_err()
+    );
+}
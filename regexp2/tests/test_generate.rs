@@ -0,0 +1,171 @@
+use regexp2::class::CharRange;
+use regexp2::generate::{Rng, SplitMix64};
+use regexp2::RegExp;
+
+use std::collections::HashMap;
+
+#[test]
+fn test_strings_shortlex() {
+    let re = RegExp::new("(a|b)*").unwrap();
+    let generator = re.generator();
+    let strings: Vec<String> = generator.strings().take(7).collect();
+    assert_eq!(strings, vec!["", "a", "b", "aa", "ab", "ba", "bb"]);
+}
+
+#[test]
+fn test_strings_finite() {
+    let re = RegExp::new("bc|a|[x-z]").unwrap();
+    let generator = re.generator();
+    let strings: Vec<String> = generator.strings().collect();
+    assert_eq!(strings, vec!["a", "x", "y", "z", "bc"]);
+}
+
+#[test]
+fn test_strings_skip_lengths() {
+    let re = RegExp::new("a(bbb)*").unwrap();
+    let generator = re.generator();
+    let strings: Vec<String> = generator.strings().take(3).collect();
+    assert_eq!(strings, vec!["a", "abbb", "abbbbbb"]);
+}
+
+#[test]
+fn test_strings_empty_language() {
+    let re = RegExp::new("a")
+        .unwrap()
+        .intersection(&RegExp::new("b").unwrap());
+    let generator = re.generator();
+    assert_eq!(generator.strings().next(), None);
+    assert_eq!(generator.count(0), 0);
+    assert_eq!(generator.count(1), 0);
+    assert_eq!(generator.sample(1, &mut SplitMix64::new(0)), None);
+}
+
+#[test]
+fn test_strings_match() {
+    let exprs = ["(a|b)*abb", "[a-c]+d?", "x(yz|[0-2])*"];
+    for expr in exprs.iter() {
+        let re = RegExp::new(expr).unwrap();
+        let generator = re.generator();
+
+        let mut by_len: HashMap<usize, u128> = HashMap::new();
+        let mut last: Option<String> = None;
+        for s in generator.strings().take_while(|s| s.chars().count() <= 6) {
+            assert!(re.is_match(&s), "{} on {}", expr, s);
+            if let Some(last) = last {
+                let key = |s: &String| (s.chars().count(), s.clone());
+                assert!(key(&last) < key(&s), "{} then {}", last, s);
+            }
+            *by_len.entry(s.chars().count()).or_default() += 1;
+            last = Some(s);
+        }
+
+        for len in 0..=6 {
+            assert_eq!(
+                generator.count(len),
+                by_len.get(&len).cloned().unwrap_or(0),
+                "{} at length {}",
+                expr,
+                len
+            );
+        }
+    }
+}
+
+#[test]
+fn test_count() {
+    let re = RegExp::new("(a|b)*").unwrap();
+    let generator = re.generator();
+    for len in 0..100 {
+        assert_eq!(generator.count(len), 1 << len);
+    }
+    assert_eq!(generator.count(128), u128::MAX);
+
+    let re = RegExp::new("[a-z][0-9]").unwrap();
+    let generator = re.generator();
+    assert_eq!(generator.count(1), 0);
+    assert_eq!(generator.count(2), 260);
+    assert_eq!(generator.count(3), 0);
+}
+
+#[test]
+fn test_count_saturates() {
+    // Every character except the newline.
+    let any: u128 = 0x110000 - 0x800 - 1;
+
+    let re = RegExp::new(".*").unwrap();
+    let generator = re.generator();
+    assert_eq!(generator.count(1), any);
+    assert_eq!(generator.count(6), any.pow(6));
+    assert_eq!(generator.count(7), u128::MAX);
+}
+
+#[test]
+fn test_sample() {
+    let re = RegExp::new("(a|b)*c[0-9]+").unwrap();
+    let generator = re.generator();
+    let mut rng = SplitMix64::new(7);
+    for len in 2..20 {
+        for _ in 0..20 {
+            let s = generator.sample(len, &mut rng).unwrap();
+            assert_eq!(s.chars().count(), len);
+            assert!(re.is_match(&s), "{}", s);
+        }
+    }
+    assert_eq!(generator.sample(1, &mut rng), None);
+}
+
+#[test]
+fn test_sample_uniform() {
+    // Five strings of length 2, reached through edges of different sizes.
+    let re = RegExp::new("a[0-3]|bx").unwrap();
+    let generator = re.generator();
+    assert_eq!(generator.count(2), 5);
+
+    let mut rng = SplitMix64::new(42);
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for _ in 0..5000 {
+        *counts
+            .entry(generator.sample(2, &mut rng).unwrap())
+            .or_default() += 1;
+    }
+
+    assert_eq!(counts.len(), 5);
+    for (s, &n) in &counts {
+        assert!((800..1200).contains(&n), "{} sampled {} times", s, n);
+    }
+}
+
+#[test]
+fn test_custom_rng() {
+    /// Always chooses the last value.
+    struct Last;
+    impl Rng for Last {
+        fn next_u64(&mut self) -> u64 {
+            u64::MAX
+        }
+
+        fn below(&mut self, n: u128) -> u128 {
+            n - 1
+        }
+    }
+
+    let re = RegExp::new("[a-c][x-z]").unwrap();
+    let generator = re.generator();
+    assert_eq!(generator.sample(2, &mut Last), Some("cz".to_string()));
+}
+
+#[test]
+fn test_range_size_and_nth() {
+    let r = CharRange::new('a', 'z');
+    assert_eq!(r.size(), 26);
+    assert_eq!(r.nth(0), Some('a'));
+    assert_eq!(r.nth(25), Some('z'));
+    assert_eq!(r.nth(26), None);
+
+    // Ranges that span the surrogates do not count them.
+    let r = CharRange::new('\u{d7fe}', '\u{e001}');
+    assert_eq!(r.size(), 4);
+    let chars: Vec<_> = (0..4).map(|n| r.nth(n).unwrap()).collect();
+    assert_eq!(chars, vec!['\u{d7fe}', '\u{d7ff}', '\u{e000}', '\u{e001}']);
+    assert_eq!(r.nth(4), None);
+}
@@ -0,0 +1,137 @@
+use regexp2::ast::{BinaryOp, Expr, UnaryOp};
+use regexp2::automata::nfa::Transition;
+use regexp2::automata::{DFA, NFA};
+use regexp2::class::CharClass;
+use regexp2::parser::ast::ASTParser;
+use regexp2::parser::nfa::NFAParser;
+use regexp2::{RegExp, UnsupportedOperator};
+
+fn glushkov(expr: &str) -> NFA<CharClass> {
+    let parser: ASTParser<CharClass> = ASTParser::new();
+    parser.parse(expr).unwrap().glushkov_nfa().unwrap()
+}
+
+/// The number of character classes written in an expression; every other character of these
+/// expressions is an operator.
+fn atoms(expr: &str) -> usize {
+    expr.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '.')
+        .count()
+}
+
+fn is_epsilon_free(nfa: &NFA<CharClass>) -> bool {
+    let adjacency = nfa.adjacency();
+    (0..nfa.total_states).all(|s| {
+        adjacency
+            .labels(s)
+            .iter()
+            .all(|t| *t != Transition::Epsilon)
+    })
+}
+
+#[test]
+fn test_glushkov_shape() {
+    let exprs = [
+        "abc",
+        "(a|b)*abb",
+        "a?b+c*",
+        "(ab|a)(bc|c)",
+        "x(y|z)*.",
+        "((a*)*b)*",
+    ];
+    for expr in exprs.iter() {
+        let nfa = glushkov(expr);
+        assert_eq!(nfa.total_states, atoms(expr) + 1, "{}", expr);
+        assert!(is_epsilon_free(&nfa), "{}", expr);
+
+        // Every transition into a position is on that position's class.
+        let adjacency = nfa.adjacency();
+        for s in 0..nfa.total_states {
+            let labels: Vec<_> = adjacency.incoming(s).map(|(label, _)| label).collect();
+            assert!(labels.windows(2).all(|w| w[0] == w[1]), "{}", expr);
+        }
+    }
+}
+
+#[test]
+fn test_glushkov_smaller_than_thompson() {
+    let expr = "(a|b)*a(a|b)(a|b)(a|b)";
+    let thompson: NFA<CharClass> = NFAParser::new().parse(expr).unwrap();
+    let glushkov = glushkov(expr);
+    assert!(glushkov.total_states < thompson.total_states);
+
+    // Both determinize to the same minimal DFA.
+    let d1: DFA<CharClass> = thompson.into();
+    let d2: DFA<CharClass> = glushkov.into();
+    assert_eq!(d1.minimize().total_states, d2.minimize().total_states);
+    assert_eq!(d1.is_equivalent_to(&d2), Ok(()));
+}
+
+#[test]
+fn test_glushkov_nullable() {
+    let nfa = glushkov("a*(b|c?)");
+    assert!(nfa.is_accepting_state(&nfa.start_state));
+    assert!(nfa.is_match("".chars()));
+
+    let nfa = glushkov("a*b");
+    assert!(!nfa.is_accepting_state(&nfa.start_state));
+}
+
+#[test]
+fn test_glushkov_matches() {
+    let exprs = [
+        "(a|b)*abb",
+        "a?b+c*",
+        "(ab|a)(bc|c)",
+        "[a-c]+[0-9]?",
+        "(x+y?)+",
+    ];
+    let inputs = [
+        "", "a", "b", "abb", "babb", "abc", "bc", "ac", "abbc", "c0", "ab9", "xxyx", "xyy",
+    ];
+    for expr in exprs.iter() {
+        let glushkov_re = RegExp::new_glushkov(expr).unwrap();
+        let re = RegExp::new(expr).unwrap();
+        for input in inputs.iter() {
+            assert_eq!(
+                glushkov_re.is_match(input),
+                re.is_match(input),
+                "{} on {}",
+                expr,
+                input
+            );
+            assert_eq!(
+                glushkov_re.find(input).map(|m| m.range()),
+                re.find(input).map(|m| m.range()),
+                "{} on {}",
+                expr,
+                input
+            );
+        }
+
+        let dfa_re = RegExp::new_glushkov(expr).unwrap().with_dfa();
+        assert_eq!(dfa_re.equivalent(&re), Ok(()), "{}", expr);
+    }
+}
+
+#[test]
+fn test_glushkov_unsupported() {
+    // Complement and intersection have no positions, so there is no Glushkov automaton for them.
+    let parser: ASTParser<CharClass> = ASTParser::new();
+    let a = Box::new(parser.parse("a*").unwrap());
+    let b = Box::new(parser.parse("(ab)*").unwrap());
+    let complement = Expr::Unary(UnaryOp::Complement, a.clone());
+    let intersect = Expr::Binary(
+        BinaryOp::Concat,
+        b.clone(),
+        Box::new(Expr::Binary(BinaryOp::Intersect, a, b)),
+    );
+    assert_eq!(
+        UnsupportedOperator::Complement,
+        complement.glushkov_nfa().unwrap_err()
+    );
+    assert_eq!(
+        UnsupportedOperator::Intersect,
+        intersect.glushkov_nfa().unwrap_err()
+    );
+}
@@ -29,6 +29,16 @@ fn test_kleene() {
     let invalids = [" ", "b", "ba", "babb"];
     run_tests!(&exprs, &valids, &invalids);
 
+    let exprs = ["(a*)*", "((a*)*)*", "(a*)+", "(a?)*"];
+    let valids = ["", "a", "aa", "aaa"];
+    let invalids = [" ", "b", "ab"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = ["(a*b*)*", "((a|b)*)*", "(a|b*)*"];
+    let valids = ["", "a", "ab", "ba", "aabba"];
+    let invalids = [" ", "c", "abc"];
+    run_tests!(&exprs, &valids, &invalids);
+
     let exprs = [r"\**", r"(\*)*", r"()\**"];
     let valids = ["", "*", "**", "***"];
     let invalids = [" ", "* ", " *", r"\*", r"\"];
@@ -0,0 +1,89 @@
+use regexp2::lazy::LazyDFA;
+use regexp2::{Engine, RegExp};
+
+include!("macros.rs");
+
+#[test]
+fn test_lazy_cache() {
+    let lazy = LazyDFA::new(compile_nfa("(a|b)*abb"));
+    assert_eq!(0, lazy.cached_states());
+
+    assert!(lazy.is_match("aababb"));
+    let cached = lazy.cached_states();
+    assert!(cached > 0);
+
+    // States determinized by previous searches are reused.
+    assert!(lazy.is_match("aababb"));
+    assert!(lazy.is_match("abb"));
+    assert!(!lazy.is_match("aaba"));
+    assert_eq!(cached, lazy.cached_states());
+    assert_eq!(0, lazy.cache_clears());
+}
+
+#[test]
+fn test_lazy_bounded_cache() {
+    let exprs = [
+        "(a|b)*a(a|b)(a|b)(a|b)",
+        "(a|bc)*",
+        r"\d+\w?",
+        "ab(a|b)*abb",
+    ];
+    let inputs = [
+        "",
+        "a",
+        "abab",
+        "bbabbb",
+        "aaaaaaaaab",
+        "abcbca",
+        "3a",
+        "999_",
+        "ababb",
+        "abbaabb",
+    ];
+
+    for expr in &exprs {
+        let nfa = compile_nfa(expr);
+        // A tiny cache forces the cache to be cleared and the search to fall back to NFA
+        // simulation.
+        let tiny = LazyDFA::with_cache_capacity(nfa.clone(), 256);
+        // A cache too small for any state always simulates the NFA.
+        let empty = LazyDFA::with_cache_capacity(nfa.clone(), 0);
+
+        for input in &inputs {
+            let expected = nfa.is_match(input.chars());
+            assert_eq!(
+                expected,
+                tiny.is_match(input),
+                r#""{}" on "{}""#,
+                expr,
+                input
+            );
+            assert_eq!(
+                expected,
+                empty.is_match(input),
+                r#""{}" on "{}""#,
+                expr,
+                input
+            );
+
+            let expected = Engine::find_at(&nfa, input, 0).map(|m| m.range());
+            assert_eq!(expected, tiny.find_at(input, 0).map(|m| m.range()));
+            assert_eq!(expected, empty.find_at(input, 0).map(|m| m.range()));
+        }
+
+        assert_eq!(0, empty.cached_states());
+    }
+
+    let tiny = LazyDFA::with_cache_capacity(compile_nfa("(a|b)*a(a|b)(a|b)(a|b)"), 256);
+    assert!(tiny.is_match("abababababbbbbaaaabab"));
+    assert!(tiny.cache_clears() > 0);
+}
+
+#[test]
+fn test_lazy_find() {
+    let re = RegExp::new_lazy("ab|abc").unwrap();
+    assert_eq!(0..3, re.find("abcd").unwrap().range());
+    assert_eq!(0..2, re.find_shortest("abcd").unwrap().range());
+    assert_eq!(1..4, re.find_at("xabc", 1).unwrap().range());
+    assert!(re.find("xabc").is_none());
+}
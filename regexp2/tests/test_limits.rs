@@ -0,0 +1,200 @@
+use regexp2::reverse::ReverseSearcher;
+use regexp2::{CompileError, Limit, Limits, RegExp, SizeLimitExceeded};
+
+/// `(a|b)*a(a|b){n}`, whose DFA has 2^(n + 1) states.
+fn blowup(n: usize) -> String {
+    format!("(a|b)*a{}", "(a|b)".repeat(n))
+}
+
+fn exceeded(err: CompileError<'_>) -> SizeLimitExceeded {
+    match err {
+        CompileError::SizeLimitExceeded(err) => err,
+        err => panic!("expected a size limit error, got {:?}", err),
+    }
+}
+
+#[test]
+fn test_dfa_state_limit() {
+    let expr = blowup(10);
+    let limits = Limits {
+        dfa_states: Some(1000),
+        ..Limits::none()
+    };
+    let err = exceeded(RegExp::new_with_limits(&expr, limits).unwrap_err());
+    assert_eq!(Limit::DFAStates, err.limit);
+    assert_eq!(1000, err.max);
+
+    let limits = Limits {
+        dfa_states: Some(1 << 11),
+        ..Limits::none()
+    };
+    let re = RegExp::new_with_limits(&expr, limits).unwrap();
+    assert!(re.is_match("babbbbbbbbbb"));
+    assert!(!re.is_match("bbbbbbbbbbbb"));
+}
+
+#[test]
+fn test_default_limits() {
+    // The subset construction for this expression would need millions of states.
+    let expr = blowup(22);
+    assert!(matches!(
+        RegExp::new(&expr),
+        Err(CompileError::SizeLimitExceeded(_))
+    ));
+
+    // The NFA is small, so engines that never build the DFA are fine.
+    let re = RegExp::new_nfa(&expr).unwrap();
+    assert!(re.is_match(&format!("a{}", "b".repeat(22))));
+}
+
+#[test]
+fn test_memory_limit() {
+    let limits = Limits {
+        memory: Some(1 << 12),
+        ..Limits::none()
+    };
+    let err = exceeded(RegExp::new_with_limits(&blowup(8), limits).unwrap_err());
+    assert_eq!(Limit::Memory, err.limit);
+
+    assert!(RegExp::new_with_limits("(a|b)*abb", limits).is_ok());
+}
+
+#[test]
+fn test_nfa_state_limit() {
+    let limits = Limits {
+        nfa_states: Some(50),
+        ..Limits::none()
+    };
+    let expr = "ab".repeat(100);
+    let err = exceeded(RegExp::new_nfa_with_limits(&expr, limits).unwrap_err());
+    assert_eq!(Limit::NFAStates, err.limit);
+    assert_eq!(50, err.max);
+
+    let err = exceeded(RegExp::new_with_limits(&expr, limits).unwrap_err());
+    assert_eq!(Limit::NFAStates, err.limit);
+
+    let expr = "ab".repeat(20);
+    let re = RegExp::new_nfa_with_limits(&expr, limits).unwrap();
+    assert!(re.is_match(&expr));
+}
+
+#[test]
+fn test_parse_errors() {
+    for expr in &["(", "a|", "**", "[]"] {
+        assert!(matches!(
+            RegExp::new_with_limits(expr, Limits::default()),
+            Err(CompileError::Parse(_))
+        ));
+    }
+
+    let expr = blowup(10);
+    let err = RegExp::new_with_limits(
+        &expr,
+        Limits {
+            dfa_states: Some(10),
+            ..Limits::none()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        "size limit exceeded: more than 10 DFA states",
+        err.to_string()
+    );
+}
+
+#[test]
+fn test_direct_construction_limits() {
+    // Without limits, the followpos DFA for this expression has half a million states.
+    let limits = Limits {
+        dfa_states: Some(1 << 12),
+        ..Limits::none()
+    };
+    let err = exceeded(RegExp::new_followpos_with_limits(&blowup(18), limits).unwrap_err());
+    assert_eq!(Limit::DFAStates, err.limit);
+    assert_eq!(1 << 12, err.max);
+
+    let limits = Limits {
+        dfa_states: Some(100),
+        ..Limits::none()
+    };
+    let expr = blowup(8);
+    let err = exceeded(RegExp::new_followpos_with_limits(&expr, limits).unwrap_err());
+    assert_eq!(Limit::DFAStates, err.limit);
+    let err = exceeded(RegExp::new_tagged_with_limits(&expr, limits).unwrap_err());
+    assert_eq!(Limit::DFAStates, err.limit);
+    let err = RegExp::new_derivative(&expr)
+        .unwrap()
+        .try_with_dfa(limits)
+        .unwrap_err();
+    assert_eq!(Limit::DFAStates, err.limit);
+
+    let expr = blowup(4);
+    assert!(RegExp::new_followpos_with_limits(&expr, limits).is_ok());
+    assert!(RegExp::new_tagged_with_limits(&expr, limits).is_ok());
+    let re = RegExp::new_derivative(&expr)
+        .unwrap()
+        .try_with_dfa(limits)
+        .unwrap();
+    assert!(re.is_match("abbbb"));
+
+    // Parse errors are reported as such by every constructor.
+    assert!(matches!(
+        RegExp::new_followpos("a|"),
+        Err(CompileError::Parse(_))
+    ));
+    assert!(matches!(
+        RegExp::new_tagged("(a"),
+        Err(CompileError::Parse(_))
+    ));
+    assert!(matches!(
+        RegExp::new_pikevm("*"),
+        Err(CompileError::Parse(_))
+    ));
+}
+
+#[test]
+fn test_reverse_searcher_limits() {
+    // The unanchored DFA that finds where matches end would need 2^19 states.
+    assert!(matches!(
+        ReverseSearcher::new(&blowup(18)),
+        Err(CompileError::SizeLimitExceeded(_))
+    ));
+
+    let limits = Limits {
+        dfa_states: Some(100),
+        ..Limits::none()
+    };
+    let err = exceeded(ReverseSearcher::with_limits(&blowup(8), limits).unwrap_err());
+    assert_eq!(Limit::DFAStates, err.limit);
+
+    let searcher = ReverseSearcher::with_limits(&blowup(4), limits).unwrap();
+    assert_eq!(
+        Some((0, 6)),
+        searcher.find("babbbb").map(|m| (m.start(), m.end()))
+    );
+}
+
+#[test]
+fn test_glushkov_limits() {
+    let limits = Limits {
+        nfa_states: Some(50),
+        ..Limits::none()
+    };
+    let err = exceeded(RegExp::new_glushkov_with_limits(&"ab".repeat(100), limits).unwrap_err());
+    assert_eq!(Limit::NFAStates, err.limit);
+    assert_eq!(50, err.max);
+    assert!(RegExp::new_glushkov_with_limits(&"ab".repeat(20), limits).is_ok());
+
+    // Each of the 64 alternatives may follow every other, so there are over 4096 transitions.
+    let limits = Limits {
+        memory: Some(1 << 12),
+        ..Limits::none()
+    };
+    let alternation: Vec<String> = (0..64).map(|i| format!("x{}", i)).collect();
+    let expr = format!("({})*", alternation.join("|"));
+    let err = exceeded(RegExp::new_glushkov_with_limits(&expr, limits).unwrap_err());
+    assert_eq!(Limit::Memory, err.limit);
+
+    let re = RegExp::new_glushkov_with_limits("(a|b)*abb", limits).unwrap();
+    assert!(re.is_match("babb"));
+}
@@ -0,0 +1,224 @@
+use regexp2::reverse::ReverseSearcher;
+use regexp2::{MatchKind, RegExp};
+
+const EXPRS: [&str; 11] = [
+    "a|ab",
+    "ab|a",
+    "abc|b",
+    "(a|b)*abb",
+    "a*",
+    "(a|ab)(c|bcd)",
+    "x(a|ab)*y?",
+    r"\d+\w?",
+    "[^a]b",
+    "b(a*|c)",
+    "(([ab])?|([ab])+)",
+];
+
+const INPUTS: [&str; 10] = [
+    "", "a", "ab", "abc", "xab", "abcd", "aababbab", "xababy", "z08m", "bacab",
+];
+
+const KINDS: [MatchKind; 3] = [
+    MatchKind::LeftmostLongest,
+    MatchKind::LeftmostFirst,
+    MatchKind::Earliest,
+];
+
+fn range(
+    re: &RegExp<impl regexp2::Engine>,
+    input: &str,
+    kind: MatchKind,
+) -> Option<(usize, usize)> {
+    re.find_kind(input, kind)
+        .unwrap()
+        .map(|m| (m.start(), m.end()))
+}
+
+#[test]
+fn test_match_kinds() {
+    let re = RegExp::new_pikevm("a|ab").unwrap();
+    assert_eq!(range(&re, "xab", MatchKind::LeftmostLongest), Some((1, 3)));
+    assert_eq!(range(&re, "xab", MatchKind::LeftmostFirst), Some((1, 2)));
+    assert_eq!(range(&re, "xab", MatchKind::Earliest), Some((1, 2)));
+
+    let re = RegExp::new_tagged("ab|a").unwrap();
+    assert_eq!(range(&re, "xab", MatchKind::LeftmostFirst), Some((1, 3)));
+
+    // The optional first alternative matches one character, so the repetition is never tried.
+    let re = RegExp::new_pikevm("(([ab])?|([ab])+)").unwrap();
+    assert_eq!(range(&re, "bab", MatchKind::LeftmostFirst), Some((0, 1)));
+    assert_eq!(range(&re, "bab", MatchKind::LeftmostLongest), Some((0, 3)));
+
+    // Engines that only find the longest match cannot tell which match is first.
+    let re = RegExp::new("(([ab])?|([ab])+)").unwrap();
+    assert!(re.find_kind("bab", MatchKind::LeftmostFirst).is_err());
+    assert_eq!(range(&re, "bab", MatchKind::LeftmostLongest), Some((0, 3)));
+
+    // The earliest match ends first, even though another match starts further left.
+    let re = RegExp::new("abc|b").unwrap();
+    assert_eq!(range(&re, "abc", MatchKind::LeftmostLongest), Some((0, 3)));
+    assert_eq!(range(&re, "abc", MatchKind::Earliest), Some((1, 2)));
+
+    // The first alternative is taken inside the repetition, after which neither matches `b`.
+    let re = RegExp::new_pikevm("x(a|ab)*").unwrap();
+    assert_eq!(range(&re, "xabab", MatchKind::LeftmostFirst), Some((0, 2)));
+    assert_eq!(
+        range(&re, "xabab", MatchKind::LeftmostLongest),
+        Some((0, 5))
+    );
+
+    // Repetition is greedy.
+    let re = RegExp::new_tagged("(a*)(a*)").unwrap();
+    assert_eq!(range(&re, "aaa", MatchKind::LeftmostFirst), Some((0, 3)));
+    assert_eq!(re.captures("aaa").unwrap().get(1).unwrap().range(), 0..3);
+
+    // An empty match at the search position is leftmost.
+    let re = RegExp::new_pikevm("a*").unwrap();
+    for &kind in &KINDS {
+        assert_eq!(range(&re, "baa", kind), Some((0, 0)));
+    }
+
+    let re = RegExp::new("b").unwrap();
+    assert_eq!(
+        re.find_kind_at("abab", 2, MatchKind::LeftmostLongest)
+            .unwrap()
+            .unwrap()
+            .range(),
+        3..4
+    );
+    assert!(re
+        .find_kind_at("abab", 4, MatchKind::Earliest)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_match_kinds_agree() {
+    for expr in &EXPRS {
+        let nfa_re = RegExp::new_nfa(expr).unwrap();
+        let dfa_re = RegExp::new(expr).unwrap();
+        let dense_re = RegExp::new_dense(expr).unwrap();
+        let lazy_re = RegExp::new_lazy(expr).unwrap();
+        let backtracker_re = RegExp::new_backtracker(expr).unwrap();
+        let derivative_re = RegExp::new_derivative(expr).unwrap();
+        let pikevm_re = RegExp::new_pikevm(expr).unwrap();
+        let tagged_re = RegExp::new_tagged(expr).unwrap();
+        let searcher = ReverseSearcher::new(expr).unwrap();
+
+        for input in &INPUTS {
+            for &kind in &[MatchKind::LeftmostLongest, MatchKind::Earliest] {
+                let expected = range(&dfa_re, input, kind);
+                let context = format!(r#""{}" on "{}" as {:?}"#, expr, input, kind);
+                assert_eq!(
+                    expected,
+                    range(&nfa_re, input, kind),
+                    "{} with nfa",
+                    context
+                );
+                assert_eq!(
+                    expected,
+                    range(&dense_re, input, kind),
+                    "{} with dense",
+                    context
+                );
+                assert_eq!(
+                    expected,
+                    range(&lazy_re, input, kind),
+                    "{} with lazy",
+                    context
+                );
+                assert_eq!(
+                    expected,
+                    range(&backtracker_re, input, kind),
+                    "{} with backtracker",
+                    context
+                );
+                assert_eq!(
+                    expected,
+                    range(&derivative_re, input, kind),
+                    "{} with derivative",
+                    context
+                );
+                assert_eq!(
+                    expected,
+                    range(&pikevm_re, input, kind),
+                    "{} with pike vm",
+                    context
+                );
+                assert_eq!(
+                    expected,
+                    range(&tagged_re, input, kind),
+                    "{} with tagged dfa",
+                    context
+                );
+            }
+
+            let longest = range(&dfa_re, input, MatchKind::LeftmostLongest);
+            let found = searcher.find(input).map(|m| (m.start(), m.end()));
+            assert_eq!(
+                longest, found,
+                r#""{}" on "{}" with reverse searcher"#,
+                expr, input
+            );
+            let earliest = range(&dfa_re, input, MatchKind::Earliest);
+            let found = searcher.find_earliest(input).map(|m| (m.start(), m.end()));
+            assert_eq!(
+                earliest, found,
+                r#""{}" on "{}" with reverse searcher, earliest"#,
+                expr, input
+            );
+
+            // Only the NFA simulation and the tagged DFA keep the order of alternatives, and
+            // they prefer the same ones.
+            let kind = MatchKind::LeftmostFirst;
+            let context = format!(r#""{}" on "{}" as {:?}"#, expr, input, kind);
+            assert!(
+                nfa_re.find_kind(input, kind).is_err(),
+                "{} with nfa",
+                context
+            );
+            assert!(
+                dfa_re.find_kind(input, kind).is_err(),
+                "{} with dfa",
+                context
+            );
+            assert!(
+                dense_re.find_kind(input, kind).is_err(),
+                "{} with dense",
+                context
+            );
+            assert!(
+                lazy_re.find_kind(input, kind).is_err(),
+                "{} with lazy",
+                context
+            );
+            assert!(
+                backtracker_re.find_kind(input, kind).is_err(),
+                "{} with backtracker",
+                context
+            );
+            assert!(
+                derivative_re.find_kind(input, kind).is_err(),
+                "{} with derivative",
+                context
+            );
+
+            let first = range(&pikevm_re, input, MatchKind::LeftmostFirst);
+            assert_eq!(
+                first,
+                range(&tagged_re, input, MatchKind::LeftmostFirst),
+                r#""{}" on "{}" leftmost-first"#,
+                expr,
+                input
+            );
+
+            // Leftmost-first starts where leftmost-longest does, and is no longer.
+            let longest = range(&dfa_re, input, MatchKind::LeftmostLongest);
+            match (first, longest) {
+                (Some((s1, e1)), Some((s2, e2))) => assert!(s1 == s2 && e1 <= e2),
+                (first, longest) => assert_eq!(first, longest),
+            }
+        }
+    }
+}
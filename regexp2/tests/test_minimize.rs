@@ -0,0 +1,66 @@
+use regexp2::automata::DFA;
+use regexp2::class::CharClass;
+
+include!("macros.rs");
+
+#[test]
+fn test_minimize_equivalence() {
+    let exprs = [
+        "(a|b)*abb",
+        "ab(a|b)*abb",
+        "(a|bc)*",
+        "a*|b",
+        "(a|b)*(a|b)",
+        "a+b?c*",
+        "[a-b]c|[b-c]c",
+        "(ab|ac|ad)*d",
+    ];
+    let inputs = strings(&['a', 'b', 'c', 'd'], 6);
+
+    for expr in &exprs {
+        let dfa = compile(expr);
+        let minimized = dfa.minimize();
+
+        assert!(minimized.total_states <= dfa.total_states);
+        for input in &inputs {
+            assert_eq!(
+                dfa.is_match(input.chars()),
+                minimized.is_match(input.chars()),
+                r#""{}" disagreed on "{}" after minimization"#,
+                expr,
+                input
+            );
+        }
+    }
+}
+
+#[test]
+fn test_minimize_state_count() {
+    // The minimal DFA for (a|b)*abb has four states.
+    let minimized = compile("(a|b)*abb").minimize();
+    assert_eq!(4, minimized.total_states);
+    assert_eq!(1, minimized.accepting_states.len());
+
+    // Equivalent patterns produce DFAs of the same size.
+    let a = compile("(a|b)*").minimize();
+    let b = compile("(b|a)*b*").minimize();
+    assert_eq!(1, a.total_states);
+    assert_eq!(a.total_states, b.total_states);
+
+    // Overlapping character classes are split before refinement.
+    let minimized = compile("[a-c]x|[b-d]x").minimize();
+    assert_eq!(3, minimized.total_states);
+}
+
+#[test]
+fn test_minimize_empty_language() {
+    // A DFA with no accepting states minimizes to a single, non-accepting state.
+    let mut dfa: DFA<CharClass> = DFA::new();
+    let s = dfa.add_state(false);
+    dfa.add_transition(dfa.start_state, s, CharClass::from('a'));
+
+    let minimized = dfa.minimize();
+    assert_eq!(1, minimized.total_states);
+    assert!(minimized.accepting_states.is_empty());
+    assert_eq!(0, minimized.transition.into_iter().count());
+}
@@ -0,0 +1,95 @@
+use regexp2::RegExp;
+
+include!("macros.rs");
+
+#[test]
+fn test_products() {
+    let pairs = [
+        ("(a|b)*abb", "a(a|b)*"),
+        ("a*b*", "(ab)*"),
+        ("[a-c]+", "b|c*"),
+        ("(a|b)*", "c"),
+    ];
+    let inputs = strings(&['a', 'b', 'c', 'x'], 5);
+
+    for (e1, e2) in &pairs {
+        let (d1, d2) = (compile(e1), compile(e2));
+        let intersection = d1.intersection(&d2);
+        let union = d1.union(&d2);
+        let difference = d1.difference(&d2);
+        let symmetric_difference = d1.symmetric_difference(&d2);
+        let complement = d1.complement();
+
+        for input in &inputs {
+            let a = d1.is_match(input.chars());
+            let b = d2.is_match(input.chars());
+            let msg = format!(r#""{}", "{}" on "{}""#, e1, e2, input);
+
+            assert_eq!(a && b, intersection.is_match(input.chars()), "{}", msg);
+            assert_eq!(a || b, union.is_match(input.chars()), "{}", msg);
+            assert_eq!(a && !b, difference.is_match(input.chars()), "{}", msg);
+            assert_eq!(
+                a != b,
+                symmetric_difference.is_match(input.chars()),
+                "{}",
+                msg
+            );
+            assert_eq!(!a, complement.is_match(input.chars()), "{}", msg);
+        }
+    }
+}
+
+#[test]
+fn test_complete() {
+    let dfa = compile("ab");
+    let complete = dfa.complete();
+
+    // One dead state is added, and every state has a transition on every character.
+    assert_eq!(dfa.total_states + 1, complete.total_states);
+    for input in &["", "a", "ab", "abc", "b", "\u{10ffff}"] {
+        assert_eq!(
+            dfa.is_match(input.chars()),
+            complete.is_match(input.chars())
+        );
+        assert_eq!(
+            input.chars().count() + 1,
+            complete.iter_on(input.chars()).count()
+        );
+    }
+
+    // A complete DFA is not completed again.
+    assert_eq!(complete.total_states, complete.complete().total_states);
+}
+
+#[test]
+fn test_regexp_products() {
+    let allowed = RegExp::new(r"/api/\w+").unwrap();
+    let denied = RegExp::new("/api/admin.*").unwrap();
+    let policy = allowed.intersection(&denied.complement());
+
+    assert!(policy.is_match("/api/users"));
+    assert!(policy.is_match("/api/adm"));
+    assert!(!policy.is_match("/api/admin"));
+    assert!(!policy.is_match("/api/admin_users"));
+    assert!(!policy.is_match("/static/x"));
+
+    let difference = allowed.difference(&denied);
+    for input in &["/api/users", "/api/admin", "/api/", "/api/adminx"] {
+        assert_eq!(policy.is_match(input), difference.is_match(input));
+    }
+
+    let a = RegExp::new("a+").unwrap();
+    let b = RegExp::new("b").unwrap();
+    assert!(a.union(&b).is_match("aa"));
+    assert!(a.union(&b).is_match("b"));
+    assert!(!a.symmetric_difference(&a).is_match("a"));
+    assert_eq!(Some("(a+)|(b)"), a.union(&b).pattern());
+    assert_eq!(None, b.complement().pattern());
+    assert_eq!("", b.complement().as_str());
+    assert_eq!(None, a.intersection(&b).pattern());
+    assert_eq!(None, a.complement().union(&b).pattern());
+
+    // The pattern of a union is accepted by the parser.
+    let union = RegExp::new(a.union(&b).as_str()).unwrap();
+    assert_eq!(Ok(()), union.equivalent(&a.union(&b)));
+}
@@ -0,0 +1,116 @@
+use regexp2::reverse::ReverseSearcher;
+use regexp2::RegExp;
+
+fn find(expr: &str, input: &str) -> Option<(usize, usize, String)> {
+    let searcher = ReverseSearcher::new(expr).unwrap();
+    searcher
+        .find(input)
+        .map(|m| (m.start(), m.end(), m.span.clone()))
+}
+
+fn find_earliest(expr: &str, input: &str) -> Option<(usize, usize, String)> {
+    let searcher = ReverseSearcher::new(expr).unwrap();
+    searcher
+        .find_earliest(input)
+        .map(|m| (m.start(), m.end(), m.span.clone()))
+}
+
+#[test]
+fn test_find_unanchored() {
+    assert_eq!(find("abb", "xxabbxx"), Some((2, 5, "abb".into())));
+    assert_eq!(
+        find("(a|b)*abb", "ccaababbcc"),
+        Some((2, 8, "aababb".into()))
+    );
+    assert_eq!(find("[0-9]+", "abc 123 456"), Some((4, 7, "123".into())));
+    assert_eq!(find("x[0-9]+y", "x1 x22y x3y"), Some((3, 7, "x22y".into())));
+    assert_eq!(find("z", "abc"), None);
+    assert_eq!(find("a", ""), None);
+}
+
+#[test]
+fn test_find_leftmost_longest() {
+    // The match that starts first wins, even if another match ends earlier.
+    assert_eq!(find("abc|b", "abc"), Some((0, 3, "abc".into())));
+    assert_eq!(find("a|ab", "xab"), Some((1, 3, "ab".into())));
+    assert_eq!(find("b(a*|c)", "xbaac"), Some((1, 4, "baa".into())));
+}
+
+#[test]
+fn test_find_earliest_end() {
+    // The match that ends first wins, even if another match starts earlier.
+    assert_eq!(find_earliest("abc|b", "abc"), Some((1, 2, "b".into())));
+    assert_eq!(
+        find_earliest("[0-9]+", "abc 123 456"),
+        Some((4, 5, "1".into()))
+    );
+
+    // Among matches that end at the same position, the one that starts first wins.
+    assert_eq!(find_earliest("a*b", "caaab"), Some((1, 5, "aaab".into())));
+}
+
+#[test]
+fn test_find_empty() {
+    assert_eq!(find("a*", "bbb"), Some((0, 0, "".into())));
+    assert_eq!(find("a?", ""), Some((0, 0, "".into())));
+}
+
+#[test]
+fn test_find_at() {
+    let searcher = ReverseSearcher::new("ab").unwrap();
+    let input = "ab ab ab";
+    let starts: Vec<usize> = (0..input.len())
+        .filter_map(|i| searcher.find_at(input, i))
+        .map(|m| m.start())
+        .collect();
+    assert_eq!(starts, vec![0, 3, 3, 3, 6, 6, 6]);
+
+    // The match never starts before the given position.
+    let searcher = ReverseSearcher::new("a+b").unwrap();
+    let m = searcher.find_at("aaab", 2).unwrap();
+    assert_eq!((m.start(), m.end()), (2, 4));
+    assert!(searcher.find_at("aaab", 5).is_none());
+}
+
+#[test]
+fn test_find_unicode() {
+    let m = ReverseSearcher::new("éé").unwrap().find("caféé!").unwrap();
+    assert_eq!((m.start(), m.end(), m.span.as_str()), (3, 5, "éé"));
+}
+
+#[test]
+fn test_find_suffix() {
+    let searcher = ReverseSearcher::new("[0-9]+").unwrap();
+    let m = searcher.find_suffix("abc 12 345").unwrap();
+    assert_eq!((m.start(), m.end(), m.span.as_str()), (7, 10, "345"));
+    assert!(searcher.find_suffix("345 abc").is_none());
+
+    let searcher = ReverseSearcher::new("b*").unwrap();
+    let m = searcher.find_suffix("abb").unwrap();
+    assert_eq!((m.start(), m.end()), (1, 3));
+    let m = searcher.find_suffix("bba").unwrap();
+    assert_eq!((m.start(), m.end()), (3, 3));
+}
+
+#[test]
+fn test_is_match() {
+    let exprs = ["(a|b)*abb", "x(yz)+", "[^a-z]"];
+    let inputs = ["", "abb", "zzabbzz", "xyzyz", "xy", "abc", "ABC", "aXb"];
+    for expr in exprs.iter() {
+        let searcher = ReverseSearcher::new(expr).unwrap();
+        let re = RegExp::new(expr).unwrap();
+        for input in inputs.iter() {
+            // A match anywhere is an anchored match at some position.
+            let expected = (0..=input.chars().count()).any(|i| re.find_at(input, i).is_some());
+            assert_eq!(searcher.is_match(input), expected, "{} on {}", expr, input);
+
+            // The match found is itself matched by the expression.
+            if let Some(m) = searcher.find(input) {
+                assert!(re.is_match(&m.span), "{} on {}", expr, input);
+            }
+            if let Some(m) = searcher.find_earliest(input) {
+                assert!(re.is_match(&m.span), "{} on {}", expr, input);
+            }
+        }
+    }
+}
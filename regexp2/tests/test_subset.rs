@@ -0,0 +1,80 @@
+use regexp2::automata::convert::DFAFromNFA;
+use regexp2::automata::DFA;
+use regexp2::class::CharClass;
+
+include!("macros.rs");
+
+#[test]
+fn test_subset_construction_size() {
+    // The n-th character from the end is an a. Any DFA for this language needs 2^(n+1) states.
+    let n = 10;
+    let expr = format!("(a|b)*a{}", "(a|b)".repeat(n));
+    let nfa = compile_nfa(&expr);
+    let dfa: DFA<CharClass> = nfa.clone().into();
+    // The star reuses its loop state as its start state, so the start state of the DFA is the
+    // state reached after reading a b, and the subset construction is already minimal.
+    assert_eq!(1 << (n + 1), dfa.total_states);
+    assert_eq!(1 << (n + 1), dfa.minimize().total_states);
+
+    let inputs = [
+        "",
+        "a",
+        "abbbbbbbbbb",
+        "babbbbbbbbbb",
+        "aabababababab",
+        "bbbbbbbbbbb",
+    ];
+    for input in &inputs {
+        assert_eq!(
+            nfa.is_match(input.chars()),
+            dfa.is_match(input.chars()),
+            r#"disagreed on "{}""#,
+            input
+        );
+    }
+}
+
+#[test]
+fn test_subset_construction_mapping() {
+    let nfa = compile_nfa("(a|b)*abb|[a-c]x");
+    let dfa_from_nfa: DFAFromNFA<CharClass> = nfa.clone().into();
+    let dfa = &dfa_from_nfa.dfa;
+
+    assert_eq!(dfa.total_states, dfa_from_nfa.nfa_mapping.len());
+    for (label, nfa_states) in dfa_from_nfa.nfa_mapping.iter() {
+        // Each DFA state is accepting exactly when one of its NFA states is.
+        assert_eq!(
+            dfa.is_accepting_state(label),
+            nfa_states.iter().any(|s| nfa.is_accepting_state(s))
+        );
+        // Each DFA state is a distinct set of NFA states.
+        assert_eq!(
+            1,
+            dfa_from_nfa
+                .nfa_mapping
+                .values()
+                .filter(|other| *other == nfa_states)
+                .count()
+        );
+    }
+}
+
+#[test]
+fn test_subset_construction_after_cleanup() {
+    let exprs = ["(a|b)*abb", "((a*)*b)*", "(ab|a)(bc|c)?", "[a-z]+[0-9]*x?"];
+    for expr in exprs.iter() {
+        let nfa = compile_nfa(expr);
+        let cleaned = nfa.remove_epsilons().trim();
+        assert!(cleaned.total_states < nfa.total_states, "{}", expr);
+
+        let dfa: DFA<CharClass> = nfa.into();
+        let cleaned_dfa: DFA<CharClass> = cleaned.into();
+        assert_eq!(dfa.is_equivalent_to(&cleaned_dfa), Ok(()), "{}", expr);
+        assert_eq!(
+            dfa.minimize().total_states,
+            cleaned_dfa.minimize().total_states,
+            "{}",
+            expr
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use regexp2::pikevm::Captures;
+use regexp2::tagged::TaggedDFA;
+use regexp2::{Budget, Limits, MatchKind, RegExp};
+
+fn groups(caps: Option<Captures>) -> Option<Vec<Option<(usize, usize)>>> {
+    caps.map(|caps| {
+        (0..caps.len())
+            .map(|i| caps.get(i).map(|m| (m.start(), m.end())))
+            .collect()
+    })
+}
+
+#[test]
+fn test_tagged_captures() {
+    let re = RegExp::new_tagged("(a|b)*(abb)").unwrap();
+    let caps = re.captures("aababb").unwrap();
+    assert_eq!(3, caps.len());
+    assert_eq!(0..6, caps.get(0).unwrap().range());
+    assert_eq!(2..3, caps.get(1).unwrap().range());
+    assert_eq!("abb", caps.get(2).unwrap().span);
+
+    let re = RegExp::new_tagged(r"((\d+)-(\d+))x?").unwrap();
+    let caps = re.captures("12-345x").unwrap();
+    assert_eq!("12-345", caps.get(1).unwrap().span);
+    assert_eq!("12", caps.get(2).unwrap().span);
+    assert_eq!("345", caps.get(3).unwrap().span);
+    assert!(re.captures("-345").is_none());
+
+    let re = RegExp::new_tagged("a(b)?(c)?").unwrap();
+    let caps = re.captures_at("xac", 1).unwrap();
+    assert_eq!(1..3, caps.get(0).unwrap().range());
+    assert!(caps.get(1).is_none());
+    assert_eq!(2..3, caps.get(2).unwrap().range());
+}
+
+#[test]
+fn test_tagged_priority() {
+    let re = RegExp::new_tagged("(a)b|a(b)").unwrap();
+    let caps = re.captures("ab").unwrap();
+    assert_eq!(0..1, caps.get(1).unwrap().range());
+    assert!(caps.get(2).is_none());
+
+    let re = RegExp::new_tagged("(a)|(ab)").unwrap();
+    let caps = re.captures("ab").unwrap();
+    assert!(caps.get(1).is_none());
+    assert_eq!(0..2, caps.get(2).unwrap().range());
+
+    let re = RegExp::new_tagged("((a*)*)b").unwrap();
+    assert_eq!(0..4, re.find("aaab").unwrap().range());
+}
+
+#[test]
+fn test_tagged_matches_pikevm() {
+    let exprs = [
+        "(a|b)*(abb)",
+        "(a*)(a*)",
+        "(a|ab)(c|bcd)(d*)",
+        "((a)|b)*",
+        "(a?)((ab)?)(b?)",
+        r"(\w+)@(\w+)\.(\w+)",
+        "(x(y)?)+z?",
+        "((a*)*)b",
+        "a|(b)|(c)",
+    ];
+    let inputs = [
+        "",
+        "a",
+        "ab",
+        "abb",
+        "aababb",
+        "abcd",
+        "aaa",
+        "abab",
+        "xyxxy",
+        "xyz",
+        "me@host.org",
+        "c",
+    ];
+
+    for expr in &exprs {
+        let pikevm_re = RegExp::new_pikevm(expr).unwrap();
+        let tagged_re = RegExp::new_tagged(expr).unwrap();
+        for input in &inputs {
+            for start in 0..=input.chars().count() {
+                assert_eq!(
+                    groups(pikevm_re.captures_at(input, start)),
+                    groups(tagged_re.captures_at(input, start)),
+                    r#""{}" on "{}" at {}"#,
+                    expr,
+                    input,
+                    start
+                );
+                assert_eq!(
+                    pikevm_re.find_shortest_at(input, start).map(|m| m.range()),
+                    tagged_re.find_shortest_at(input, start).map(|m| m.range()),
+                    r#""{}" on "{}" at {}"#,
+                    expr,
+                    input,
+                    start
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_tagged_leftmost_first() {
+    // The match of highest priority is found in a single pass, however far into the input.
+    let re = RegExp::new_tagged("(a|ab)(c|bcd)").unwrap();
+    let input = format!("{}abcd", "ab".repeat(1000));
+    let budget = Budget::steps(input.len());
+    let found = re
+        .find_kind_at_within(&input, 0, MatchKind::LeftmostFirst, &budget)
+        .unwrap();
+    assert_eq!(Some(2000..2004), found.map(|m| m.range()));
+
+    // The states are cleared when there are too many, without changing the matches.
+    for expr in &["(a|ab)(c|bcd)", "(x(y)?)+z?", "a|(b)|(c)"] {
+        let limits = Limits {
+            dfa_states: Some(TaggedDFA::new(expr).unwrap().total_states()),
+            ..Limits::none()
+        };
+        let pikevm_re = RegExp::new_pikevm(expr).unwrap();
+        let tagged_re = RegExp::new_tagged_with_limits(expr, limits).unwrap();
+        for input in &["abcd", "xxabcd", "ababcbcd", "zxyxxyz", "xcba", ""] {
+            assert_eq!(
+                pikevm_re
+                    .find_kind(input, MatchKind::LeftmostFirst)
+                    .unwrap()
+                    .map(|m| m.range()),
+                tagged_re
+                    .find_kind(input, MatchKind::LeftmostFirst)
+                    .unwrap()
+                    .map(|m| m.range()),
+                r#""{}" on "{}""#,
+                expr,
+                input
+            );
+        }
+    }
+}
+
+#[test]
+fn test_tagged_size() {
+    // Registers are renumbered in each state, so a repeated group does not need a new state or
+    // register for every iteration.
+    let dfa = TaggedDFA::new("(a|b)*c").unwrap();
+    assert!(dfa.total_states() <= 6);
+    assert!(dfa.registers() <= 4);
+
+    let re = RegExp::new_tagged("(a|b)*c").unwrap();
+    assert!(re.captures(&"ab".repeat(1000)).is_none());
+    let input = format!("{}c", "ab".repeat(1000));
+    let caps = re.captures(&input).unwrap();
+    assert_eq!(1999..2000, caps.get(1).unwrap().range());
+}